@@ -0,0 +1,49 @@
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use phppp::parser;
+use rayon::prelude::*;
+use tree_sitter::Parser;
+
+/// `examples/big.php` repeated to the sizes we see in real projects,
+/// from a single file up to a 5k-line controller.
+fn inputs() -> Vec<(usize, String)> {
+    let base = std::fs::read_to_string("examples/big.php").unwrap();
+    [1, 10, 40]
+        .into_iter()
+        .map(|n| (n, format!("<?php\n{}", base.replace("<?php", "").repeat(n))))
+        .collect()
+}
+
+fn single(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parse");
+    for (n, src) in inputs() {
+        group.throughput(Throughput::Bytes(src.len() as u64));
+        group.bench_with_input(BenchmarkId::new("thread_local", n), &src, |b, src| {
+            b.iter(|| parser::parse_php(src))
+        });
+        group.bench_with_input(BenchmarkId::new("fresh_parser", n), &src, |b, src| {
+            b.iter(|| {
+                let mut p = Parser::new();
+                p.set_language(&tree_sitter_php::LANGUAGE_PHP.into())
+                    .unwrap();
+                p.parse(src, None).unwrap()
+            })
+        });
+    }
+    group.finish();
+}
+
+fn parallel(c: &mut Criterion) {
+    let (_, src) = inputs().swap_remove(1);
+    let files = vec![src; 256];
+    let bytes: usize = files.iter().map(String::len).sum();
+    let mut group = c.benchmark_group("parse_parallel");
+    group.throughput(Throughput::Bytes(bytes as u64));
+    group.sample_size(20);
+    group.bench_function("256_files", |b| {
+        b.iter(|| files.par_iter().map(|src| parser::parse_php(src)).count())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, single, parallel);
+criterion_main!(benches);
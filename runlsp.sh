@@ -101,6 +101,13 @@ function activate(context) {
     }
   );
 
+  context.subscriptions.push(
+    vscode.workspace.registerTextDocumentContentProvider("phppp-stub", {
+      provideTextDocumentContent: (uri) =>
+        client.sendRequest("phppp/stubSource", { uri: uri.toString() })
+    })
+  );
+
   context.subscriptions.push(
     vscode.commands.registerCommand("phppp.restart", async () => {
       await client.stop();
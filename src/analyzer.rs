@@ -1,12 +1,605 @@
-use crate::indexer::Symbol;
-use rayon::prelude::*;
 use std::collections::HashMap;
+use std::fmt;
 
-pub fn resolve_types_parallel(symbols: &HashMap<String, Symbol>) {
-    tracing::debug!("Resolving types for {} symbols", symbols.len());
-    symbols.par_iter().for_each(|(_, s)| {
-        // Dummy resolution logic
-        let _ = format!("Resolved symbol: {}", s.name);
-    });
-    tracing::debug!("Type resolution completed");
+use dashmap::DashMap;
+use tower_lsp::lsp_types::Url;
+use tree_sitter::Node;
+
+use crate::cache::content_hash;
+use crate::indexer::{GlobalIndex, Symbol};
+use crate::phpdoc::{self, DocBlock};
+use crate::references::variable_scope;
+use crate::resolver::{self, DocumentContext, ResolvedSymbol};
+
+/// Type names that don't name a class.
+const SCALARS: &[&str] = &[
+    "int", "float", "string", "bool", "true", "false", "null", "void", "never", "mixed", "array",
+    "iterable", "callable", "object", "resource",
+];
+
+/// How many expressions deep inference follows assignments and calls.
+const MAX_DEPTH: usize = 32;
+
+/// The possible types of an expression: fully qualified class names and
+/// scalar type names. Empty when nothing is known.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Type(Vec<String>);
+
+impl Type {
+    /// A type written the way [`resolver::declared_type`] produces it, such
+    /// as `?App\User` or `App\A|(App\B&App\C)`.
+    pub fn parse(declared: &str) -> Self {
+        let mut ty = Type::default();
+        if declared.starts_with('?') {
+            ty.add("null");
+        }
+        for name in declared.split(['|', '&', '(', ')', '?']) {
+            ty.add(name.trim());
+        }
+        ty
+    }
+
+    pub fn named(name: &str) -> Self {
+        let mut ty = Type::default();
+        ty.add(name);
+        ty
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// The class names among the possible types.
+    pub fn classes(&self) -> impl Iterator<Item = &str> {
+        self.0
+            .iter()
+            .map(String::as_str)
+            .filter(|name| !SCALARS.contains(&name.to_ascii_lowercase().as_str()))
+    }
+
+    fn add(&mut self, name: &str) {
+        let name = name.trim_start_matches('\\');
+        if !name.is_empty() && !self.0.iter().any(|n| n.eq_ignore_ascii_case(name)) {
+            self.0.push(name.to_string());
+        }
+    }
+
+    fn union(mut self, other: Type) -> Self {
+        for name in &other.0 {
+            self.add(name);
+        }
+        self
+    }
+}
+
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.0.is_empty() {
+            return f.write_str("mixed");
+        }
+        f.write_str(&self.0.join("|"))
+    }
+}
+
+/// Types inferred in each document, reused until the document changes or
+/// anything the inference looked up in the index is redefined.
+#[derive(Debug, Default)]
+pub struct TypeCache {
+    documents: DashMap<Url, DocumentTypes>,
+}
+
+#[derive(Debug, Default)]
+struct DocumentTypes {
+    /// Hash of the text the types were inferred from.
+    hash: u64,
+    /// The index names consulted, with their revision at the time.
+    deps: HashMap<String, u64>,
+    /// Inferred types keyed by expression start, end and kind.
+    types: HashMap<(usize, usize, u16), Type>,
+}
+
+impl TypeCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Drop the types inferred for a document, once it is closed.
+    pub fn forget(&self, uri: &Url) {
+        self.documents.remove(uri);
+    }
+
+    /// The type of the expression `node` in `doc`.
+    pub fn type_of(&self, doc: &DocumentContext, node: Node, global: &GlobalIndex) -> Type {
+        self.with_document(doc, global, |inference| inference.expr(node))
+    }
+
+    /// Resolve the member named by `node` in `$obj->name`, `$obj->name()` or
+    /// `$obj?->name` through the inferred type of `$obj`.
+    pub fn resolve_member(
+        &self,
+        doc: &DocumentContext,
+        node: Node,
+        global: &GlobalIndex,
+    ) -> Option<ResolvedSymbol> {
+        let access = node.parent()?;
+        if access.child_by_field_name("name")? != node {
+            return None;
+        }
+        let member = match access.kind() {
+            "member_call_expression" | "nullsafe_member_call_expression" => text(node, doc.src),
+            "member_access_expression" | "nullsafe_member_access_expression" => {
+                format!("${}", text(node, doc.src))
+            }
+            _ => return None,
+        };
+        let object = access.child_by_field_name("object")?;
+        self.with_document(doc, global, |inference| {
+            let receiver = inference.expr(object);
+            receiver
+                .classes()
+                .find_map(|class| inference.lookup_member(class, &member))
+        })
+    }
+
+    /// The members reachable through the expression `object`, by name, for
+    /// completing `$object->` or `Class::`. Members are looked up on every
+    /// class the expression may be an instance of and the types those
+    /// inherit from; a member declared by several of them comes from the
+    /// closest one.
+    pub fn members(
+        &self,
+        doc: &DocumentContext,
+        object: Node,
+        global: &GlobalIndex,
+    ) -> Vec<(String, Symbol)> {
+        let classes: Vec<String> = match object.kind() {
+            "name" | "qualified_name" | "relative_scope" => {
+                resolver::scope_class(&text(object, doc.src), object.start_position(), doc)
+                    .into_iter()
+                    .collect()
+            }
+            _ => {
+                let ty = self.type_of(doc, object, global);
+                ty.classes().map(str::to_string).collect()
+            }
+        };
+        let mut chain: Vec<String> = Vec::new();
+        for class in &classes {
+            for ancestor in resolver::class_chain(class, doc.symbols, global, &mut |_| {}) {
+                if !chain.iter().any(|c| c.eq_ignore_ascii_case(&ancestor)) {
+                    chain.push(ancestor);
+                }
+            }
+        }
+        // member name to its closest declaration
+        let mut closest: HashMap<String, String> = HashMap::new();
+        for class in &chain {
+            for name in global.members_of(class) {
+                if let Some((_, member)) = name.split_once("::") {
+                    closest.entry(member.to_string()).or_insert(name);
+                }
+            }
+        }
+        let mut members: Vec<(String, Symbol)> = closest
+            .into_iter()
+            .filter_map(|(member, name)| {
+                let (_, sym) = global.definitions(&name).into_iter().next()?;
+                Some((member, sym))
+            })
+            .collect();
+        members.sort_by(|a, b| a.0.cmp(&b.0));
+        members
+    }
+
+    fn with_document<T>(
+        &self,
+        doc: &DocumentContext,
+        global: &GlobalIndex,
+        f: impl FnOnce(&mut Inference) -> T,
+    ) -> T {
+        let hash = content_hash(doc.src.as_bytes());
+        let mut state = self
+            .documents
+            .remove(doc.uri)
+            .map(|(_, state)| state)
+            .filter(|state| {
+                state.hash == hash
+                    && state
+                        .deps
+                        .iter()
+                        .all(|(name, revision)| global.revision(name) == *revision)
+            })
+            .unwrap_or_else(|| DocumentTypes {
+                hash,
+                ..DocumentTypes::default()
+            });
+        let result = f(&mut Inference {
+            doc,
+            global,
+            state: &mut state,
+            depth: 0,
+        });
+        self.documents.insert(doc.uri.clone(), state);
+        result
+    }
+}
+
+struct Inference<'a, 'd> {
+    doc: &'a DocumentContext<'d>,
+    global: &'a GlobalIndex,
+    state: &'a mut DocumentTypes,
+    depth: usize,
+}
+
+impl Inference<'_, '_> {
+    fn expr(&mut self, node: Node) -> Type {
+        let key = (node.start_byte(), node.end_byte(), node.kind_id());
+        if let Some(ty) = self.state.types.get(&key) {
+            return ty.clone();
+        }
+        if self.depth >= MAX_DEPTH {
+            return Type::default();
+        }
+        self.depth += 1;
+        let ty = self.infer(node);
+        self.depth -= 1;
+        self.state.types.insert(key, ty.clone());
+        ty
+    }
+
+    fn infer(&mut self, node: Node) -> Type {
+        let field = |name: &str| node.child_by_field_name(name);
+        match node.kind() {
+            "parenthesized_expression" | "clone_expression" => match node.named_child(0) {
+                Some(inner) => self.expr(inner),
+                None => Type::default(),
+            },
+            "object_creation_expression" => match node.named_child(0) {
+                Some(class)
+                    if matches!(class.kind(), "name" | "qualified_name" | "relative_scope") =>
+                {
+                    self.class_name(class)
+                }
+                _ => Type::default(),
+            },
+            "variable_name" => self.variable(node),
+            "member_call_expression" | "nullsafe_member_call_expression" => {
+                match (field("object"), field("name")) {
+                    (Some(object), Some(name)) => {
+                        let member = text(name, self.doc.src);
+                        self.member_type(object, &member)
+                    }
+                    _ => Type::default(),
+                }
+            }
+            "member_access_expression" | "nullsafe_member_access_expression" => {
+                match (field("object"), field("name")) {
+                    (Some(object), Some(name)) => {
+                        let member = format!("${}", text(name, self.doc.src));
+                        self.member_type(object, &member)
+                    }
+                    _ => Type::default(),
+                }
+            }
+            "scoped_call_expression" | "scoped_property_access_expression" => {
+                match (field("scope"), field("name")) {
+                    (Some(scope), Some(name)) => {
+                        let member = text(name, self.doc.src);
+                        self.static_member_type(scope, &member)
+                    }
+                    _ => Type::default(),
+                }
+            }
+            "class_constant_access_expression" => {
+                let count = node.named_child_count();
+                match (
+                    node.named_child(0),
+                    node.named_child(count.saturating_sub(1)),
+                ) {
+                    (Some(scope), Some(name)) if count >= 2 => {
+                        let member = text(name, self.doc.src);
+                        if member.eq_ignore_ascii_case("class") {
+                            return Type::named("string");
+                        }
+                        self.static_member_type(scope, &member)
+                    }
+                    _ => Type::default(),
+                }
+            }
+            "function_call_expression" => match field("function") {
+                Some(name) if matches!(name.kind(), "name" | "qualified_name") => {
+                    self.function_type(name)
+                }
+                _ => Type::default(),
+            },
+            "assignment_expression" | "reference_assignment_expression" => match field("right") {
+                Some(right) => self.expr(right),
+                None => Type::default(),
+            },
+            "conditional_expression" => {
+                let then = field("body").or_else(|| field("condition"));
+                let then = then.map(|n| self.expr(n)).unwrap_or_default();
+                let otherwise = field("alternative")
+                    .map(|n| self.expr(n))
+                    .unwrap_or_default();
+                then.union(otherwise)
+            }
+            "binary_expression" => {
+                let operator = field("operator").map(|op| op.kind());
+                match (operator, field("left"), field("right")) {
+                    (Some("??"), Some(left), Some(right)) => {
+                        let left = self.expr(left);
+                        left.union(self.expr(right))
+                    }
+                    _ => Type::default(),
+                }
+            }
+            "string" | "encapsed_string" | "heredoc" | "nowdoc" => Type::named("string"),
+            "integer" => Type::named("int"),
+            "float" => Type::named("float"),
+            "boolean" => Type::named("bool"),
+            "null" => Type::named("null"),
+            "array_creation_expression" => Type::named("array"),
+            _ => Type::default(),
+        }
+    }
+
+    /// The class a `name`, `qualified_name` or `self`/`static`/`parent`
+    /// node stands for.
+    fn class_name(&self, node: Node) -> Type {
+        resolver::scope_class(&text(node, self.doc.src), node.start_position(), self.doc)
+            .map(|class| Type::named(&class))
+            .unwrap_or_default()
+    }
+
+    fn variable(&mut self, node: Node) -> Type {
+        let name = text(node, self.doc.src);
+        if name == "$this" {
+            return resolver::scope_class("static", node.start_position(), self.doc)
+                .map(|class| Type::named(&class))
+                .unwrap_or_default();
+        }
+        if let Some(narrowed) = self.narrowed(node, &name) {
+            return narrowed;
+        }
+        if let Some(parent) = node.parent()
+            && parent.kind() == "assignment_expression"
+            && parent.child_by_field_name("left") == Some(node)
+        {
+            return self.assigned(parent, &name);
+        }
+        let Some(scope) = variable_scope(node, self.doc.src) else {
+            return Type::default();
+        };
+        // the latest assignment, `catch` or `@var` comment before the use
+        // wins over the parameter declaration
+        let mut latest: Option<Node> = None;
+        walk_scope(scope, &mut |n| {
+            if n.end_byte() > node.start_byte() {
+                return;
+            }
+            let binds = match n.kind() {
+                "assignment_expression" => n
+                    .child_by_field_name("left")
+                    .is_some_and(|left| text(left, self.doc.src) == name),
+                "catch_clause" => n
+                    .child_by_field_name("name")
+                    .is_some_and(|var| text(var, self.doc.src) == name),
+                "comment" => {
+                    let comment = text(n, self.doc.src);
+                    comment.starts_with("/**")
+                        && comment.contains(&name)
+                        && phpdoc::parse(&comment)
+                            .vars
+                            .iter()
+                            .any(|var| var.name.as_deref() == Some(name.as_str()))
+                }
+                _ => false,
+            };
+            if binds && latest.is_none_or(|l| l.start_byte() < n.start_byte()) {
+                latest = Some(n);
+            }
+        });
+        if let Some(binding) = latest {
+            return match binding.kind() {
+                "catch_clause" => self.declared(binding),
+                "comment" => {
+                    let doc = phpdoc::parse(&text(binding, self.doc.src));
+                    self.documented(doc, binding, &name, false)
+                        .unwrap_or_default()
+                }
+                _ => self.assigned(binding, &name),
+            };
+        }
+        let params = scope.child_by_field_name("parameters");
+        let param = (0..params.map_or(0, |p| p.named_child_count()))
+            .filter_map(|i| params?.named_child(i))
+            .find(|p| {
+                p.child_by_field_name("name")
+                    .is_some_and(|var| text(var, self.doc.src) == name)
+            });
+        let Some(param) = param else {
+            return Type::default();
+        };
+        phpdoc::doc_before(scope, self.doc.src)
+            .and_then(|doc| self.documented(doc, scope, &name, true))
+            .unwrap_or_else(|| self.declared(param))
+    }
+
+    /// The type of `name` assigned by `assignment`: the `@var` type the
+    /// docblock before the statement gives it, or the type of the value.
+    fn assigned(&mut self, assignment: Node, name: &str) -> Type {
+        if let Some(statement) = assignment.parent()
+            && statement.kind() == "expression_statement"
+            && let Some(doc) = phpdoc::doc_before(statement, self.doc.src)
+            && let Some(ty) = self.documented(doc, statement, name, false)
+        {
+            return ty;
+        }
+        assignment
+            .child_by_field_name("right")
+            .map(|right| self.expr(right))
+            .unwrap_or_default()
+    }
+
+    /// The type the docblock `doc` written at `node` gives `name` with a
+    /// `@var` tag, or with a `@param` tag for parameters.
+    fn documented(&self, mut doc: DocBlock, node: Node, name: &str, param: bool) -> Option<Type> {
+        let point = node.start_position();
+        let class = resolver::scope_class("self", point, self.doc);
+        resolver::qualify_doc(&mut doc, &[], self.doc.imports, point, class.as_deref());
+        let tag = if param {
+            doc.param(name)
+        } else {
+            doc.var(name)
+        };
+        doc.hint(tag, &[]).map(|hint| Type::parse(&hint))
+    }
+
+    /// The class `name` is narrowed to by an enclosing
+    /// `if ($name instanceof Foo)`.
+    fn narrowed(&self, node: Node, name: &str) -> Option<Type> {
+        let mut current = node;
+        while let Some(parent) = current.parent() {
+            if matches!(
+                parent.kind(),
+                "function_definition" | "method_declaration" | "anonymous_function"
+            ) {
+                return None;
+            }
+            if parent.kind() == "if_statement"
+                && parent.child_by_field_name("body") == Some(current)
+                && let Some(condition) = parent.child_by_field_name("condition")
+                && let Some(class) = self.instanceof(condition, name)
+            {
+                return Some(class);
+            }
+            current = parent;
+        }
+        None
+    }
+
+    /// The class checked by a non-negated `$name instanceof Foo` in
+    /// `condition`, looking through `&&` and parentheses.
+    fn instanceof(&self, condition: Node, name: &str) -> Option<Type> {
+        match condition.kind() {
+            "parenthesized_expression" => self.instanceof(condition.named_child(0)?, name),
+            "binary_expression" => {
+                let operator = condition.child_by_field_name("operator")?;
+                let left = condition.child_by_field_name("left")?;
+                let right = condition.child_by_field_name("right")?;
+                match operator.kind() {
+                    "instanceof"
+                        if left.kind() == "variable_name"
+                            && text(left, self.doc.src) == name
+                            && matches!(right.kind(), "name" | "qualified_name") =>
+                    {
+                        Some(self.class_name(right))
+                    }
+                    "&&" | "and" => self
+                        .instanceof(left, name)
+                        .or_else(|| self.instanceof(right, name)),
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// The declared type of a parameter or `catch` clause.
+    fn declared(&self, node: Node) -> Type {
+        let Some(ty) = node.child_by_field_name("type") else {
+            return Type::default();
+        };
+        let class = resolver::scope_class("self", node.start_position(), self.doc);
+        Type::parse(&resolver::declared_type(
+            self.doc.src,
+            ty,
+            self.doc.imports,
+            class.as_deref(),
+        ))
+    }
+
+    fn member_type(&mut self, object: Node, member: &str) -> Type {
+        let receiver = self.expr(object);
+        let mut ty = Type::default();
+        for class in receiver.classes() {
+            if let Some(sym) = self.lookup_member(class, member) {
+                ty = ty.union(hint(&sym));
+            }
+        }
+        ty
+    }
+
+    fn static_member_type(&mut self, scope: Node, member: &str) -> Type {
+        let class = self.class_name(scope);
+        let mut ty = Type::default();
+        for class in class.classes() {
+            if let Some(sym) = self.lookup_member(class, member) {
+                ty = ty.union(hint(&sym));
+            }
+        }
+        ty
+    }
+
+    fn function_type(&mut self, name: Node) -> Type {
+        let text = text(name, self.doc.src);
+        for candidate in resolver::qualified_candidates(&text, name.start_position(), self.doc) {
+            self.depend(&candidate);
+            if let Some(sym) = resolver::lookup(&candidate, self.doc.symbols, self.global) {
+                return hint(&sym);
+            }
+        }
+        Type::default()
+    }
+
+    fn lookup_member(&mut self, class: &str, member: &str) -> Option<ResolvedSymbol> {
+        let global = self.global;
+        let deps = &mut self.state.deps;
+        resolver::lookup_member(class, member, self.doc.symbols, global, &mut |name| {
+            deps.insert(name.to_string(), global.revision(name));
+        })
+    }
+
+    fn depend(&mut self, name: &str) {
+        self.state
+            .deps
+            .insert(name.to_string(), self.global.revision(name));
+    }
+}
+
+fn hint(sym: &ResolvedSymbol) -> Type {
+    sym.type_hint
+        .as_deref()
+        .map(Type::parse)
+        .unwrap_or_default()
+}
+
+/// Visit the nodes of a variable scope, without descending into the
+/// functions and classes declared in it.
+fn walk_scope<'t>(scope: Node<'t>, f: &mut impl FnMut(Node<'t>)) {
+    for i in 0..scope.named_child_count() {
+        let Some(child) = scope.named_child(i) else {
+            continue;
+        };
+        if matches!(
+            child.kind(),
+            "function_definition"
+                | "method_declaration"
+                | "anonymous_function"
+                | "class_declaration"
+                | "interface_declaration"
+                | "trait_declaration"
+                | "enum_declaration"
+                | "anonymous_class"
+        ) {
+            continue;
+        }
+        f(child);
+        walk_scope(child, f);
+    }
+}
+
+fn text(node: Node, src: &str) -> String {
+    node.utf8_text(src.as_bytes()).unwrap_or("").to_string()
 }
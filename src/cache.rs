@@ -0,0 +1,168 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use serde::{Deserialize, Serialize};
+
+use crate::indexer::{FileSymbols, Layer};
+
+/// Bumped whenever the cache layout or the extracted symbols change, so
+/// caches written by other phppp builds are discarded instead of misread.
+pub const FORMAT_VERSION: u32 = 5;
+
+/// What a file looked like when it was indexed: its modification time and
+/// size for a cheap check, plus a content hash for when only the mtime moved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileStamp {
+    pub mtime: u64,
+    pub len: u64,
+    pub hash: u64,
+    pub layer: Layer,
+}
+
+impl FileStamp {
+    /// The mtime and size of `path`, with the hash left to fill in.
+    pub fn of(path: &Path, layer: Layer) -> std::io::Result<Self> {
+        let meta = fs::metadata(path)?;
+        let mtime = meta
+            .modified()?
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or(0);
+        Ok(Self {
+            mtime,
+            len: meta.len(),
+            hash: 0,
+            layer,
+        })
+    }
+
+    /// Whether `self` can be trusted without reading the file again.
+    pub fn unchanged(&self, cached: &FileStamp) -> bool {
+        self.mtime == cached.mtime && self.len == cached.len && self.layer == cached.layer
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedFile {
+    pub stamp: FileStamp,
+    pub symbols: FileSymbols,
+}
+
+/// Symbols of previously indexed files, persisted between server runs.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct IndexCache {
+    version: u32,
+    phppp: String,
+    files: HashMap<PathBuf, CachedFile>,
+}
+
+impl Default for IndexCache {
+    fn default() -> Self {
+        Self {
+            version: FORMAT_VERSION,
+            phppp: env!("CARGO_PKG_VERSION").to_string(),
+            files: HashMap::new(),
+        }
+    }
+}
+
+impl IndexCache {
+    /// Read the cache at `path`. A missing, unreadable or outdated cache
+    /// yields an empty one.
+    pub fn load(path: &Path) -> Self {
+        let Ok(text) = fs::read(path) else {
+            return Self::default();
+        };
+        match serde_json::from_slice::<IndexCache>(&text) {
+            Ok(cache) if cache.is_current() => {
+                tracing::debug!(
+                    "loaded {} cached files from {}",
+                    cache.len(),
+                    path.display()
+                );
+                cache
+            }
+            Ok(cache) => {
+                tracing::info!(
+                    "discarding index cache written by phppp {} (format {})",
+                    cache.phppp,
+                    cache.version
+                );
+                Self::default()
+            }
+            Err(e) => {
+                tracing::warn!(
+                    "discarding unreadable index cache {}: {}",
+                    path.display(),
+                    e
+                );
+                Self::default()
+            }
+        }
+    }
+
+    /// Write the cache to `path`, replacing any previous one atomically.
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let tmp = path.with_extension("tmp");
+        let data = serde_json::to_vec(self)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+        fs::write(&tmp, data)?;
+        fs::rename(tmp, path)
+    }
+
+    /// Build a cache from files as a scan indexed them. The live index
+    /// isn't used: it may hold unsaved editor buffers that don't match the
+    /// files' stamps.
+    pub fn from_files(files: impl IntoIterator<Item = (PathBuf, CachedFile)>) -> Self {
+        Self {
+            files: files.into_iter().collect(),
+            ..Self::default()
+        }
+    }
+
+    pub fn get(&self, path: &Path) -> Option<&CachedFile> {
+        self.files.get(path)
+    }
+
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    fn is_current(&self) -> bool {
+        self.version == FORMAT_VERSION && self.phppp == env!("CARGO_PKG_VERSION")
+    }
+}
+
+/// Where the index cache of the workspace at `root` lives: under
+/// `$XDG_CACHE_HOME/phppp` (or `~/.cache/phppp`), falling back to
+/// `.phppp/cache` inside the workspace when no cache home is known.
+pub fn cache_path(root: &Path) -> PathBuf {
+    let home = std::env::var_os("XDG_CACHE_HOME")
+        .filter(|v| !v.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|h| PathBuf::from(h).join(".cache")));
+    match home {
+        Some(home) => home.join("phppp").join(format!(
+            "{:016x}.json",
+            content_hash(root.as_os_str().as_encoded_bytes())
+        )),
+        None => root.join(".phppp").join("cache").join("index.json"),
+    }
+}
+
+/// 64-bit FNV-1a, which unlike `DefaultHasher` is stable across Rust
+/// releases and so safe to persist.
+pub fn content_hash(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, b| {
+        (hash ^ *b as u64).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
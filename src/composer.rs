@@ -1,29 +1,247 @@
 use serde::Deserialize;
 use std::collections::HashMap;
 use std::fs;
-use std::path::Path;
+use std::path::{Component, Path, PathBuf};
 
 #[derive(Deserialize)]
 struct Composer {
     #[serde(default)]
-    autoload: Autoload,
+    autoload: RawAutoload,
+    #[serde(rename = "autoload-dev", default)]
+    autoload_dev: RawAutoload,
+    #[serde(default)]
+    config: ComposerConfig,
 }
 
+/// An `autoload` section as written in JSON, with paths relative to the
+/// package root.
 #[derive(Deserialize, Default)]
-struct Autoload {
+struct RawAutoload {
     #[serde(rename = "psr-4", default)]
-    psr4: HashMap<String, String>,
+    psr4: HashMap<String, OneOrMany>,
+    #[serde(rename = "psr-0", default)]
+    psr0: HashMap<String, OneOrMany>,
+    #[serde(default)]
+    classmap: Vec<String>,
+    #[serde(default)]
+    files: Vec<String>,
+}
+
+/// PSR mappings may map a prefix to one path or to a list of paths.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum OneOrMany {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl OneOrMany {
+    fn into_vec(self) -> Vec<String> {
+        match self {
+            OneOrMany::One(p) => vec![p],
+            OneOrMany::Many(ps) => ps,
+        }
+    }
+}
+
+#[derive(Deserialize, Default)]
+struct ComposerConfig {
+    #[serde(rename = "vendor-dir")]
+    vendor_dir: Option<String>,
 }
 
-/// Load PSR-4 autoload namespace mappings from a `composer.json` file
-/// located at `root`.
-pub fn load_autoload_paths(root: &Path) -> std::io::Result<HashMap<String, String>> {
+/// A namespace prefix and the directories its classes are loaded from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NamespaceMapping {
+    /// Namespace prefix without a leading backslash, e.g. `App\`. Empty for
+    /// a fallback mapping.
+    pub prefix: String,
+    pub paths: Vec<PathBuf>,
+}
+
+/// Autoload configuration of a project or package with absolute paths.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Autoload {
+    pub psr4: Vec<NamespaceMapping>,
+    pub psr0: Vec<NamespaceMapping>,
+    pub classmap: Vec<PathBuf>,
+    pub files: Vec<PathBuf>,
+}
+
+impl Autoload {
+    fn from_raw(raw: RawAutoload, base: &Path) -> Self {
+        let mut out = Autoload::default();
+        out.extend(raw, base);
+        out
+    }
+
+    fn extend(&mut self, raw: RawAutoload, base: &Path) {
+        let mappings = |map: HashMap<String, OneOrMany>| {
+            map.into_iter().map(|(prefix, paths)| NamespaceMapping {
+                prefix: prefix.trim_start_matches('\\').to_string(),
+                paths: paths
+                    .into_vec()
+                    .iter()
+                    .map(|p| normalize(&base.join(p)))
+                    .collect(),
+            })
+        };
+        self.psr4.extend(mappings(raw.psr4));
+        self.psr0.extend(mappings(raw.psr0));
+        self.classmap
+            .extend(raw.classmap.iter().map(|p| normalize(&base.join(p))));
+        self.files
+            .extend(raw.files.iter().map(|p| normalize(&base.join(p))));
+        // longest prefix first so lookups find the most specific mapping
+        self.psr4.sort_by_key(|m| std::cmp::Reverse(m.prefix.len()));
+        self.psr0.sort_by_key(|m| std::cmp::Reverse(m.prefix.len()));
+    }
+
+    /// Every file and directory the configuration covers.
+    pub fn paths(&self) -> Vec<PathBuf> {
+        self.psr4
+            .iter()
+            .chain(&self.psr0)
+            .flat_map(|m| m.paths.iter().cloned())
+            .chain(self.classmap.iter().cloned())
+            .chain(self.files.iter().cloned())
+            .collect()
+    }
+
+    /// The files PSR-4 and PSR-0 autoloading would load the class `fqn`
+    /// from, most specific prefix first.
+    pub fn class_candidates(&self, fqn: &str) -> Vec<PathBuf> {
+        let fqn = fqn.trim_start_matches('\\');
+        let mut out = Vec::new();
+        for mapping in &self.psr4 {
+            if let Some(rest) = fqn.strip_prefix(mapping.prefix.as_str()) {
+                for dir in &mapping.paths {
+                    let mut path = dir.clone();
+                    path.extend(rest.split('\\'));
+                    path.set_extension("php");
+                    out.push(path);
+                }
+            }
+        }
+        for mapping in &self.psr0 {
+            if fqn.starts_with(mapping.prefix.as_str()) {
+                // PSR-0 keeps the prefix and maps `_` in the class name to
+                // directory separators
+                let (namespace, class) = fqn.rsplit_once('\\').unwrap_or(("", fqn));
+                for dir in &mapping.paths {
+                    let mut path = dir.clone();
+                    path.extend(namespace.split('\\').filter(|s| !s.is_empty()));
+                    path.extend(class.split('_'));
+                    path.set_extension("php");
+                    out.push(path);
+                }
+            }
+        }
+        out
+    }
+}
+
+fn read_composer(root: &Path) -> std::io::Result<Option<Composer>> {
     let path = root.join("composer.json");
     if !path.exists() {
-        return Ok(HashMap::new());
+        return Ok(None);
     }
     let data = fs::read_to_string(path)?;
     let composer: Composer = serde_json::from_str(&data)
         .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
-    Ok(composer.autoload.psr4)
+    Ok(Some(composer))
+}
+
+/// Load the `autoload` and `autoload-dev` configuration of the
+/// `composer.json` file located at `root`.
+pub fn load_autoload(root: &Path) -> std::io::Result<Autoload> {
+    let Some(composer) = read_composer(root)? else {
+        return Ok(Autoload::default());
+    };
+    let mut autoload = Autoload::from_raw(composer.autoload, root);
+    autoload.extend(composer.autoload_dev, root);
+    Ok(autoload)
+}
+
+/// Return the vendor directory of the project at `root`, honouring the
+/// `config.vendor-dir` setting of `composer.json`.
+pub fn vendor_dir(root: &Path) -> PathBuf {
+    let configured = read_composer(root)
+        .ok()
+        .flatten()
+        .and_then(|c| c.config.vendor_dir);
+    root.join(configured.as_deref().unwrap_or("vendor"))
+}
+
+/// A dependency installed by Composer.
+#[derive(Debug, Clone)]
+pub struct InstalledPackage {
+    pub name: String,
+    pub install_path: PathBuf,
+    pub autoload: Autoload,
+}
+
+#[derive(Deserialize)]
+struct InstalledEntry {
+    name: String,
+    #[serde(rename = "install-path")]
+    install_path: Option<String>,
+    #[serde(default)]
+    autoload: RawAutoload,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum InstalledJson {
+    /// Composer 2 wraps the package list in an object.
+    V2 { packages: Vec<InstalledEntry> },
+    /// Composer 1 stores a bare package list.
+    V1(Vec<InstalledEntry>),
+}
+
+/// Load the packages listed in `vendor/composer/installed.json`. Returns
+/// `Ok(None)` when dependencies have not been installed.
+pub fn load_installed_packages(vendor: &Path) -> std::io::Result<Option<Vec<InstalledPackage>>> {
+    let path = vendor.join("composer").join("installed.json");
+    if !path.exists() {
+        return Ok(None);
+    }
+    let data = fs::read_to_string(path)?;
+    let installed: InstalledJson = serde_json::from_str(&data)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+    let entries = match installed {
+        InstalledJson::V2 { packages } => packages,
+        InstalledJson::V1(packages) => packages,
+    };
+    Ok(Some(
+        entries
+            .into_iter()
+            .map(|entry| {
+                let install_path = match &entry.install_path {
+                    Some(p) => normalize(&vendor.join("composer").join(p)),
+                    None => vendor.join(&entry.name),
+                };
+                InstalledPackage {
+                    autoload: Autoload::from_raw(entry.autoload, &install_path),
+                    name: entry.name,
+                    install_path,
+                }
+            })
+            .collect(),
+    ))
+}
+
+/// Lexically resolve `.` and `..` components so paths map onto stable URIs.
+fn normalize(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                out.pop();
+            }
+            other => out.push(other),
+        }
+    }
+    out
 }
@@ -6,12 +6,48 @@ use std::path::Path;
 pub struct Config {
     #[serde(default)]
     pub enable_laravel: bool,
+    /// Bundled PHP extension stubs to index. Defaults to every bundled
+    /// extension.
+    #[serde(default = "crate::stubs::extensions")]
+    pub stubs: Vec<String>,
+    /// Persist the workspace index between runs so only changed files are
+    /// parsed on startup.
+    #[serde(default = "default_cache")]
+    pub cache: bool,
+    /// Globs, relative to the workspace root, that project files must match
+    /// to be indexed. Empty means every file.
+    #[serde(default)]
+    pub include: Vec<String>,
+    /// Globs, relative to the workspace root, of files and directories to
+    /// leave out of the index.
+    #[serde(default)]
+    pub exclude: Vec<String>,
+    /// File extensions indexed as PHP in addition to `php`.
+    #[serde(default)]
+    pub extensions: Vec<String>,
+    /// Skip files ignored by `.gitignore`.
+    #[serde(default = "default_gitignore")]
+    pub gitignore: bool,
+}
+
+fn default_gitignore() -> bool {
+    true
+}
+
+fn default_cache() -> bool {
+    true
 }
 
 impl Default for Config {
     fn default() -> Self {
         Self {
             enable_laravel: false,
+            stubs: crate::stubs::extensions(),
+            cache: default_cache(),
+            include: Vec::new(),
+            exclude: Vec::new(),
+            extensions: Vec::new(),
+            gitignore: default_gitignore(),
         }
     }
 }
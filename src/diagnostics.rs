@@ -0,0 +1,59 @@
+use tower_lsp::lsp_types::{Diagnostic, DiagnosticSeverity};
+use tree_sitter::Node;
+
+use crate::parser::{self, Ast};
+
+/// Longest snippet of offending source quoted in an "unexpected" message.
+const MAX_SNIPPET: usize = 30;
+
+/// Collect syntax errors from the ERROR and MISSING nodes of a parse tree.
+pub fn syntax_errors(src: &str, ast: &Ast) -> Vec<Diagnostic> {
+    let mut out = Vec::new();
+    let root = ast.0.root_node();
+    if root.has_error() {
+        collect(src, root, &mut out);
+    }
+    tracing::debug!("found {} syntax errors", out.len());
+    out
+}
+
+fn collect(src: &str, node: Node, out: &mut Vec<Diagnostic>) {
+    if node.is_missing() {
+        out.push(diagnostic(src, node, format!("missing `{}`", node.kind())));
+        return;
+    }
+    if node.is_error() {
+        out.push(diagnostic(src, node, unexpected_message(src, node)));
+        return;
+    }
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        if child.has_error() {
+            collect(src, child, out);
+        }
+    }
+}
+
+fn unexpected_message(src: &str, node: Node) -> String {
+    let text = node.utf8_text(src.as_bytes()).unwrap_or("").trim();
+    let first_line = text.lines().next().unwrap_or("").trim();
+    if first_line.is_empty() {
+        return "syntax error".to_string();
+    }
+    let snippet: String = first_line.chars().take(MAX_SNIPPET).collect();
+    if snippet.len() < first_line.len() {
+        format!("unexpected `{}…`", snippet)
+    } else {
+        format!("unexpected `{}`", snippet)
+    }
+}
+
+fn diagnostic(src: &str, node: Node, message: String) -> Diagnostic {
+    Diagnostic {
+        range: parser::node_range(src, node),
+        severity: Some(DiagnosticSeverity::ERROR),
+        source: Some("phppp".into()),
+        message,
+        ..Diagnostic::default()
+    }
+}
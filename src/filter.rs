@@ -0,0 +1,159 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use ignore::Match;
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use walkdir::WalkDir;
+
+use crate::config::Config;
+
+/// Decides which files under the workspace root are indexed as project
+/// code, from the `.phppprc` include/exclude globs and extensions and the
+/// workspace's `.gitignore` files. The initial scan and the file watcher
+/// share one filter so they always agree.
+#[derive(Debug)]
+pub struct FileFilter {
+    root: PathBuf,
+    extensions: Vec<String>,
+    include: Option<GlobSet>,
+    exclude: GlobSet,
+    gitignore: bool,
+    /// Parsed `.gitignore` of each directory looked at so far, `None` for
+    /// directories without one.
+    ignores: Mutex<HashMap<PathBuf, Option<Arc<Gitignore>>>>,
+}
+
+impl FileFilter {
+    pub fn new(root: &Path, config: &Config) -> Self {
+        let mut extensions = vec!["php".to_string()];
+        extensions.extend(
+            config
+                .extensions
+                .iter()
+                .map(|e| e.trim_start_matches('.').to_lowercase()),
+        );
+        Self {
+            root: root.to_path_buf(),
+            extensions,
+            include: (!config.include.is_empty()).then(|| glob_set(&config.include)),
+            exclude: glob_set(&config.exclude),
+            gitignore: config.gitignore,
+            ignores: Mutex::default(),
+        }
+    }
+
+    /// Whether `path` has one of the configured PHP file extensions.
+    pub fn is_php(&self, path: &Path) -> bool {
+        path.extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|e| self.extensions.iter().any(|x| x.eq_ignore_ascii_case(e)))
+    }
+
+    /// Whether the file at `path` should be indexed as project code.
+    pub fn accepts(&self, path: &Path) -> bool {
+        if !self.is_php(path) || !self.included(path) || self.skipped(path, false) {
+            return false;
+        }
+        !path
+            .ancestors()
+            .skip(1)
+            .take_while(|dir| *dir != self.root && dir.starts_with(&self.root))
+            .any(|dir| self.skipped(dir, true))
+    }
+
+    /// The files under `dir` that [`accepts`](Self::accepts) allows, without
+    /// descending into excluded or ignored directories.
+    pub fn walk<'a>(&'a self, dir: &Path) -> impl Iterator<Item = PathBuf> + 'a {
+        WalkDir::new(dir)
+            .into_iter()
+            .filter_entry(|e| e.depth() == 0 || !self.skipped(e.path(), e.file_type().is_dir()))
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_file() && self.is_php(e.path()) && self.included(e.path()))
+            .map(|e| e.into_path())
+    }
+
+    /// Drop the cached rules of a `.gitignore` file after it changed.
+    pub fn invalidate(&self, path: &Path) {
+        if path.file_name().is_some_and(|n| n == ".gitignore")
+            && let Some(dir) = path.parent()
+        {
+            self.ignores.lock().unwrap().remove(dir);
+        }
+    }
+
+    fn included(&self, path: &Path) -> bool {
+        match (&self.include, path.strip_prefix(&self.root)) {
+            (Some(include), Ok(rel)) => include.is_match(rel),
+            _ => true,
+        }
+    }
+
+    /// Whether `path` itself, regardless of its parents, is excluded by a
+    /// glob or ignored by git.
+    fn skipped(&self, path: &Path, is_dir: bool) -> bool {
+        let Ok(rel) = path.strip_prefix(&self.root) else {
+            return false;
+        };
+        if is_dir && path.file_name().is_some_and(|n| n == ".git") {
+            return true;
+        }
+        self.exclude.is_match(rel) || (self.gitignore && self.git_ignored(path, is_dir))
+    }
+
+    fn git_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        let mut dirs: Vec<&Path> = path
+            .ancestors()
+            .skip(1)
+            .take_while(|dir| dir.starts_with(&self.root))
+            .collect();
+        // rules in deeper directories take precedence
+        dirs.reverse();
+        let mut ignored = false;
+        for dir in dirs {
+            if let Some(rules) = self.gitignore_in(dir) {
+                match rules.matched(path, is_dir) {
+                    Match::Ignore(_) => ignored = true,
+                    Match::Whitelist(_) => ignored = false,
+                    Match::None => {}
+                }
+            }
+        }
+        ignored
+    }
+
+    fn gitignore_in(&self, dir: &Path) -> Option<Arc<Gitignore>> {
+        let mut ignores = self.ignores.lock().unwrap();
+        ignores
+            .entry(dir.to_path_buf())
+            .or_insert_with(|| {
+                let file = dir.join(".gitignore");
+                if !file.is_file() {
+                    return None;
+                }
+                let mut builder = GitignoreBuilder::new(dir);
+                if let Some(e) = builder.add(&file) {
+                    tracing::warn!("failed to read {}: {}", file.display(), e);
+                }
+                builder.build().ok().map(Arc::new)
+            })
+            .clone()
+    }
+}
+
+fn glob_set(patterns: &[String]) -> GlobSet {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        match Glob::new(pattern.trim_end_matches('/')) {
+            Ok(glob) => {
+                builder.add(glob);
+            }
+            Err(e) => tracing::warn!("ignoring invalid glob {}: {}", pattern, e),
+        }
+    }
+    builder.build().unwrap_or_else(|e| {
+        tracing::warn!("failed to build glob set: {}", e);
+        GlobSet::empty()
+    })
+}
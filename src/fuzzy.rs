@@ -0,0 +1,61 @@
+/// Score how well `query` fuzzily matches `candidate`, or `None` when it
+/// doesn't match at all. Every query character has to appear in order,
+/// ignoring case; matches at the start of a camel-case, `_` or `\` separated
+/// word and runs of consecutive characters score higher, so `UsRe` ranks
+/// `UserRepository` above `UnusedResult`. An uppercase query character only
+/// matches at a word start when one is available.
+pub fn score(query: &str, candidate: &str) -> Option<i32> {
+    let chars: Vec<char> = candidate.chars().collect();
+    let mut score = 0;
+    let mut pos = 0;
+    let mut previous: Option<usize> = None;
+    for q in query.chars() {
+        let found = if q.is_uppercase() {
+            find(&chars, pos, |i| eq(chars[i], q) && is_word_start(&chars, i))
+                .or_else(|| find(&chars, pos, |i| eq(chars[i], q)))
+        } else {
+            find(&chars, pos, |i| eq(chars[i], q))
+        }?;
+        if is_word_start(&chars, found) {
+            score += 10;
+        }
+        match previous {
+            Some(p) if p + 1 == found => score += 5,
+            Some(p) => score -= (found - p - 1).min(5) as i32,
+            None => score -= found.min(10) as i32,
+        }
+        previous = Some(found);
+        pos = found + 1;
+    }
+    if query.len() == candidate.len() && query.eq_ignore_ascii_case(candidate) {
+        score += 50;
+    } else if candidate
+        .get(..query.len())
+        .is_some_and(|head| head.eq_ignore_ascii_case(query))
+    {
+        score += 20;
+    }
+    // prefer shorter names among otherwise equal matches
+    Some(score * 4 - chars.len().min(40) as i32)
+}
+
+fn find(chars: &[char], from: usize, pred: impl Fn(usize) -> bool) -> Option<usize> {
+    (from..chars.len()).find(|&i| pred(i))
+}
+
+fn eq(a: char, b: char) -> bool {
+    a == b || a.to_lowercase().eq(b.to_lowercase())
+}
+
+fn is_word_start(chars: &[char], i: usize) -> bool {
+    let Some(prev) = i.checked_sub(1).map(|p| chars[p]) else {
+        return true;
+    };
+    let c = chars[i];
+    !prev.is_alphanumeric()
+        || (c.is_uppercase() && !prev.is_uppercase())
+        || (c.is_alphabetic() && prev.is_ascii_digit())
+        || (c.is_uppercase()
+            && prev.is_uppercase()
+            && chars.get(i + 1).is_some_and(|n| n.is_lowercase()))
+}
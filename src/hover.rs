@@ -0,0 +1,218 @@
+use std::path::Path;
+
+use tower_lsp::lsp_types::{Location, Url};
+use tree_sitter::Node;
+
+use crate::parser::{self, Ast};
+use crate::phpdoc::{DocBlock, Tag};
+use crate::resolver::ResolvedSymbol;
+
+/// Render the hover of a resolved symbol as Markdown: its declaration as it
+/// appears in `source`, the text and syntax tree of the file defining it,
+/// then its docblock, and last the class or namespace containing it and the
+/// file, relative to `root` when it lies inside.
+pub fn render(sym: &ResolvedSymbol, source: Option<(&str, &Ast)>, root: Option<&Path>) -> String {
+    let signature = source
+        .and_then(|(src, ast)| signature(src, ast, &sym.location))
+        .unwrap_or_else(|| sym.name.clone());
+    let mut sections = vec![format!("```php\n{}\n```", signature)];
+    if let Some(doc) = &sym.doc {
+        sections.extend(docblock(doc));
+    }
+    let mut footer = Vec::new();
+    if let Some(container) = container(&sym.name) {
+        footer.push(format!("in `{}`", container));
+    }
+    footer.push(format!(
+        "defined in `{}`",
+        file_name(&sym.location.uri, root)
+    ));
+    sections.push("---".to_string());
+    sections.push(footer.join(", "));
+    sections.join("\n\n")
+}
+
+/// The hover of a variable: its name and inferred type.
+pub fn variable(name: &str, ty: &str) -> String {
+    format!("```php\n{} {}\n```", ty, name)
+}
+
+/// The declaration at `location` in `src`, parsed as `ast`, on one line,
+/// without its body and attributes:
+/// `public static function find(int $id = 0): ?User`,
+/// `final class User extends Model`, `public ?string $name = null` or
+/// `const LIMIT = 10`. Members declared in a docblock show their tag.
+pub fn signature(src: &str, ast: &Ast, location: &Location) -> Option<String> {
+    let node = declaration(src, ast, location)?;
+    match node.kind() {
+        "function_definition"
+        | "method_declaration"
+        | "class_declaration"
+        | "interface_declaration"
+        | "trait_declaration"
+        | "enum_declaration" => {
+            let end = node
+                .child_by_field_name("body")
+                .map_or(node.end_byte(), |body| body.start_byte());
+            Some(one_line(&src[declaration_start(node)..end]))
+        }
+        "property_element" | "const_element" => {
+            let decl = node.parent()?;
+            let parts: Vec<String> = children(decl)
+                .filter(|child| {
+                    !matches!(
+                        child.kind(),
+                        "attribute_list" | "," | ";" | "property_element" | "const_element"
+                    ) || *child == node
+                })
+                .map(|child| one_line(&src[child.byte_range()]))
+                .collect();
+            Some(parts.join(" "))
+        }
+        "comment" => {
+            // `@property string $name` or `@method static self make()`
+            let line = src.lines().nth(location.range.start.line as usize)?;
+            let tag = line.trim_start().trim_start_matches('*').trim_start();
+            let (_, rest) = tag.split_once(char::is_whitespace)?;
+            Some(rest.trim().to_string())
+        }
+        "variable_name" => Some(src[node.byte_range()].to_string()),
+        _ => Some(one_line(&src[declaration_start(node)..node.end_byte()])),
+    }
+}
+
+/// The declaration a symbol location points at: a function, class-like,
+/// method, property or constant element, enum case, parameter, variable,
+/// or the docblock declaring a magic member.
+pub(crate) fn declaration<'t>(src: &str, ast: &'t Ast, location: &Location) -> Option<Node<'t>> {
+    let mut node = ast.0.root_node().descendant_for_point_range(
+        parser::point_at(src, location.range.start),
+        parser::point_at(src, location.range.end),
+    )?;
+    loop {
+        match node.kind() {
+            "function_definition"
+            | "method_declaration"
+            | "class_declaration"
+            | "interface_declaration"
+            | "trait_declaration"
+            | "enum_declaration"
+            | "property_element"
+            | "const_element"
+            | "enum_case"
+            | "property_promotion_parameter"
+            | "simple_parameter"
+            | "comment" => return Some(node),
+            "variable_name" if node.parent().is_none_or(|p| p.kind() != "simple_parameter") => {
+                return Some(node);
+            }
+            _ => node = node.parent()?,
+        }
+    }
+}
+
+/// The docblock as Markdown sections: the deprecation notice, summary,
+/// description and the tags.
+fn docblock(doc: &DocBlock) -> Vec<String> {
+    let mut sections = Vec::new();
+    if let Some(message) = &doc.deprecated {
+        sections.push(format!("**Deprecated** {}", message).trim_end().to_string());
+    }
+    for text in [&doc.summary, &doc.description] {
+        if !text.is_empty() {
+            sections.push(text.clone());
+        }
+    }
+    let mut tags = Vec::new();
+    for template in &doc.templates {
+        match &template.bound {
+            Some(bound) => tags.push(format!("*@template* `{} of {}`", template.name, bound)),
+            None => tags.push(format!("*@template* `{}`", template.name)),
+        }
+    }
+    for param in &doc.params {
+        tags.push(tag("@param", param));
+    }
+    tags.extend(doc.returns.iter().map(|returns| tag("@return", returns)));
+    for throws in &doc.throws {
+        tags.push(tag("@throws", throws));
+    }
+    if !tags.is_empty() {
+        sections.push(tags.join("  \n"));
+    }
+    sections
+}
+
+fn tag(name: &str, tag: &Tag) -> String {
+    let code: Vec<String> = tag
+        .ty
+        .iter()
+        .map(|ty| ty.to_string())
+        .chain(tag.name.clone())
+        .collect();
+    let mut line = format!("*{}*", name);
+    if !code.is_empty() {
+        line.push_str(&format!(" `{}`", code.join(" ")));
+    }
+    if !tag.description.is_empty() {
+        line.push_str(&format!(" — {}", one_line(&tag.description)));
+    }
+    line
+}
+
+/// The class containing a member, or the namespace containing anything
+/// else, `None` in the global namespace.
+fn container(name: &str) -> Option<&str> {
+    if let Some((class, _)) = name.split_once("::") {
+        return Some(class);
+    }
+    name.rsplit_once('\\').map(|(namespace, _)| namespace)
+}
+
+/// The path of a file relative to `root`, or its full path or URI when it
+/// isn't in the workspace or on disk.
+fn file_name(uri: &Url, root: Option<&Path>) -> String {
+    let Ok(path) = uri.to_file_path() else {
+        return uri.to_string();
+    };
+    root.and_then(|root| path.strip_prefix(root).ok())
+        .unwrap_or(&path)
+        .display()
+        .to_string()
+}
+
+/// Where the declaration starts, after its attributes.
+pub(crate) fn declaration_start(node: Node) -> usize {
+    children(node)
+        .find(|child| child.kind() != "attribute_list")
+        .map_or(node.start_byte(), |child| child.start_byte())
+}
+
+fn children<'t>(node: Node<'t>) -> impl Iterator<Item = Node<'t>> {
+    (0..node.child_count()).filter_map(move |i| node.child(i))
+}
+
+/// `text` on one line. Line breaks and the indentation after them become a
+/// single space, or disappear inside brackets, and trailing commas before
+/// a closing bracket are dropped.
+pub(crate) fn one_line(text: &str) -> String {
+    let mut out = String::new();
+    let mut lines = text.trim().trim_end_matches(';').trim_end().lines();
+    if let Some(first) = lines.next() {
+        out.push_str(first.trim_end());
+    }
+    for line in lines {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        if out.ends_with(',') && line.starts_with([')', ']']) {
+            out.pop();
+        }
+        if !out.ends_with(['(', '[']) && !line.starts_with([')', ']']) {
+            out.push(' ');
+        }
+        out.push_str(line);
+    }
+    out
+}
@@ -1,81 +1,491 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
 
 use dashmap::DashMap;
-use tower_lsp::lsp_types::{Location, Position, Range, Url};
-use tree_sitter::Node;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use tower_lsp::lsp_types::{self, Location, Range, Url};
+use tree_sitter::{Node, Point};
 
+use crate::cache::{CachedFile, FileStamp, IndexCache, content_hash};
+use crate::filter::FileFilter;
 use crate::parser::{self, Ast};
-use bumpalo::Bump;
+use crate::phpdoc::{self, DocBlock, Template};
+use crate::resolver::{self, ImportTable};
+use crate::{composer, config};
 use walkdir::WalkDir;
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum SymbolKind {
     Function,
     Class,
+    Interface,
+    Trait,
+    Enum,
     Constant,
     Variable,
+    Method,
+    Property,
+    ClassConstant,
+    EnumCase,
 }
 
-#[derive(Debug, Clone)]
+/// Map an indexed symbol kind onto the LSP `SymbolKind` used by symbol
+/// requests.
+pub fn map_symbol_kind(kind: &SymbolKind) -> lsp_types::SymbolKind {
+    match kind {
+        SymbolKind::Function => lsp_types::SymbolKind::FUNCTION,
+        SymbolKind::Class => lsp_types::SymbolKind::CLASS,
+        SymbolKind::Interface => lsp_types::SymbolKind::INTERFACE,
+        SymbolKind::Trait => lsp_types::SymbolKind::CLASS,
+        SymbolKind::Enum => lsp_types::SymbolKind::ENUM,
+        SymbolKind::Constant => lsp_types::SymbolKind::CONSTANT,
+        SymbolKind::Variable => lsp_types::SymbolKind::VARIABLE,
+        SymbolKind::Method => lsp_types::SymbolKind::METHOD,
+        SymbolKind::Property => lsp_types::SymbolKind::PROPERTY,
+        SymbolKind::ClassConstant => lsp_types::SymbolKind::CONSTANT,
+        SymbolKind::EnumCase => lsp_types::SymbolKind::ENUM_MEMBER,
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Symbol {
     pub name: String,
     pub kind: SymbolKind,
     pub location: Location,
     pub container: Option<String>,
+    /// For classes, interfaces, traits and enums: the fully qualified names
+    /// of the traits they use, the classes or interfaces they extend and the
+    /// interfaces they implement, in that order.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub supertypes: Vec<String>,
+    /// The declared type with class names fully qualified: the return type
+    /// of functions and methods, the type of properties, and the enum an
+    /// enum case belongs to.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub type_hint: Option<String>,
+    /// The docblock before the declaration, with class names fully
+    /// qualified.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub doc: Option<Box<DocBlock>>,
+}
+
+/// Symbols declared in a file, keyed by fully qualified name. A name
+/// declared more than once, such as a polyfill in both branches of an `if`,
+/// keeps every definition in source order.
+pub type FileSymbols = HashMap<String, Vec<Symbol>>;
+pub type GlobalIndex = std::sync::Arc<WorkspaceIndex>;
+
+/// Where an indexed file comes from. Layers are ordered by lookup priority:
+/// project code shadows dependencies, which shadow bundled stubs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Layer {
+    Project,
+    Vendor,
+    Stub,
 }
 
-pub type FileSymbols = HashMap<String, Symbol>;
-pub type GlobalIndex = std::sync::Arc<DashMap<Url, FileSymbols>>;
+/// Symbols of every indexed file, tagged with the layer each file belongs
+/// to. Vendor and stub files are read-only.
+#[derive(Debug, Default)]
+pub struct WorkspaceIndex {
+    files: DashMap<Url, FileSymbols>,
+    layers: DashMap<Url, Layer>,
+    /// Reverse index from fully qualified name to the files defining it,
+    /// kept in step with `files`.
+    names: DashMap<String, Vec<(Url, SymbolKind)>>,
+    /// The indexed `Class::member` names of each class, keyed by the class
+    /// name in lowercase, kept in step with `names`.
+    members: DashMap<String, HashSet<String>>,
+    /// When each name's definitions last changed, as a tick of `clock`.
+    /// Lets caches built from the index tell whether what they looked up is
+    /// still current.
+    revisions: DashMap<String, u64>,
+    clock: AtomicU64,
+    autoload: RwLock<composer::Autoload>,
+}
+
+impl WorkspaceIndex {
+    /// Insert or replace the symbols of a file, keeping the layer it was
+    /// first indexed in (new files join the project layer).
+    pub fn insert(&self, uri: Url, symbols: FileSymbols) {
+        let layer = self.layer(&uri);
+        self.insert_in(uri, symbols, layer);
+    }
+
+    pub fn insert_in(&self, uri: Url, symbols: FileSymbols, layer: Layer) {
+        self.layers.insert(uri.clone(), layer);
+        // hold the file's entry while the reverse index is updated so
+        // concurrent replacements of the same file can't interleave
+        let mut entry = self.files.entry(uri.clone()).or_default();
+        for name in entry.keys() {
+            self.unlink(name, &uri);
+        }
+        for (name, defs) in &symbols {
+            if let Some(sym) = defs.first() {
+                self.names
+                    .entry(name.clone())
+                    .or_default()
+                    .push((uri.clone(), sym.kind.clone()));
+                if let Some((class, _)) = name.split_once("::") {
+                    self.members
+                        .entry(class.to_ascii_lowercase())
+                        .or_default()
+                        .insert(name.clone());
+                }
+                self.touch(name);
+            }
+        }
+        *entry = symbols;
+    }
+
+    pub fn remove(&self, uri: &Url) {
+        if let Some((_, symbols)) = self.files.remove(uri) {
+            for name in symbols.keys() {
+                self.unlink(name, uri);
+            }
+        }
+        self.layers.remove(uri);
+    }
+
+    pub fn clear(&self) {
+        let names: Vec<String> = self.names.iter().map(|e| e.key().clone()).collect();
+        self.files.clear();
+        self.layers.clear();
+        self.names.clear();
+        self.members.clear();
+        for name in &names {
+            self.touch(name);
+        }
+    }
+
+    /// The revision of `name`: it changes whenever a definition of the name
+    /// is added, replaced or removed, and is 0 for names never indexed.
+    pub fn revision(&self, name: &str) -> u64 {
+        self.revisions.get(name).map(|r| *r).unwrap_or(0)
+    }
+
+    /// Every definition of `fqn` across the index, project code first, then
+    /// dependencies, then stubs.
+    pub fn definitions(&self, fqn: &str) -> Vec<(Layer, Symbol)> {
+        // copy the file list out first: `insert_in` locks `files` before
+        // `names`, so holding both here in the other order could deadlock
+        let uris: Vec<Url> = match self.names.get(fqn) {
+            Some(defs) => defs.iter().map(|(uri, _)| uri.clone()).collect(),
+            None => return Vec::new(),
+        };
+        let mut out: Vec<(Layer, Symbol)> = uris
+            .iter()
+            .filter_map(|uri| {
+                let layer = self.layer(uri);
+                let symbols = self.files.get(uri)?.get(fqn)?.clone();
+                Some(symbols.into_iter().map(move |s| (layer, s)))
+            })
+            .flatten()
+            .collect();
+        out.sort_by_key(|(layer, _)| *layer);
+        out
+    }
+
+    /// The indexed members of `class`, as `Class::member` names.
+    pub fn members_of(&self, class: &str) -> Vec<String> {
+        self.members
+            .get(&class.to_ascii_lowercase())
+            .map(|members| members.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Visit every indexed name with the kind and layer of its highest
+    /// priority definition, without copying any symbols.
+    pub fn for_each_name(&self, mut f: impl FnMut(&str, &SymbolKind, Layer)) {
+        for entry in self.names.iter() {
+            if let Some((layer, kind)) = entry
+                .value()
+                .iter()
+                .map(|(uri, kind)| (self.layer(uri), kind))
+                .min_by_key(|(layer, _)| *layer)
+            {
+                f(entry.key(), kind, layer);
+            }
+        }
+    }
+
+    fn touch(&self, name: &str) {
+        let tick = self.clock.fetch_add(1, Ordering::Relaxed) + 1;
+        self.revisions.insert(name.to_string(), tick);
+    }
+
+    fn unlink(&self, name: &str, uri: &Url) {
+        self.touch(name);
+        if let dashmap::mapref::entry::Entry::Occupied(mut e) = self.names.entry(name.to_string()) {
+            e.get_mut().retain(|(u, _)| u != uri);
+            if e.get().is_empty() {
+                e.remove();
+                if let Some((class, _)) = name.split_once("::") {
+                    self.members
+                        .remove_if_mut(&class.to_ascii_lowercase(), |_, members| {
+                            members.remove(name);
+                            members.is_empty()
+                        });
+                }
+            }
+        }
+    }
+
+    pub fn get(&self, uri: &Url) -> Option<dashmap::mapref::one::Ref<'_, Url, FileSymbols>> {
+        self.files.get(uri)
+    }
+
+    pub fn iter(&self) -> dashmap::iter::Iter<'_, Url, FileSymbols> {
+        self.files.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    /// The layer of an indexed file; unknown files count as project code.
+    pub fn layer(&self, uri: &Url) -> Layer {
+        self.layers.get(uri).map(|l| *l).unwrap_or(Layer::Project)
+    }
+
+    /// Register the project's Composer autoload configuration, used to
+    /// locate classes whose files have not been indexed yet.
+    pub fn set_autoload(&self, autoload: composer::Autoload) {
+        *self.autoload.write().unwrap() = autoload;
+    }
+
+    /// The project's Composer autoload configuration.
+    pub fn autoload(&self) -> composer::Autoload {
+        self.autoload.read().unwrap().clone()
+    }
+}
+
+/// Index the file PSR-4 or PSR-0 autoloading maps `fqn` to, if it exists
+/// and is not indexed yet. Returns the URI of the file now holding the class.
+pub fn load_autoload_class(index: &WorkspaceIndex, fqn: &str) -> Option<Url> {
+    let candidates = index.autoload.read().unwrap().class_candidates(fqn);
+    for path in candidates {
+        if !path.is_file() {
+            continue;
+        }
+        let uri = Url::from_file_path(&path).ok()?;
+        if index.get(&uri).is_none() {
+            tracing::debug!("loading {} on demand for {}", path.display(), fqn);
+            index_file(&path, index).ok()?;
+        }
+        return Some(uri);
+    }
+    None
+}
 
 pub fn new_index() -> GlobalIndex {
-    std::sync::Arc::new(DashMap::new())
+    std::sync::Arc::new(WorkspaceIndex::default())
 }
 
-pub fn scan_workspace(root: &Path, index: &GlobalIndex) -> std::io::Result<()> {
+/// Index the project files under `root` followed by its Composer
+/// dependencies. The vendor directory is never part of the project layer.
+/// Project files are filtered by the `.phppprc` found at `root`.
+pub fn scan_workspace(root: &Path, index: &WorkspaceIndex) -> std::io::Result<()> {
     index.clear();
-    for entry in WalkDir::new(root).into_iter().filter_map(|e| e.ok()) {
-        if entry.path().extension().and_then(|s| s.to_str()) == Some("php") {
-            index_file(entry.path(), index)?;
+    let filter = FileFilter::new(root, &config::load_config(root).unwrap_or_default());
+    let files = workspace_files(root, index, &filter)?;
+    index_files(&files, index, |_| {});
+    Ok(())
+}
+
+/// Collect the PHP files a workspace scan indexes, with the layer each one
+/// belongs to: project files under `root` that `filter` accepts, autoload
+/// paths outside of it, then the Composer dependencies.
+pub fn workspace_files(
+    root: &Path,
+    index: &WorkspaceIndex,
+    filter: &FileFilter,
+) -> std::io::Result<Vec<(PathBuf, Layer)>> {
+    let vendor = composer::vendor_dir(root);
+    let mut files: Vec<(PathBuf, Layer)> = filter
+        .walk(root)
+        .filter(|p| !p.starts_with(&vendor))
+        .map(|p| (p, Layer::Project))
+        .collect();
+    // classmap and files entries may point outside the project root
+    for path in index.autoload().paths() {
+        if path.starts_with(root) || !path.exists() {
+            continue;
         }
+        files.extend(
+            WalkDir::new(&path)
+                .into_iter()
+                .filter_map(|e| e.ok())
+                .filter(|e| filter.is_php(e.path()))
+                .map(|e| (e.into_path(), Layer::Project)),
+        );
     }
-    Ok(())
+    files.extend(vendor_files(&vendor, filter)?);
+    Ok(files)
+}
+
+/// The autoloadable files of every package listed in
+/// `vendor/composer/installed.json`, or the whole vendor directory without
+/// an `installed.json`. Dependencies are indexed whatever the project's globs
+/// and `.gitignore` say, which usually ignores `vendor/` itself; only the
+/// extensions apply. Files under more than one autoload root, such as a
+/// classmap directory inside a PSR-4 one, are listed once.
+fn vendor_files(vendor: &Path, filter: &FileFilter) -> std::io::Result<Vec<(PathBuf, Layer)>> {
+    if !vendor.is_dir() {
+        return Ok(Vec::new());
+    }
+    let roots = match composer::load_installed_packages(vendor)? {
+        Some(packages) => {
+            tracing::debug!("indexing {} vendor packages", packages.len());
+            packages
+                .into_iter()
+                .flat_map(|p| p.autoload.paths())
+                .collect()
+        }
+        None => vec![vendor.to_path_buf()],
+    };
+    let mut files: Vec<PathBuf> = roots
+        .iter()
+        .flat_map(|root| WalkDir::new(root).into_iter().filter_map(|e| e.ok()))
+        .filter(|e| filter.is_php(e.path()))
+        .map(|e| e.into_path())
+        .collect();
+    files.sort();
+    files.dedup();
+    Ok(files.into_iter().map(|p| (p, Layer::Vendor)).collect())
 }
 
-pub fn index_file(path: &Path, index: &GlobalIndex) -> std::io::Result<()> {
+/// Index `files` in parallel on the rayon pool. `progress` is called with
+/// the number of files done after each one; files that fail to index are
+/// logged and skipped.
+pub fn index_files(
+    files: &[(PathBuf, Layer)],
+    index: &WorkspaceIndex,
+    progress: impl Fn(usize) + Sync,
+) {
+    index_files_cached(files, index, &IndexCache::default(), progress);
+}
+
+/// Like [`index_files`], but take the symbols of files whose mtime and
+/// size, or failing that content hash, match `cache` instead of parsing
+/// them again. Returns the stamp and symbols of every file as read from
+/// disk, for the next cache.
+pub fn index_files_cached(
+    files: &[(PathBuf, Layer)],
+    index: &WorkspaceIndex,
+    cache: &IndexCache,
+    progress: impl Fn(usize) + Sync,
+) -> Vec<(PathBuf, CachedFile)> {
+    let done = AtomicUsize::new(0);
+    let reused = AtomicUsize::new(0);
+    let indexed: Vec<_> = files
+        .par_iter()
+        .filter_map(|(path, layer)| {
+            let file = match index_cached(path, index, *layer, cache) {
+                Ok((file, hit)) => {
+                    if hit {
+                        reused.fetch_add(1, Ordering::Relaxed);
+                    }
+                    Some((path.clone(), file))
+                }
+                Err(e) => {
+                    tracing::warn!("failed to index {}: {}", path.display(), e);
+                    None
+                }
+            };
+            progress(done.fetch_add(1, Ordering::Relaxed) + 1);
+            file
+        })
+        .collect();
+    tracing::debug!(
+        "indexed {} files, {} from cache",
+        indexed.len(),
+        reused.into_inner()
+    );
+    indexed
+}
+
+/// Index one file, reusing its cached symbols when it has not changed.
+/// Returns what was indexed and whether the cache was hit.
+fn index_cached(
+    path: &Path,
+    index: &WorkspaceIndex,
+    layer: Layer,
+    cache: &IndexCache,
+) -> std::io::Result<(CachedFile, bool)> {
+    let mut stamp = FileStamp::of(path, layer)?;
+    let uri = Url::from_file_path(path).unwrap();
+    let cached = cache.get(path);
+    if let Some(cached) = cached
+        && stamp.unchanged(&cached.stamp)
+    {
+        index.insert_in(uri, cached.symbols.clone(), layer);
+        return Ok((cached.clone(), true));
+    }
+    let src = fs::read_to_string(path)?;
+    stamp.hash = content_hash(src.as_bytes());
+    if let Some(cached) = cached
+        && cached.stamp.hash == stamp.hash
+        && cached.stamp.layer == layer
+    {
+        index.insert_in(uri, cached.symbols.clone(), layer);
+        let symbols = cached.symbols.clone();
+        return Ok((CachedFile { stamp, symbols }, true));
+    }
+    let ast = parser::parse_php(&src);
+    let symbols = extract_symbols(&src, &ast, &uri);
+    index.insert_in(uri, symbols.clone(), layer);
+    Ok((CachedFile { stamp, symbols }, false))
+}
+
+pub fn index_file(path: &Path, index: &WorkspaceIndex) -> std::io::Result<()> {
+    let layer = Url::from_file_path(path)
+        .map(|uri| index.layer(&uri))
+        .unwrap_or(Layer::Project);
+    index_file_in(path, index, layer)
+}
+
+pub fn index_file_in(path: &Path, index: &WorkspaceIndex, layer: Layer) -> std::io::Result<()> {
     if !path.exists() {
         index.remove(&Url::from_file_path(path).unwrap());
         return Ok(());
     }
-    if path.extension().and_then(|s| s.to_str()) != Some("php") {
-        return Ok(());
-    }
     let src = fs::read_to_string(path)?;
-    let bump = Bump::new();
-    let ast = parser::parse_php(&src, &bump);
+    let ast = parser::parse_php(&src);
     let uri = Url::from_file_path(path).unwrap();
     let symbols = extract_symbols(&src, &ast, &uri);
-    index.insert(uri, symbols);
+    index.insert_in(uri, symbols, layer);
     Ok(())
 }
 
 pub fn extract_symbols(src: &str, ast: &Ast, uri: &Url) -> FileSymbols {
     tracing::debug!("Indexing symbols in {}", uri);
     let root = ast.0.root_node();
+    let scopes = namespace_scopes(src, ast);
+    let imports = ImportTable::build(src, ast);
     let mut out = HashMap::new();
-    let mut namespace = String::new();
     for i in 0..root.named_child_count() {
         if let Some(child) = root.named_child(i) {
+            let namespace = namespace_at(&scopes, child.start_position());
             match child.kind() {
                 "namespace_definition" => {
-                    if let Some(name_node) = child.child_by_field_name("name") {
-                        if let Ok(ns) = name_node.utf8_text(src.as_bytes()) {
-                            namespace = ns.to_string();
+                    if let Some(body) = child.child_by_field_name("body") {
+                        for j in 0..body.named_child_count() {
+                            if let Some(stmt) = body.named_child(j) {
+                                collect_node(src, stmt, uri, namespace, &imports, &mut out);
+                            }
                         }
                     }
                 }
-                _ => collect_node(src, child, uri, &namespace, &mut out),
+                _ => collect_node(src, child, uri, namespace, &imports, &mut out),
             }
         }
     }
@@ -83,121 +493,558 @@ pub fn extract_symbols(src: &str, ast: &Ast, uri: &Url) -> FileSymbols {
     out
 }
 
-fn collect_node(src: &str, node: Node, uri: &Url, namespace: &str, out: &mut FileSymbols) {
-    match node.kind() {
+/// A region of a file governed by one namespace declaration: either a braced
+/// `namespace Foo { ... }` block or a `namespace Foo;` statement running up to
+/// the next namespace declaration.
+#[derive(Debug, Clone)]
+pub struct NamespaceScope {
+    pub name: String,
+    pub start: Point,
+    pub end: Point,
+}
+
+/// Collect the namespace scopes declared at the top level of `ast`, in source
+/// order. Code outside every scope belongs to the global namespace.
+pub fn namespace_scopes(src: &str, ast: &Ast) -> Vec<NamespaceScope> {
+    let root = ast.0.root_node();
+    let mut scopes: Vec<NamespaceScope> = Vec::new();
+    for i in 0..root.named_child_count() {
+        let Some(child) = root.named_child(i) else {
+            continue;
+        };
+        if child.kind() != "namespace_definition" {
+            continue;
+        }
+        if let Some(open) = scopes.last_mut()
+            && open.end > child.start_position()
+        {
+            open.end = child.start_position();
+        }
+        let name = child
+            .child_by_field_name("name")
+            .and_then(|n| n.utf8_text(src.as_bytes()).ok())
+            .unwrap_or("")
+            .to_string();
+        let end = if child.child_by_field_name("body").is_some() {
+            child.end_position()
+        } else {
+            root.end_position()
+        };
+        scopes.push(NamespaceScope {
+            name,
+            start: child.start_position(),
+            end,
+        });
+    }
+    scopes
+}
+
+impl NamespaceScope {
+    pub fn contains(&self, point: Point) -> bool {
+        self.start <= point && point < self.end
+    }
+}
+
+/// Return the namespace in effect at `point`.
+pub fn namespace_at(scopes: &[NamespaceScope], point: Point) -> &str {
+    scopes
+        .iter()
+        .find(|s| s.contains(point))
+        .map(|s| s.name.as_str())
+        .unwrap_or("")
+}
+
+fn collect_node(
+    src: &str,
+    node: Node,
+    uri: &Url,
+    namespace: &str,
+    imports: &ImportTable,
+    out: &mut FileSymbols,
+) {
+    let kind = match node.kind() {
         "function_definition" => {
-            add_symbol(src, node, uri, namespace, SymbolKind::Function, out);
+            if let Some(sym) = add_symbol(src, node, uri, namespace, SymbolKind::Function, out) {
+                let doc = doc_comment(src, node, imports, None, &[]);
+                sym.type_hint = declared_type(src, node, "return_type", imports, None);
+                if let Some(doc) = &doc
+                    && let Some(ty) = doc.hint(doc.returns.as_ref(), &[])
+                {
+                    sym.type_hint = Some(ty);
+                }
+                sym.doc = doc;
+            }
+            return;
+        }
+        "class_declaration" => SymbolKind::Class,
+        "interface_declaration" => SymbolKind::Interface,
+        "trait_declaration" => SymbolKind::Trait,
+        "enum_declaration" => SymbolKind::Enum,
+        "const_declaration" => {
+            add_constant(src, node, uri, namespace, out);
+            return;
         }
-        "class_declaration" => {
-            add_symbol(src, node, uri, namespace, SymbolKind::Class, out);
+        "if_statement" => {
+            collect_conditional(src, node, uri, namespace, imports, out);
+            return;
         }
-        "const_declaration" => add_constant(src, node, uri, namespace, out),
         "expression_statement" => {
-            if let Some(expr) = node.named_child(0) {
-                if expr.kind() == "assignment_expression" {
-                    add_variable(src, expr, uri, namespace, out);
-                }
+            if let Some(expr) = node.named_child(0)
+                && expr.kind() == "assignment_expression"
+            {
+                add_variable(src, expr, uri, namespace, out);
             }
+            return;
         }
-        _ => {}
-    }
+        _ => return,
+    };
+    let Some(sym) = add_symbol(src, node, uri, namespace, kind, out) else {
+        return;
+    };
+    let doc = doc_comment(src, node, imports, Some(&sym.name), &[]);
+    sym.supertypes = resolver::supertypes(src, node, imports);
+    sym.doc = doc.clone();
+    collect_members(src, node, uri, namespace, imports, doc.as_deref(), out);
 }
 
-fn add_symbol(
+/// Index the declarations in the branches of a top-level `if`, where
+/// polyfills and version-dependent definitions usually live.
+fn collect_conditional(
     src: &str,
     node: Node,
     uri: &Url,
     namespace: &str,
-    kind: SymbolKind,
+    imports: &ImportTable,
     out: &mut FileSymbols,
 ) {
-    if let Some(name_node) = node.child_by_field_name("name") {
-        if let Ok(name) = name_node.utf8_text(src.as_bytes()) {
-            let fqn = if namespace.is_empty() {
-                name.to_string()
-            } else {
-                format!("{}\\{}", namespace, name)
-            };
-            out.insert(
-                fqn.clone(),
-                Symbol {
-                    name: fqn,
-                    kind,
-                    location: node_location(uri, node),
-                    container: None,
-                },
-            );
+    for i in 0..node.named_child_count() {
+        let Some(child) = node.named_child(i) else {
+            continue;
+        };
+        match child.kind() {
+            "compound_statement" | "colon_block" | "else_clause" | "else_if_clause"
+            | "if_statement" => collect_conditional(src, child, uri, namespace, imports, out),
+            "function_definition"
+            | "class_declaration"
+            | "interface_declaration"
+            | "trait_declaration"
+            | "enum_declaration"
+            | "const_declaration" => collect_node(src, child, uri, namespace, imports, out),
+            _ => {}
         }
     }
 }
 
-fn add_constant(src: &str, node: Node, uri: &Url, namespace: &str, out: &mut FileSymbols) {
-    for i in 0..node.named_child_count() {
-        if let Some(constant) = node.named_child(i) {
-            if constant.kind() == "const_element" {
-                let name_node = constant
-                    .child_by_field_name("name")
-                    .or_else(|| constant.named_child(0));
-                if let Some(name_node) = name_node {
-                    if let Ok(name) = name_node.utf8_text(src.as_bytes()) {
-                        let fqn = if namespace.is_empty() {
-                            name.to_string()
-                        } else {
-                            format!("{}\\{}", namespace, name)
-                        };
-                        out.insert(
-                            fqn.clone(),
-                            Symbol {
-                                name: fqn,
-                                kind: SymbolKind::Constant,
-                                location: node_location(uri, name_node),
-                                container: None,
-                            },
+fn qualify(namespace: &str, name: &str) -> String {
+    if namespace.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}\\{}", namespace, name)
+    }
+}
+
+fn add_symbol<'a>(
+    src: &str,
+    node: Node,
+    uri: &Url,
+    namespace: &str,
+    kind: SymbolKind,
+    out: &'a mut FileSymbols,
+) -> Option<&'a mut Symbol> {
+    let name = node
+        .child_by_field_name("name")?
+        .utf8_text(src.as_bytes())
+        .ok()?;
+    let fqn = qualify(namespace, name);
+    Some(define(
+        out,
+        Symbol {
+            name: fqn,
+            kind,
+            location: node_location(uri, src, node),
+            container: None,
+            supertypes: Vec::new(),
+            type_hint: None,
+            doc: None,
+        },
+    ))
+}
+
+/// The declared type in the `field` child of `node`, see
+/// [`resolver::declared_type`].
+fn declared_type(
+    src: &str,
+    node: Node,
+    field: &str,
+    imports: &ImportTable,
+    class: Option<&str>,
+) -> Option<String> {
+    let ty = node.child_by_field_name(field)?;
+    Some(resolver::declared_type(src, ty, imports, class))
+}
+
+/// The docblock before `node`, with class names qualified through the
+/// imports and `self`, `static` and `$this` standing for `class`.
+/// `templates` are the ones of the enclosing class.
+fn doc_comment(
+    src: &str,
+    node: Node,
+    imports: &ImportTable,
+    class: Option<&str>,
+    templates: &[Template],
+) -> Option<Box<DocBlock>> {
+    let mut doc = phpdoc::doc_before(node, src)?;
+    resolver::qualify_doc(&mut doc, templates, imports, node.start_position(), class);
+    Some(Box::new(doc))
+}
+
+/// Index the methods, properties, class constants and enum cases declared in
+/// the body of a class-like declaration, and the `@property` and `@method`
+/// tags of its docblock `doc`. Members are keyed as `Class\Fqn::member`
+/// (properties keep their `$`) and carry the owning class in `container`.
+/// Types from member docblocks take precedence over declared ones.
+fn collect_members(
+    src: &str,
+    node: Node,
+    uri: &Url,
+    namespace: &str,
+    imports: &ImportTable,
+    doc: Option<&DocBlock>,
+    out: &mut FileSymbols,
+) {
+    let Some(class_name) = node
+        .child_by_field_name("name")
+        .and_then(|n| n.utf8_text(src.as_bytes()).ok())
+    else {
+        return;
+    };
+    let Some(body) = node.child_by_field_name("body") else {
+        return;
+    };
+    let class_fqn = qualify(namespace, class_name);
+    let templates = doc.map(|d| d.templates.clone()).unwrap_or_default();
+    for i in 0..body.named_child_count() {
+        let Some(member) = body.named_child(i) else {
+            continue;
+        };
+        let member_doc = || doc_comment(src, member, imports, Some(&class_fqn), &templates);
+        match member.kind() {
+            "method_declaration" => {
+                if let Some(name_node) = member.child_by_field_name("name") {
+                    let doc = member_doc();
+                    if name_node.utf8_text(src.as_bytes()) == Ok("__construct") {
+                        add_promoted_properties(
+                            src,
+                            member,
+                            uri,
+                            &class_fqn,
+                            imports,
+                            doc.as_deref(),
+                            out,
                         );
                     }
+                    if let Some(sym) = add_member(
+                        src,
+                        name_node,
+                        member,
+                        uri,
+                        &class_fqn,
+                        SymbolKind::Method,
+                        out,
+                    ) {
+                        sym.type_hint =
+                            declared_type(src, member, "return_type", imports, Some(&class_fqn));
+                        if let Some(doc) = &doc
+                            && let Some(ty) = doc.hint(doc.returns.as_ref(), &templates)
+                        {
+                            sym.type_hint = Some(ty);
+                        }
+                        sym.doc = doc;
+                    }
                 }
             }
+            "property_declaration" => {
+                let ty = declared_type(src, member, "type", imports, Some(&class_fqn));
+                let doc = member_doc();
+                for j in 0..member.named_child_count() {
+                    if let Some(element) = member.named_child(j)
+                        && element.kind() == "property_element"
+                        && let Some(name_node) = element.child_by_field_name("name")
+                        && let Ok(name) = name_node.utf8_text(src.as_bytes())
+                        && let Some(sym) = add_member(
+                            src,
+                            name_node,
+                            element,
+                            uri,
+                            &class_fqn,
+                            SymbolKind::Property,
+                            out,
+                        )
+                    {
+                        sym.type_hint = ty.clone();
+                        if let Some(doc) = &doc
+                            && let Some(ty) = doc.hint(doc.var(name), &templates)
+                        {
+                            sym.type_hint = Some(ty);
+                        }
+                        sym.doc = doc.clone();
+                    }
+                }
+            }
+            "const_declaration" => {
+                let doc = member_doc();
+                for j in 0..member.named_child_count() {
+                    if let Some(element) = member.named_child(j)
+                        && element.kind() == "const_element"
+                        && let Some(name_node) = element
+                            .child_by_field_name("name")
+                            .or_else(|| element.named_child(0))
+                        && let Some(sym) = add_member(
+                            src,
+                            name_node,
+                            name_node,
+                            uri,
+                            &class_fqn,
+                            SymbolKind::ClassConstant,
+                            out,
+                        )
+                    {
+                        sym.doc = doc.clone();
+                    }
+                }
+            }
+            "enum_case" => {
+                if let Some(name_node) = member.child_by_field_name("name")
+                    && let Some(sym) = add_member(
+                        src,
+                        name_node,
+                        member,
+                        uri,
+                        &class_fqn,
+                        SymbolKind::EnumCase,
+                        out,
+                    )
+                {
+                    sym.type_hint = Some(class_fqn.clone());
+                    sym.doc = member_doc();
+                }
+            }
+            _ => {}
         }
     }
+    if let Some(doc) = doc {
+        add_doc_members(src, node, uri, &class_fqn, doc, out);
+    }
 }
 
-fn add_variable(src: &str, node: Node, uri: &Url, namespace: &str, out: &mut FileSymbols) {
-    if let Some(left) = node.child_by_field_name("left") {
-        if left.kind() == "variable_name" {
-            if let Ok(name) = left.utf8_text(src.as_bytes()) {
-                let fqn = if namespace.is_empty() {
-                    name.to_string()
-                } else {
-                    format!("{}\\{}", namespace, name)
-                };
-                out.insert(
-                    fqn.clone(),
-                    Symbol {
-                        name: fqn,
-                        kind: SymbolKind::Variable,
-                        location: node_location(uri, left),
-                        container: None,
-                    },
-                );
+/// Index the magic members a class declares with `@property` and
+/// `@method` tags, unless the class body declares them too. They are
+/// located on their tag.
+fn add_doc_members(
+    src: &str,
+    node: Node,
+    uri: &Url,
+    class_fqn: &str,
+    doc: &DocBlock,
+    out: &mut FileSymbols,
+) {
+    let Some(comment) = node.prev_named_sibling() else {
+        return;
+    };
+    let properties = doc.properties.iter().filter_map(|tag| {
+        let name = tag.name.clone()?;
+        let ty = doc.hint(Some(tag), &[]);
+        Some((name, SymbolKind::Property, ty, &tag.description, tag.line))
+    });
+    let methods = doc.methods.iter().map(|method| {
+        let ty = method.returns.as_ref().map(|ty| ty.hint(&doc.templates));
+        let name = method.name.clone();
+        (
+            name,
+            SymbolKind::Method,
+            ty,
+            &method.description,
+            method.line,
+        )
+    });
+    for (name, kind, type_hint, description, line) in properties.chain(methods).collect::<Vec<_>>()
+    {
+        let key = format!("{}::{}", class_fqn, name);
+        if out.contains_key(&key) {
+            continue;
+        }
+        let row = comment.start_position().row + line as usize;
+        let column = src
+            .lines()
+            .nth(row)
+            .and_then(|text| doc_member_column(text, &name, &kind))
+            .unwrap_or(0);
+        let start = parser::position_at(src, Point { row, column });
+        let end = parser::position_at(
+            src,
+            Point {
+                row,
+                column: column + name.len(),
+            },
+        );
+        define(
+            out,
+            Symbol {
+                name: key,
+                kind,
+                location: Location::new(uri.clone(), Range::new(start, end)),
+                container: Some(class_fqn.to_string()),
+                supertypes: Vec::new(),
+                type_hint,
+                doc: Some(Box::new(DocBlock {
+                    summary: description.clone(),
+                    ..DocBlock::default()
+                })),
+            },
+        );
+    }
+}
+
+/// The byte column of the member `name` on the line of its `@property` or
+/// `@method` tag. The name is looked for after the tag as a whole word, and
+/// for methods before the `(` of their parameters, so a type spelling it
+/// doesn't match.
+fn doc_member_column(line: &str, name: &str, kind: &SymbolKind) -> Option<usize> {
+    let tag = line.find('@')?;
+    let after = tag + line[tag..].find(char::is_whitespace)?;
+    let is_word = |c: char| c.is_alphanumeric() || matches!(c, '_' | '\\' | '$');
+    line[after..]
+        .match_indices(name)
+        .map(|(i, _)| after + i)
+        .find(|&start| {
+            let rest = &line[start + name.len()..];
+            !line[..start].ends_with(is_word)
+                && !rest.starts_with(is_word)
+                && (*kind != SymbolKind::Method || rest.trim_start().starts_with('('))
+        })
+}
+
+/// Constructor parameters with a visibility modifier also declare properties,
+/// typed by the constructor's `@param` tags in `doc` if it has any.
+fn add_promoted_properties(
+    src: &str,
+    method: Node,
+    uri: &Url,
+    class_fqn: &str,
+    imports: &ImportTable,
+    doc: Option<&DocBlock>,
+    out: &mut FileSymbols,
+) {
+    let Some(params) = method.child_by_field_name("parameters") else {
+        return;
+    };
+    for i in 0..params.named_child_count() {
+        if let Some(param) = params.named_child(i)
+            && param.kind() == "property_promotion_parameter"
+            && let Some(name_node) = param.child_by_field_name("name")
+            && let Some(sym) = add_member(
+                src,
+                name_node,
+                param,
+                uri,
+                class_fqn,
+                SymbolKind::Property,
+                out,
+            )
+        {
+            sym.type_hint = declared_type(src, param, "type", imports, Some(class_fqn));
+            if let Some(doc) = doc
+                && let Ok(name) = name_node.utf8_text(src.as_bytes())
+                && let Some(ty) = doc.hint(doc.param(name), &[])
+            {
+                sym.type_hint = Some(ty);
             }
         }
     }
 }
 
-fn node_location(uri: &Url, node: Node) -> Location {
-    let start = node.start_position();
-    let end = node.end_position();
+fn add_member<'a>(
+    src: &str,
+    name_node: Node,
+    decl: Node,
+    uri: &Url,
+    class_fqn: &str,
+    kind: SymbolKind,
+    out: &'a mut FileSymbols,
+) -> Option<&'a mut Symbol> {
+    let name = name_node.utf8_text(src.as_bytes()).ok()?;
+    let key = format!("{}::{}", class_fqn, name);
+    Some(define(
+        out,
+        Symbol {
+            name: key,
+            kind,
+            location: node_location(uri, src, decl),
+            container: Some(class_fqn.to_string()),
+            supertypes: Vec::new(),
+            type_hint: None,
+            doc: None,
+        },
+    ))
+}
+
+fn add_constant(src: &str, node: Node, uri: &Url, namespace: &str, out: &mut FileSymbols) {
+    for i in 0..node.named_child_count() {
+        if let Some(constant) = node.named_child(i)
+            && constant.kind() == "const_element"
+            && let Some(name_node) = constant
+                .child_by_field_name("name")
+                .or_else(|| constant.named_child(0))
+            && let Ok(name) = name_node.utf8_text(src.as_bytes())
+        {
+            let fqn = qualify(namespace, name);
+            define(
+                out,
+                Symbol {
+                    name: fqn,
+                    kind: SymbolKind::Constant,
+                    location: node_location(uri, src, name_node),
+                    container: None,
+                    supertypes: Vec::new(),
+                    type_hint: None,
+                    doc: None,
+                },
+            );
+        }
+    }
+}
+
+fn add_variable(src: &str, node: Node, uri: &Url, namespace: &str, out: &mut FileSymbols) {
+    if let Some(left) = node.child_by_field_name("left")
+        && left.kind() == "variable_name"
+        && let Ok(name) = left.utf8_text(src.as_bytes())
+    {
+        let fqn = qualify(namespace, name);
+        out.insert(
+            fqn.clone(),
+            vec![Symbol {
+                name: fqn,
+                kind: SymbolKind::Variable,
+                location: node_location(uri, src, left),
+                container: None,
+                supertypes: Vec::new(),
+                type_hint: None,
+                doc: None,
+            }],
+        );
+    }
+}
+
+/// Record a definition, keeping earlier ones of the same name.
+fn define(out: &mut FileSymbols, symbol: Symbol) -> &mut Symbol {
+    let defs = out.entry(symbol.name.clone()).or_default();
+    defs.push(symbol);
+    defs.last_mut().expect("definition was just added")
+}
+
+fn node_location(uri: &Url, src: &str, node: Node) -> Location {
     Location {
         uri: uri.clone(),
-        range: Range {
-            start: Position {
-                line: start.row as u32,
-                character: start.column as u32,
-            },
-            end: Position {
-                line: end.row as u32,
-                character: end.column as u32,
-            },
-        },
+        range: parser::node_range(src, node),
     }
 }
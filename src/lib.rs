@@ -1,12 +1,22 @@
 pub mod analyzer;
+pub mod cache;
 pub mod composer;
 pub mod config;
+pub mod diagnostics;
+pub mod filter;
 pub mod fs;
+pub mod fuzzy;
+pub mod hover;
 pub mod indexer;
 pub mod laravel;
 pub mod logging;
 pub mod metrics;
+pub mod outline;
 pub mod parser;
+pub mod phpdoc;
 pub mod plugin;
+pub mod references;
 pub mod resolver;
 pub mod server;
+pub mod signature_help;
+pub mod stubs;
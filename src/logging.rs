@@ -18,7 +18,7 @@ impl log::Log for ClientLogger {
         }
         let msg = format!("[{}] {}", record.level(), record.args());
         if let Some(client) = CLIENT.lock().unwrap().clone() {
-            let _ = tokio::spawn(async move {
+            tokio::spawn(async move {
                 let _ = client.log_message(MessageType::LOG, msg).await;
             });
         } else {
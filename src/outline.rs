@@ -0,0 +1,360 @@
+use tower_lsp::lsp_types::{DocumentSymbol, SymbolKind};
+use tree_sitter::Node;
+
+use crate::indexer::{self, map_symbol_kind};
+use crate::parser::{self, Ast};
+
+/// Build the outline of a document: namespaces holding the functions,
+/// constants and class-like declarations in them, which in turn hold their
+/// methods, properties, constants and enum cases.
+pub fn document_symbols(src: &str, ast: &Ast) -> Vec<DocumentSymbol> {
+    let root = ast.0.root_node();
+    let mut out = Vec::new();
+    // `namespace Foo;` owns the statements up to the next namespace
+    let mut open: Option<DocumentSymbol> = None;
+    for i in 0..root.named_child_count() {
+        let Some(child) = root.named_child(i) else {
+            continue;
+        };
+        if child.kind() == "namespace_definition" {
+            out.extend(open.take());
+            let body = child.child_by_field_name("body");
+            let mut children = Vec::new();
+            if let Some(body) = body {
+                for j in 0..body.named_child_count() {
+                    if let Some(stmt) = body.named_child(j) {
+                        collect(src, stmt, &mut children);
+                    }
+                }
+            }
+            let Some(name) = child.child_by_field_name("name") else {
+                // `namespace { ... }` is the global namespace
+                out.extend(children);
+                continue;
+            };
+            let namespace = symbol(
+                src,
+                text(src, name),
+                None,
+                SymbolKind::NAMESPACE,
+                child,
+                name,
+                children,
+            );
+            if body.is_some() {
+                out.push(namespace);
+            } else {
+                open = Some(namespace);
+            }
+            continue;
+        }
+        match open.as_mut() {
+            Some(namespace) => {
+                let before = namespace.children.as_ref().map_or(0, Vec::len);
+                collect(src, child, namespace.children.get_or_insert_with(Vec::new));
+                if namespace.children.as_ref().map_or(0, Vec::len) > before {
+                    namespace.range.end = parser::node_range(src, child).end;
+                }
+            }
+            None => collect(src, child, &mut out),
+        }
+    }
+    out.extend(open);
+    for symbol in &mut out {
+        if symbol.children.as_ref().is_some_and(Vec::is_empty) {
+            symbol.children = None;
+        }
+    }
+    out
+}
+
+fn collect(src: &str, node: Node, out: &mut Vec<DocumentSymbol>) {
+    let kind = match node.kind() {
+        "function_definition" => {
+            if let Some(name) = node.child_by_field_name("name") {
+                let detail = signature(src, node);
+                out.push(symbol(
+                    src,
+                    text(src, name),
+                    Some(detail),
+                    SymbolKind::FUNCTION,
+                    node,
+                    name,
+                    Vec::new(),
+                ));
+            }
+            return;
+        }
+        "const_declaration" => {
+            constants(src, node, out);
+            return;
+        }
+        "if_statement" | "compound_statement" | "colon_block" | "else_clause"
+        | "else_if_clause" => {
+            for i in 0..node.named_child_count() {
+                if let Some(child) = node.named_child(i) {
+                    collect(src, child, out);
+                }
+            }
+            return;
+        }
+        "class_declaration" => indexer::SymbolKind::Class,
+        "interface_declaration" => indexer::SymbolKind::Interface,
+        "trait_declaration" => indexer::SymbolKind::Trait,
+        "enum_declaration" => indexer::SymbolKind::Enum,
+        _ => return,
+    };
+    let Some(name) = node.child_by_field_name("name") else {
+        return;
+    };
+    let mut members = Vec::new();
+    if let Some(body) = node.child_by_field_name("body") {
+        for i in 0..body.named_child_count() {
+            if let Some(member) = body.named_child(i) {
+                collect_member(src, member, &mut members);
+            }
+        }
+    }
+    out.push(symbol(
+        src,
+        text(src, name),
+        Some(class_detail(src, node)),
+        map_symbol_kind(&kind),
+        node,
+        name,
+        members,
+    ));
+}
+
+fn collect_member(src: &str, member: Node, out: &mut Vec<DocumentSymbol>) {
+    match member.kind() {
+        "method_declaration" => {
+            let Some(name) = member.child_by_field_name("name") else {
+                return;
+            };
+            let method = text(src, name);
+            let kind = if method.eq_ignore_ascii_case("__construct") {
+                SymbolKind::CONSTRUCTOR
+            } else {
+                SymbolKind::METHOD
+            };
+            let detail = join([modifiers(src, member), signature(src, member)]);
+            out.push(symbol(
+                src,
+                method,
+                Some(detail),
+                kind,
+                member,
+                name,
+                Vec::new(),
+            ));
+            if kind == SymbolKind::CONSTRUCTOR {
+                promoted_properties(src, member, out);
+            }
+        }
+        "property_declaration" => {
+            let ty = member
+                .child_by_field_name("type")
+                .map(|t| text(src, t))
+                .unwrap_or_default();
+            let detail = join([modifiers(src, member), ty]);
+            let elements = named_children(member, "property_element");
+            for element in &elements {
+                if let Some(name) = element.child_by_field_name("name") {
+                    let range = if elements.len() == 1 {
+                        member
+                    } else {
+                        *element
+                    };
+                    out.push(symbol(
+                        src,
+                        text(src, name),
+                        Some(detail.clone()),
+                        SymbolKind::PROPERTY,
+                        range,
+                        name,
+                        Vec::new(),
+                    ));
+                }
+            }
+        }
+        "const_declaration" => constants(src, member, out),
+        "enum_case" => {
+            if let Some(name) = member.child_by_field_name("name") {
+                let detail = member
+                    .child_by_field_name("value")
+                    .map(|v| format!("= {}", text(src, v)));
+                out.push(symbol(
+                    src,
+                    text(src, name),
+                    detail,
+                    SymbolKind::ENUM_MEMBER,
+                    member,
+                    name,
+                    Vec::new(),
+                ));
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Constructor parameters with a visibility modifier also declare properties.
+fn promoted_properties(src: &str, method: Node, out: &mut Vec<DocumentSymbol>) {
+    let Some(params) = method.child_by_field_name("parameters") else {
+        return;
+    };
+    for param in named_children(params, "property_promotion_parameter") {
+        if let Some(name) = param.child_by_field_name("name") {
+            let ty = param
+                .child_by_field_name("type")
+                .map(|t| text(src, t))
+                .unwrap_or_default();
+            out.push(symbol(
+                src,
+                text(src, name),
+                Some(join([modifiers(src, param), ty])),
+                SymbolKind::PROPERTY,
+                param,
+                name,
+                Vec::new(),
+            ));
+        }
+    }
+}
+
+fn constants(src: &str, node: Node, out: &mut Vec<DocumentSymbol>) {
+    let visibility = modifiers(src, node);
+    let elements = named_children(node, "const_element");
+    for element in &elements {
+        let Some(name) = element
+            .child_by_field_name("name")
+            .or_else(|| element.named_child(0))
+        else {
+            continue;
+        };
+        let value = element
+            .child_by_field_name("value")
+            .or_else(|| element.named_child(1))
+            .map(|v| format!("= {}", text(src, v)))
+            .unwrap_or_default();
+        let range = if elements.len() == 1 { node } else { *element };
+        out.push(symbol(
+            src,
+            text(src, name),
+            Some(join([visibility.clone(), value])),
+            SymbolKind::CONSTANT,
+            range,
+            name,
+            Vec::new(),
+        ));
+    }
+}
+
+/// `(int $a, string ...$rest): ?Foo` for a function or method.
+fn signature(src: &str, node: Node) -> String {
+    let params = node
+        .child_by_field_name("parameters")
+        .map(|params| {
+            (0..params.named_child_count())
+                .filter_map(|i| params.named_child(i))
+                .filter(|p| p.kind() != "comment")
+                .map(|p| text(src, p))
+                .collect::<Vec<_>>()
+                .join(", ")
+        })
+        .unwrap_or_default();
+    match node.child_by_field_name("return_type") {
+        Some(ret) => format!("({}): {}", params, text(src, ret)),
+        None => format!("({})", params),
+    }
+}
+
+/// Modifiers, parent class and interfaces of a class-like declaration, or
+/// the backing type of an enum.
+fn class_detail(src: &str, node: Node) -> String {
+    let mut parts = vec![modifiers(src, node)];
+    for i in 0..node.named_child_count() {
+        let Some(child) = node.named_child(i) else {
+            continue;
+        };
+        match child.kind() {
+            "base_clause" | "class_interface_clause" => {
+                let keyword = if child.kind() == "base_clause" {
+                    "extends"
+                } else {
+                    "implements"
+                };
+                let names: Vec<String> = (0..child.named_child_count())
+                    .filter_map(|j| child.named_child(j))
+                    .map(|n| text(src, n))
+                    .collect();
+                parts.push(format!("{} {}", keyword, names.join(", ")));
+            }
+            "primitive_type" if node.kind() == "enum_declaration" => {
+                parts.push(format!(": {}", text(src, child)));
+            }
+            _ => {}
+        }
+    }
+    join(parts)
+}
+
+/// Visibility, `static`, `abstract`, `final` and `readonly` modifiers in
+/// source order.
+fn modifiers(src: &str, node: Node) -> String {
+    let mut cursor = node.walk();
+    let parts: Vec<String> = node
+        .children(&mut cursor)
+        .filter(|c| c.kind().ends_with("_modifier") && c.kind() != "reference_modifier")
+        .map(|c| text(src, c))
+        .collect();
+    parts.join(" ")
+}
+
+fn named_children<'t>(node: Node<'t>, kind: &str) -> Vec<Node<'t>> {
+    (0..node.named_child_count())
+        .filter_map(|i| node.named_child(i))
+        .filter(|c| c.kind() == kind)
+        .collect()
+}
+
+fn join<I: IntoIterator<Item = String>>(parts: I) -> String {
+    parts
+        .into_iter()
+        .filter(|p| !p.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Source text of `node` with runs of whitespace collapsed, so multi-line
+/// parameters read as one line.
+fn text(src: &str, node: Node) -> String {
+    node.utf8_text(src.as_bytes())
+        .unwrap_or("")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[allow(deprecated)]
+fn symbol(
+    src: &str,
+    name: String,
+    detail: Option<String>,
+    kind: SymbolKind,
+    node: Node,
+    name_node: Node,
+    children: Vec<DocumentSymbol>,
+) -> DocumentSymbol {
+    DocumentSymbol {
+        name,
+        detail: detail.filter(|d| !d.is_empty()),
+        kind,
+        tags: None,
+        deprecated: None,
+        range: parser::node_range(src, node),
+        selection_range: parser::node_range(src, name_node),
+        children: (!children.is_empty()).then_some(children),
+    }
+}
@@ -1,17 +1,160 @@
-use bumpalo::Bump;
-use tree_sitter::{Parser, Tree};
+use std::cell::RefCell;
+
+use tower_lsp::lsp_types::{Position, Range, TextDocumentContentChangeEvent};
+use tree_sitter::{InputEdit, Node, Parser, Point, Tree};
 use tree_sitter_php::LANGUAGE_PHP;
 
 #[derive(Debug, Clone)]
 pub struct Ast(pub Tree);
 
-pub fn parse_php(input: &str, _bump: &Bump) -> Ast {
-    tracing::debug!("Parsing PHP source ({} bytes)", input.len());
+thread_local! {
+    /// One parser per thread, so rayon workers and tokio tasks parse without
+    /// contending on a lock or paying for `set_language` on every call.
+    static PARSER: RefCell<Parser> = RefCell::new(new_parser());
+}
+
+fn new_parser() -> Parser {
     let mut parser = Parser::new();
     parser
         .set_language(&LANGUAGE_PHP.into())
         .expect("Failed to load PHP grammar");
-    let tree = parser.parse(input, None).expect("Failed to parse");
+    parser
+}
+
+pub fn parse_php(input: &str) -> Ast {
+    reparse_php(input, None)
+}
+
+/// Parse `input`, reusing the unchanged parts of `old` when it has been
+/// brought in line with the new text through [`apply_change`].
+pub fn reparse_php(input: &str, old: Option<&Ast>) -> Ast {
+    tracing::debug!(
+        "Parsing PHP source ({} bytes, incremental: {})",
+        input.len(),
+        old.is_some()
+    );
+    let tree = PARSER.with(|parser| {
+        let mut parser = parser.borrow_mut();
+        let tree = parser.parse(input, old.map(|ast| &ast.0));
+        // a parse only fails when it is cancelled or times out, which
+        // leaves the parser mid-document
+        if tree.is_none() {
+            parser.reset();
+        }
+        tree
+    });
     tracing::debug!("Finished parsing");
-    Ast(tree)
+    Ast(tree.expect("Failed to parse"))
+}
+
+/// Apply one `textDocument/didChange` content change to `text`, editing
+/// `ast` to match so it can be handed to [`reparse_php`]. A change without
+/// a range replaces the whole document and drops the old tree.
+pub fn apply_change(
+    text: &mut String,
+    ast: &mut Option<Ast>,
+    change: &TextDocumentContentChangeEvent,
+) {
+    let Some(range) = change.range else {
+        *text = change.text.clone();
+        *ast = None;
+        return;
+    };
+    let start = point_at(text, range.start);
+    let old_end = point_at(text, range.end);
+    let start_byte = byte_offset(text, start);
+    let old_end_byte = byte_offset(text, old_end).max(start_byte);
+    text.replace_range(start_byte..old_end_byte, &change.text);
+
+    let new_end_position = match change.text.rfind('\n') {
+        Some(i) => Point {
+            row: start.row + change.text.matches('\n').count(),
+            column: change.text.len() - i - 1,
+        },
+        None => Point {
+            row: start.row,
+            column: start.column + change.text.len(),
+        },
+    };
+    if let Some(ast) = ast {
+        ast.0.edit(&InputEdit {
+            start_byte,
+            old_end_byte,
+            new_end_byte: start_byte + change.text.len(),
+            start_position: start,
+            old_end_position: old_end,
+            new_end_position,
+        });
+    }
+}
+
+/// Convert an LSP position (UTF-16 column) into a tree-sitter point (byte
+/// column), clamping to the end of the line or document.
+pub(crate) fn point_at(text: &str, pos: Position) -> Point {
+    let mut lines = text.split('\n');
+    let Some(line) = lines.nth(pos.line as usize) else {
+        let row = text.matches('\n').count();
+        let column = text.len() - text.rfind('\n').map(|i| i + 1).unwrap_or(0);
+        return Point { row, column };
+    };
+    let mut units = 0;
+    let mut column = line.len();
+    for (i, c) in line.char_indices() {
+        if units >= pos.character as usize {
+            column = i;
+            break;
+        }
+        units += c.len_utf16();
+    }
+    Point {
+        row: pos.line as usize,
+        column,
+    }
+}
+
+/// Convert a tree-sitter point (byte column) into an LSP position (UTF-16
+/// column), the inverse of [`point_at`].
+pub(crate) fn position_at(text: &str, point: Point) -> Position {
+    let line = text.split('\n').nth(point.row).unwrap_or("");
+    Position {
+        line: point.row as u32,
+        character: utf16_column(line, point.column),
+    }
+}
+
+/// The LSP range `node` spans in `text`, the source it was parsed from.
+pub(crate) fn node_range(text: &str, node: Node) -> Range {
+    // the line a node starts or ends on begins `column` bytes before it,
+    // so there is no need to look the line up
+    let position = |byte: usize, point: Point| {
+        let line = text.get(byte.saturating_sub(point.column)..).unwrap_or("");
+        Position {
+            line: point.row as u32,
+            character: utf16_column(line, point.column),
+        }
+    };
+    Range {
+        start: position(node.start_byte(), node.start_position()),
+        end: position(node.end_byte(), node.end_position()),
+    }
+}
+
+/// The UTF-16 length of the first `column` bytes of `line`, rounded down to
+/// a character boundary.
+fn utf16_column(line: &str, column: usize) -> u32 {
+    let mut end = column.min(line.len());
+    while !line.is_char_boundary(end) {
+        end -= 1;
+    }
+    line[..end].encode_utf16().count() as u32
+}
+
+/// The byte offset of a tree-sitter point in `text`.
+pub fn byte_offset(text: &str, point: Point) -> usize {
+    let line_start: usize = text
+        .split_inclusive('\n')
+        .take(point.row)
+        .map(str::len)
+        .sum();
+    line_start + point.column
 }
@@ -0,0 +1,722 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use tree_sitter::Node;
+
+/// Type keywords and pseudo-types that never name a class.
+const KEYWORDS: &[&str] = &[
+    "int", "integer", "float", "double", "string", "bool", "boolean", "true", "false", "null",
+    "void", "never", "mixed", "array", "list", "iterable", "callable", "object", "resource",
+    "scalar", "numeric",
+];
+
+/// A type written in a docblock, such as `?User`, `int|string`,
+/// `array<int, User>`, `User[]` or `array{id: int, name?: string}`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DocType {
+    /// A class, keyword or literal, with its generic arguments:
+    /// `User`, `int`, `'draft'`, `Collection<User>`.
+    Named {
+        name: String,
+        args: Vec<DocType>,
+    },
+    Nullable(Box<DocType>),
+    Union(Vec<DocType>),
+    Intersection(Vec<DocType>),
+    /// `User[]`
+    Array(Box<DocType>),
+    /// `array{id: int, name?: string}` or the list form `array{int, string}`.
+    Shape(Vec<ShapeEntry>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ShapeEntry {
+    pub key: Option<String>,
+    pub optional: bool,
+    pub ty: DocType,
+}
+
+/// A tag naming a type and usually a variable: `@var`, `@param`,
+/// `@return`, `@throws` and `@property`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Tag {
+    pub ty: Option<DocType>,
+    /// The variable or property name, with its `$`.
+    pub name: Option<String>,
+    pub description: String,
+    /// The line of the tag, counted from the first line of the docblock.
+    pub line: u32,
+}
+
+/// A `@method [static] [Type] name(params)` tag.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MethodTag {
+    pub is_static: bool,
+    pub returns: Option<DocType>,
+    pub name: String,
+    pub params: Vec<Tag>,
+    pub description: String,
+    pub line: u32,
+}
+
+/// A `@template T of Bound` tag.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Template {
+    pub name: String,
+    pub bound: Option<DocType>,
+}
+
+/// A parsed `/** ... */` comment.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DocBlock {
+    /// The first paragraph.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub summary: String,
+    /// The text between the summary and the first tag.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub description: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub vars: Vec<Tag>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub params: Vec<Tag>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub returns: Option<Tag>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub throws: Vec<Tag>,
+    /// `@property`, `@property-read` and `@property-write`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub properties: Vec<Tag>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub methods: Vec<MethodTag>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub templates: Vec<Template>,
+    /// The deprecation message, empty when the tag has none.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deprecated: Option<String>,
+}
+
+impl DocBlock {
+    /// The `@param` tag of the parameter `name`, given with its `$`.
+    pub fn param(&self, name: &str) -> Option<&Tag> {
+        self.params.iter().find(|p| p.name.as_deref() == Some(name))
+    }
+
+    /// The `@var` tag applying to `name`: one naming it or an unnamed one.
+    pub fn var(&self, name: &str) -> Option<&Tag> {
+        self.vars
+            .iter()
+            .find(|v| v.name.as_deref() == Some(name))
+            .or_else(|| self.vars.iter().find(|v| v.name.is_none()))
+    }
+
+    /// The type of one of the docblock's tags, flattened with
+    /// [`DocType::hint`]. The docblock's own templates and the `outer` ones,
+    /// such as those of the enclosing class, are erased.
+    pub fn hint(&self, tag: Option<&Tag>, outer: &[Template]) -> Option<String> {
+        let ty = tag?.ty.as_ref()?;
+        let mut templates = outer.to_vec();
+        templates.extend(self.templates.iter().cloned());
+        Some(ty.hint(&templates))
+    }
+
+    /// Rewrite every class name in the docblock's types with `qualify`.
+    /// Keywords, pseudo-types, literals and the names in `templates` or the
+    /// docblock's own `@template` tags are left alone.
+    pub fn qualify(&mut self, templates: &[Template], qualify: &impl Fn(&str) -> String) {
+        let mut skip: Vec<String> = templates.iter().map(|t| t.name.clone()).collect();
+        skip.extend(self.templates.iter().map(|t| t.name.clone()));
+        let visit = |ty: &mut Option<DocType>| {
+            if let Some(ty) = ty {
+                ty.qualify(&skip, qualify);
+            }
+        };
+        for tag in self
+            .vars
+            .iter_mut()
+            .chain(&mut self.params)
+            .chain(&mut self.returns)
+            .chain(&mut self.throws)
+            .chain(&mut self.properties)
+        {
+            visit(&mut tag.ty);
+        }
+        for method in &mut self.methods {
+            visit(&mut method.returns);
+            for param in &mut method.params {
+                visit(&mut param.ty);
+            }
+        }
+        for template in &mut self.templates {
+            visit(&mut template.bound);
+        }
+    }
+}
+
+impl DocType {
+    /// Flatten the type into the form [`crate::resolver::declared_type`]
+    /// produces, such as `?App\User` or `App\A|(App\B&App\C)`. Generic
+    /// arguments are dropped, arrays and shapes become `array`, pseudo-types
+    /// their base type, and templates their bound or `mixed`.
+    pub fn hint(&self, templates: &[Template]) -> String {
+        match self {
+            DocType::Named { name, .. } => {
+                if let Some(template) = templates.iter().find(|t| &t.name == name) {
+                    return match &template.bound {
+                        Some(bound) => bound.hint(&[]),
+                        None => "mixed".to_string(),
+                    };
+                }
+                let lower = name.to_ascii_lowercase();
+                let base = match lower.as_str() {
+                    _ if name.starts_with(['\'', '"']) => "string",
+                    _ if lower
+                        .trim_start_matches('-')
+                        .starts_with(|c: char| c.is_ascii_digit()) =>
+                    {
+                        if lower.contains('.') {
+                            "float"
+                        } else {
+                            "int"
+                        }
+                    }
+                    "list" | "non-empty-list" | "non-empty-array" => "array",
+                    "integer" => "int",
+                    "double" => "float",
+                    "boolean" => "bool",
+                    _ if lower.contains('-') && lower.ends_with("string") => "string",
+                    _ if lower.contains('-') && lower.ends_with("int") => "int",
+                    _ if lower.contains('-') => "mixed",
+                    _ => name,
+                };
+                base.to_string()
+            }
+            DocType::Nullable(inner) => format!("?{}", inner.hint(templates)),
+            DocType::Union(types) => types
+                .iter()
+                .map(|ty| match ty {
+                    DocType::Intersection(_) => format!("({})", ty.hint(templates)),
+                    _ => ty.hint(templates),
+                })
+                .collect::<Vec<_>>()
+                .join("|"),
+            DocType::Intersection(types) => types
+                .iter()
+                .map(|ty| ty.hint(templates))
+                .collect::<Vec<_>>()
+                .join("&"),
+            DocType::Array(_) | DocType::Shape(_) => "array".to_string(),
+        }
+    }
+
+    fn qualify(&mut self, templates: &[String], qualify: &impl Fn(&str) -> String) {
+        match self {
+            DocType::Named { name, args } => {
+                if is_class_name(name) && !templates.contains(name) {
+                    *name = qualify(name);
+                }
+                for arg in args {
+                    arg.qualify(templates, qualify);
+                }
+            }
+            DocType::Nullable(inner) | DocType::Array(inner) => inner.qualify(templates, qualify),
+            DocType::Union(types) | DocType::Intersection(types) => {
+                for ty in types {
+                    ty.qualify(templates, qualify);
+                }
+            }
+            DocType::Shape(entries) => {
+                for entry in entries {
+                    entry.ty.qualify(templates, qualify);
+                }
+            }
+        }
+    }
+}
+
+fn is_class_name(name: &str) -> bool {
+    !name.contains('-')
+        && !name.starts_with(['\'', '"'])
+        && !name.starts_with(|c: char| c.is_ascii_digit())
+        && !KEYWORDS.contains(&name.to_ascii_lowercase().as_str())
+}
+
+impl fmt::Display for DocType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let join = |f: &mut fmt::Formatter<'_>, types: &[DocType], separator: &str| {
+            for (i, ty) in types.iter().enumerate() {
+                if i > 0 {
+                    f.write_str(separator)?;
+                }
+                match ty {
+                    DocType::Union(_) | DocType::Intersection(_) => write!(f, "({})", ty)?,
+                    _ => write!(f, "{}", ty)?,
+                }
+            }
+            Ok(())
+        };
+        match self {
+            DocType::Named { name, args } => {
+                f.write_str(name)?;
+                if !args.is_empty() {
+                    f.write_str("<")?;
+                    join(f, args, ", ")?;
+                    f.write_str(">")?;
+                }
+                Ok(())
+            }
+            DocType::Nullable(inner) => write!(f, "?{}", inner),
+            DocType::Union(types) => join(f, types, "|"),
+            DocType::Intersection(types) => join(f, types, "&"),
+            DocType::Array(inner) => match **inner {
+                DocType::Union(_) | DocType::Intersection(_) => write!(f, "({})[]", inner),
+                _ => write!(f, "{}[]", inner),
+            },
+            DocType::Shape(entries) => {
+                f.write_str("array{")?;
+                for (i, entry) in entries.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    if let Some(key) = &entry.key {
+                        write!(f, "{}{}: ", key, if entry.optional { "?" } else { "" })?;
+                    }
+                    write!(f, "{}", entry.ty)?;
+                }
+                f.write_str("}")
+            }
+        }
+    }
+}
+
+/// The `/** ... */` comment directly before a declaration or statement.
+pub fn comment_before<'s>(node: Node, src: &'s str) -> Option<&'s str> {
+    let comment = node.prev_named_sibling()?;
+    if comment.kind() != "comment" {
+        return None;
+    }
+    let text = comment.utf8_text(src.as_bytes()).ok()?;
+    text.starts_with("/**").then_some(text)
+}
+
+/// The docblock directly before `node`, parsed.
+pub fn doc_before(node: Node, src: &str) -> Option<DocBlock> {
+    comment_before(node, src).map(parse)
+}
+
+/// Parse a `/** ... */` comment. Unknown tags are ignored and tags whose
+/// type can't be parsed keep their name and description.
+pub fn parse(comment: &str) -> DocBlock {
+    let body = comment.trim();
+    let body = body.strip_prefix("/**").unwrap_or(body);
+    let body = body.strip_suffix("*/").unwrap_or(body);
+    let lines: Vec<&str> = body
+        .lines()
+        .map(|line| {
+            let line = line.trim();
+            let line = line.strip_prefix('*').unwrap_or(line);
+            line.strip_prefix(' ').unwrap_or(line).trim_end()
+        })
+        .collect();
+
+    let mut doc = DocBlock::default();
+    let mut text: Vec<&str> = Vec::new();
+    // each tag runs until the next line starting with `@`
+    let mut tags: Vec<(u32, String)> = Vec::new();
+    for (i, line) in lines.iter().enumerate() {
+        if line.starts_with('@') {
+            tags.push((i as u32, line.to_string()));
+        } else if let Some((_, tag)) = tags.last_mut() {
+            tag.push('\n');
+            tag.push_str(line);
+        } else {
+            text.push(line);
+        }
+    }
+    let text = text.join("\n");
+    let text = text.trim();
+    match text.split_once("\n\n") {
+        Some((summary, description)) => {
+            doc.summary = summary.trim().to_string();
+            doc.description = description.trim().to_string();
+        }
+        None => doc.summary = text.to_string(),
+    }
+    for (line, tag) in tags {
+        parse_tag(&mut doc, &tag, line);
+    }
+    doc
+}
+
+fn parse_tag(doc: &mut DocBlock, tag: &str, line: u32) {
+    let (name, rest) = tag[1..]
+        .split_once(char::is_whitespace)
+        .unwrap_or((&tag[1..], ""));
+    let mut cursor = Cursor::new(rest);
+    match name {
+        "var" | "param" | "property" | "property-read" | "property-write" => {
+            cursor.skip_ws();
+            let ty = if cursor.at_variable() {
+                None
+            } else {
+                cursor.ty()
+            };
+            cursor.skip_ws();
+            let variable = cursor.variable();
+            let tag = Tag {
+                ty,
+                name: variable,
+                description: cursor.rest(),
+                line,
+            };
+            match name {
+                "var" => doc.vars.push(tag),
+                "param" => doc.params.push(tag),
+                _ => doc.properties.push(tag),
+            }
+        }
+        "return" | "throws" => {
+            let tag = Tag {
+                ty: cursor.ty(),
+                name: None,
+                description: cursor.rest(),
+                line,
+            };
+            if name == "return" {
+                doc.returns = Some(tag);
+            } else {
+                doc.throws.push(tag);
+            }
+        }
+        "method" => {
+            if let Some(method) = parse_method(&mut cursor, line) {
+                doc.methods.push(method);
+            }
+        }
+        "template" | "template-covariant" | "template-contravariant" => {
+            cursor.skip_ws();
+            let Some(name) = cursor.word() else {
+                return;
+            };
+            cursor.skip_ws();
+            let bound = match cursor.word().as_deref() {
+                Some("of" | "as") => cursor.ty(),
+                _ => None,
+            };
+            doc.templates.push(Template { name, bound });
+        }
+        "deprecated" => doc.deprecated = Some(rest.trim().to_string()),
+        _ => {}
+    }
+}
+
+fn parse_method(cursor: &mut Cursor, line: u32) -> Option<MethodTag> {
+    let mut method = MethodTag {
+        line,
+        ..MethodTag::default()
+    };
+    // `static` followed by another type or the name is the modifier
+    let mut first = cursor.ty()?;
+    cursor.skip_ws();
+    if first == DocType::named("static") && !cursor.at('(') {
+        method.is_static = true;
+        first = cursor.ty()?;
+        cursor.skip_ws();
+    }
+    if cursor.at('(') {
+        // no return type, `first` is the name
+        let DocType::Named { name, .. } = first else {
+            return None;
+        };
+        method.name = name;
+    } else {
+        method.returns = Some(first);
+        method.name = cursor.word()?;
+        cursor.skip_ws();
+    }
+    if cursor.eat('(') {
+        loop {
+            cursor.skip_ws();
+            if cursor.eat(')') || cursor.is_done() {
+                break;
+            }
+            let ty = if cursor.at_variable() {
+                None
+            } else {
+                cursor.ty()
+            };
+            cursor.skip_ws();
+            let name = cursor.variable();
+            cursor.skip_until(&[',', ')']);
+            method.params.push(Tag {
+                ty,
+                name,
+                ..Tag::default()
+            });
+            cursor.eat(',');
+        }
+    }
+    method.description = cursor.rest();
+    Some(method)
+}
+
+/// Parse a docblock type on its own, such as `array<int, User>|null`.
+pub fn parse_type(text: &str) -> Option<DocType> {
+    let mut cursor = Cursor::new(text);
+    let ty = cursor.ty()?;
+    cursor.skip_ws();
+    cursor.is_done().then_some(ty)
+}
+
+impl DocType {
+    fn named(name: &str) -> Self {
+        DocType::Named {
+            name: name.to_string(),
+            args: Vec::new(),
+        }
+    }
+}
+
+/// A position in the text of a tag.
+struct Cursor {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl Cursor {
+    fn new(text: &str) -> Self {
+        Cursor {
+            chars: text.chars().collect(),
+            pos: 0,
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn at(&self, c: char) -> bool {
+        self.peek() == Some(c)
+    }
+
+    fn is_done(&self) -> bool {
+        self.pos >= self.chars.len()
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        let found = self.at(c);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn skip_ws(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.pos += 1;
+        }
+    }
+
+    /// Whether the next non-blank character is `c`, without consuming
+    /// anything.
+    fn next_is(&self, c: char) -> bool {
+        self.chars[self.pos..]
+            .iter()
+            .find(|c| !c.is_whitespace())
+            .is_some_and(|&next| next == c)
+    }
+
+    /// Whether a by-reference, variadic or plain variable name comes next.
+    fn at_variable(&self) -> bool {
+        matches!(self.peek(), Some('$' | '&')) || self.chars[self.pos..].starts_with(&['.'; 3])
+    }
+
+    fn rest(&mut self) -> String {
+        let rest: String = self.chars[self.pos..].iter().collect();
+        self.pos = self.chars.len();
+        rest.trim().to_string()
+    }
+
+    fn skip_until(&mut self, stops: &[char]) {
+        let mut depth = 0usize;
+        while let Some(c) = self.peek() {
+            match c {
+                '(' | '<' | '{' | '[' => depth += 1,
+                ')' | '>' | '}' | ']' if depth > 0 => depth -= 1,
+                _ if depth == 0 && stops.contains(&c) => return,
+                _ => {}
+            }
+            self.pos += 1;
+        }
+    }
+
+    /// A name made of letters, digits, `_`, `\` and `-`, like `App\User`,
+    /// `int` or `class-string`.
+    fn word(&mut self) -> Option<String> {
+        let start = self.pos;
+        while self
+            .peek()
+            .is_some_and(|c| c.is_alphanumeric() || matches!(c, '_' | '\\' | '-'))
+        {
+            self.pos += 1;
+        }
+        (self.pos > start).then(|| self.chars[start..self.pos].iter().collect())
+    }
+
+    /// `$name`, `&$name` or `...$name`, returned with its `$`.
+    fn variable(&mut self) -> Option<String> {
+        self.eat('&');
+        while self.eat('.') {}
+        if !self.eat('$') {
+            return None;
+        }
+        self.word().map(|name| format!("${}", name))
+    }
+
+    fn ty(&mut self) -> Option<DocType> {
+        self.skip_ws();
+        let mut types = vec![self.intersection()?];
+        while self.next_is('|') {
+            self.skip_ws();
+            self.eat('|');
+            self.skip_ws();
+            types.push(self.intersection()?);
+        }
+        Some(if types.len() == 1 {
+            types.remove(0)
+        } else {
+            DocType::Union(types)
+        })
+    }
+
+    fn intersection(&mut self) -> Option<DocType> {
+        let mut types = vec![self.postfix()?];
+        loop {
+            let save = self.pos;
+            self.skip_ws();
+            // `Foo &$bar` passes `$bar` by reference
+            if !self.eat('&') {
+                self.pos = save;
+                break;
+            }
+            self.skip_ws();
+            if self.at_variable() {
+                self.pos = save;
+                break;
+            }
+            types.push(self.postfix()?);
+        }
+        Some(if types.len() == 1 {
+            types.remove(0)
+        } else {
+            DocType::Intersection(types)
+        })
+    }
+
+    fn postfix(&mut self) -> Option<DocType> {
+        let mut ty = if self.eat('?') {
+            DocType::Nullable(Box::new(self.postfix()?))
+        } else {
+            self.atom()?
+        };
+        while self.chars[self.pos..].starts_with(&['[', ']']) {
+            self.pos += 2;
+            ty = DocType::Array(Box::new(ty));
+        }
+        Some(ty)
+    }
+
+    fn atom(&mut self) -> Option<DocType> {
+        match self.peek()? {
+            '(' => {
+                self.pos += 1;
+                let ty = self.ty()?;
+                self.skip_ws();
+                self.eat(')').then_some(ty)
+            }
+            quote @ ('\'' | '"') => {
+                let start = self.pos;
+                self.pos += 1;
+                while self.peek().is_some_and(|c| c != quote) {
+                    self.pos += 1;
+                }
+                self.eat(quote);
+                Some(DocType::named(
+                    &self.chars[start..self.pos].iter().collect::<String>(),
+                ))
+            }
+            _ => {
+                let this = self.eat('$');
+                let name = self.word()?;
+                let name = if this { format!("${}", name) } else { name };
+                let lower = name.to_ascii_lowercase();
+                if self.at('{') && matches!(lower.as_str(), "array" | "list" | "object") {
+                    return self.shape();
+                }
+                if self.at('(') && matches!(lower.as_str(), "callable" | "closure" | "\\closure") {
+                    // the signature isn't kept, only that it's callable
+                    self.pos += 1;
+                    self.skip_until(&[')']);
+                    self.eat(')');
+                    let save = self.pos;
+                    self.skip_ws();
+                    if self.eat(':') {
+                        self.skip_ws();
+                        self.postfix()?;
+                    } else {
+                        self.pos = save;
+                    }
+                    return Some(DocType::named(&name));
+                }
+                let mut args = Vec::new();
+                if self.eat('<') {
+                    loop {
+                        args.push(self.ty()?);
+                        self.skip_ws();
+                        if self.eat('>') {
+                            break;
+                        }
+                        if !self.eat(',') {
+                            return None;
+                        }
+                    }
+                }
+                Some(DocType::Named { name, args })
+            }
+        }
+    }
+
+    /// `{key: Type, other?: Type}` after `array`.
+    fn shape(&mut self) -> Option<DocType> {
+        self.eat('{');
+        let mut entries = Vec::new();
+        loop {
+            self.skip_ws();
+            if self.eat('}') {
+                break;
+            }
+            let save = self.pos;
+            let key = match self.peek()? {
+                '\'' | '"' => self.atom().map(|k| k.to_string()),
+                _ => self.word(),
+            };
+            let optional = key.is_some() && self.eat('?');
+            self.skip_ws();
+            let key = if key.is_some() && self.eat(':') {
+                key
+            } else {
+                self.pos = save;
+                None
+            };
+            let ty = self.ty()?;
+            entries.push(ShapeEntry { key, optional, ty });
+            self.skip_ws();
+            if self.eat('}') {
+                break;
+            }
+            if !self.eat(',') {
+                return None;
+            }
+        }
+        Some(DocType::Shape(entries))
+    }
+}
@@ -5,6 +5,7 @@ pub trait Plugin: Send + Sync {
     fn register(&self, _index: &GlobalIndex) {}
 }
 
+#[derive(Default)]
 pub struct PluginManager {
     plugins: Vec<Box<dyn Plugin>>,
 }
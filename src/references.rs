@@ -0,0 +1,498 @@
+use std::ops::Range as ByteRange;
+
+use tower_lsp::lsp_types::{Location, Position, Range, TextEdit, Url};
+use tree_sitter::Node;
+
+use crate::analyzer::TypeCache;
+use crate::indexer::{FileSymbols, GlobalIndex, Layer};
+use crate::parser;
+use crate::resolver::{self, DocumentContext, ImportTable, ResolvedSymbol};
+
+/// What a reference search looks for.
+#[derive(Debug, Clone)]
+pub enum Target {
+    /// A class, function, constant or class member, matched by the
+    /// definition each candidate name resolves to.
+    Symbol(ResolvedSymbol),
+    /// A variable, matched by name within the function it belongs to.
+    Variable {
+        uri: Url,
+        name: String,
+        scope: ByteRange<usize>,
+    },
+}
+
+impl Target {
+    /// The unqualified name occurrences have to spell, without the `$` of
+    /// variables and properties.
+    fn short_name(&self) -> &str {
+        let name = match self {
+            Target::Symbol(sym) => {
+                let name = sym.name.rsplit("::").next().unwrap_or(&sym.name);
+                name.rsplit('\\').next().unwrap_or(name)
+            }
+            Target::Variable { name, .. } => name,
+        };
+        name.trim_start_matches('$')
+    }
+
+    /// Whether files other than the one the search started in can refer to
+    /// the target.
+    pub fn is_global(&self) -> bool {
+        matches!(self, Target::Symbol(_))
+    }
+}
+
+/// The symbol the name at `position` refers to or declares. `$this` and
+/// names that don't resolve have no target. Members of objects other than
+/// `$this` resolve through the object's inferred type.
+pub fn target_at(
+    doc: &DocumentContext,
+    position: Position,
+    global: &GlobalIndex,
+    types: &TypeCache,
+) -> Option<Target> {
+    let node = resolver::name_node_at(doc.ast, parser::point_at(doc.src, position))?;
+    if node.kind() == "variable_name" {
+        let name = text(node, doc.src);
+        if name == "$this" {
+            return None;
+        }
+        if let Some(scope) = variable_scope(node, doc.src) {
+            return Some(Target::Variable {
+                uri: doc.uri.clone(),
+                name: name.to_string(),
+                scope: scope.byte_range(),
+            });
+        }
+    }
+    if let Some(decl) = declaration_of(node) {
+        // a declaration resolves through the symbol it defines
+        let sym = doc.symbols.values().flatten().find(|s| {
+            s.location == location(doc.uri, doc.src, decl)
+                || s.location == location(doc.uri, doc.src, node)
+        })?;
+        let name = format!("\\{}", sym.name);
+        return resolver::resolve_in_document(&name, position, doc, global).map(Target::Symbol);
+    }
+    resolve(node, doc, global, types).map(Target::Symbol)
+}
+
+/// One place a target is named.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Occurrence {
+    /// The range of the name itself: the last segment of a qualified name,
+    /// the member of `Foo::bar`, a variable including its `$`.
+    pub range: Range,
+    /// The name as written, which is an alias rather than the target's own
+    /// name where the target is imported under one.
+    pub text: String,
+    pub declaration: bool,
+}
+
+/// Every occurrence of `target` in one document. Names are resolved through
+/// the document's namespace and imports, and members through the inferred
+/// type of their object, so `foo` inside `foobar`, strings, comments and
+/// same-named symbols of other namespaces or classes don't match.
+pub fn occurrences_in_document(
+    target: &Target,
+    doc: &DocumentContext,
+    global: &GlobalIndex,
+    types: &TypeCache,
+) -> Vec<Occurrence> {
+    let mut out = Vec::new();
+    let short = target.short_name();
+    let aliases = match target {
+        Target::Symbol(sym) => doc.imports.aliases_of(&sym.name),
+        Target::Variable { uri, .. } if uri != doc.uri => return out,
+        Target::Variable { .. } => Vec::new(),
+    };
+    walk(doc.ast.0.root_node(), &mut |node| {
+        if let Some(word) = candidate(node, doc.src)
+            && (word.eq_ignore_ascii_case(short)
+                || aliases.iter().any(|a| a.eq_ignore_ascii_case(word)))
+            && let Some(declaration) = occurrence(target, node, doc, global, types)
+        {
+            let name = last_segment(node);
+            out.push(Occurrence {
+                range: location(doc.uri, doc.src, name).range,
+                text: text(name, doc.src).to_string(),
+                declaration,
+            });
+        }
+    });
+    out
+}
+
+/// [`occurrences_in_document`] for a file that isn't open in the editor,
+/// read and parsed from disk. Files that can't be read or don't mention the
+/// target's name yield nothing. Types are inferred afresh rather than kept
+/// in a cache that would outlive the search.
+pub fn occurrences_in_file(target: &Target, uri: &Url, global: &GlobalIndex) -> Vec<Occurrence> {
+    let Ok(path) = uri.to_file_path() else {
+        return Vec::new();
+    };
+    let Ok(src) = std::fs::read_to_string(&path) else {
+        return Vec::new();
+    };
+    // aliases are imported by a `use` clause spelling out the name, so a
+    // file that never mentions it can't refer to the target
+    if !src
+        .to_ascii_lowercase()
+        .contains(&target.short_name().to_ascii_lowercase())
+    {
+        return Vec::new();
+    }
+    let ast = parser::parse_php(&src);
+    let symbols: FileSymbols = global.get(uri).map(|s| s.clone()).unwrap_or_default();
+    let imports = ImportTable::build(&src, &ast);
+    let doc = DocumentContext {
+        uri,
+        src: &src,
+        ast: &ast,
+        symbols: &symbols,
+        imports: &imports,
+    };
+    occurrences_in_document(target, &doc, global, &TypeCache::new())
+}
+
+/// The locations of the occurrences found in the file at `uri`, leaving out
+/// the declaration unless `include_declaration`.
+pub fn locations(
+    uri: &Url,
+    occurrences: Vec<Occurrence>,
+    include_declaration: bool,
+) -> Vec<Location> {
+    occurrences
+        .into_iter()
+        .filter(|o| include_declaration || !o.declaration)
+        .map(|o| Location {
+            uri: uri.clone(),
+            range: o.range,
+        })
+        .collect()
+}
+
+/// Check that the name at `position` can be renamed and return what it
+/// refers to together with the range to rename. Variables can always be
+/// renamed; other names have to resolve to a symbol declared in the project
+/// rather than a built-in, vendor code or a magic member. Promoted
+/// constructor properties can't be renamed either, as the constructor's
+/// variable and named arguments would have to follow.
+pub fn prepare_rename(
+    doc: &DocumentContext,
+    position: Position,
+    global: &GlobalIndex,
+    types: &TypeCache,
+) -> Result<(Target, Range), &'static str> {
+    let node = resolver::name_node_at(doc.ast, parser::point_at(doc.src, position))
+        .ok_or("No symbol to rename")?;
+    let target = target_at(doc, position, global, types)
+        .ok_or("The name does not refer to a known symbol")?;
+    if let Target::Symbol(sym) = &target {
+        match global.layer(&sym.location.uri) {
+            Layer::Project => {}
+            Layer::Vendor => return Err("Symbols declared in vendor code can't be renamed"),
+            Layer::Stub => return Err("Built-in symbols can't be renamed"),
+        }
+        if target.short_name().starts_with("__") {
+            return Err("Magic members can't be renamed");
+        }
+        if is_promoted(sym, global) {
+            return Err("Promoted constructor properties can't be renamed");
+        }
+    }
+    if let Target::Variable { name, scope, .. } = &target
+        && let Some(function) = doc
+            .ast
+            .0
+            .root_node()
+            .descendant_for_byte_range(scope.start, scope.end)
+        && promotes(function, name, doc.src)
+    {
+        return Err("Promoted constructor properties can't be renamed");
+    }
+    Ok((target, location(doc.uri, doc.src, last_segment(node)).range))
+}
+
+/// Whether `sym` is a property promoted from a constructor parameter, which
+/// is also a variable of the constructor and the name of an argument.
+fn is_promoted(sym: &ResolvedSymbol, global: &GlobalIndex) -> bool {
+    let Some((class, _)) = sym.name.rsplit_once("::") else {
+        return false;
+    };
+    let range = sym.location.range;
+    global
+        .definitions(&format!("{}::__construct", class))
+        .iter()
+        .any(|(_, ctor)| {
+            let outer = ctor.location.range;
+            ctor.location.uri == sym.location.uri
+                && outer.start <= range.start
+                && range.end <= outer.end
+        })
+}
+
+/// Whether `function` promotes its parameter `name` to a property.
+fn promotes(function: Node, name: &str, src: &str) -> bool {
+    function
+        .child_by_field_name("parameters")
+        .is_some_and(|params| {
+            (0..params.named_child_count())
+                .filter_map(|i| params.named_child(i))
+                .filter(|p| p.kind() == "property_promotion_parameter")
+                .filter_map(|p| p.child_by_field_name("name"))
+                .any(|n| text(n, src) == name)
+        })
+}
+
+/// Whether `name` can replace a PHP identifier. A leading `$` is accepted
+/// and ignored, so variables can be renamed with or without it.
+pub fn is_valid_name(name: &str) -> bool {
+    let name = name.strip_prefix('$').unwrap_or(name);
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c == '_' || c.is_alphabetic() || !c.is_ascii())
+        && chars.all(|c| c == '_' || c.is_alphanumeric() || !c.is_ascii())
+        && name != "this"
+}
+
+/// The edits renaming `target` to `new_name` at the occurrences found in one
+/// file. Names written as an alias of the target are left alone: the `use`
+/// clause the alias comes from is renamed instead.
+pub fn rename_edits(target: &Target, occurrences: &[Occurrence], new_name: &str) -> Vec<TextEdit> {
+    let short = target.short_name();
+    let new_name = new_name.trim_start_matches('$');
+    occurrences
+        .iter()
+        .filter(|o| o.text.trim_start_matches('$').eq_ignore_ascii_case(short))
+        .map(|o| TextEdit {
+            range: o.range,
+            new_text: if o.text.starts_with('$') {
+                format!("${}", new_name)
+            } else {
+                new_name.to_string()
+            },
+        })
+        .collect()
+}
+
+/// The name a `name`, `qualified_name` or `variable_name` node spells when
+/// it is a standalone name worth resolving: the last segment of qualified
+/// names, variables and properties without their `$`.
+fn candidate<'a>(node: Node, src: &'a str) -> Option<&'a str> {
+    let parent = node.parent();
+    match node.kind() {
+        "name" => {
+            let skipped = parent.is_some_and(|p| match p.kind() {
+                "qualified_name" | "variable_name" | "namespace_name" => true,
+                // named arguments and import aliases don't refer to anything
+                "argument" => p.child_by_field_name("name") == Some(node),
+                "namespace_use_clause" => p.child_by_field_name("alias") == Some(node),
+                _ => false,
+            });
+            if skipped {
+                return None;
+            }
+            Some(text(node, src))
+        }
+        "qualified_name" => Some(text(last_segment(node), src)),
+        "variable_name" => Some(text(node, src).trim_start_matches('$')),
+        _ => None,
+    }
+}
+
+/// Whether `node` is an occurrence of `target`, and if so whether it is the
+/// target's declaration.
+fn occurrence(
+    target: &Target,
+    node: Node,
+    doc: &DocumentContext,
+    global: &GlobalIndex,
+    types: &TypeCache,
+) -> Option<bool> {
+    match target {
+        Target::Variable { name, scope, .. } => {
+            if node.kind() != "variable_name"
+                || text(node, doc.src) != name
+                || variable_scope(node, doc.src)?.byte_range() != *scope
+            {
+                return None;
+            }
+            Some(is_variable_declaration(node))
+        }
+        Target::Symbol(sym) => {
+            let defined_here = |n: Node| {
+                let loc = location(doc.uri, doc.src, n);
+                sym.location == loc || sym.alternatives.contains(&loc)
+            };
+            if let Some(decl) = declaration_of(node) {
+                return (defined_here(decl) || defined_here(node)).then_some(true);
+            }
+            // only static properties are variable names naming a member
+            if node.kind() == "variable_name"
+                && node
+                    .parent()
+                    .is_none_or(|p| p.kind() != "scoped_property_access_expression")
+            {
+                return None;
+            }
+            let resolved = resolve(node, doc, global, types)?;
+            (resolved.location == sym.location).then(|| defined_here(node))
+        }
+    }
+}
+
+/// The `name` a qualified name ends in, or `node` itself.
+fn last_segment(node: Node) -> Node {
+    if node.kind() == "qualified_name"
+        && let Some(last) = node.named_child(node.named_child_count().saturating_sub(1))
+        && last.kind() == "name"
+    {
+        return last;
+    }
+    node
+}
+
+/// The declaration `node` names: a function, class-like, method, property,
+/// constant or enum case.
+fn declaration_of(node: Node) -> Option<Node> {
+    let parent = node.parent()?;
+    let named = match parent.kind() {
+        "function_definition"
+        | "class_declaration"
+        | "interface_declaration"
+        | "trait_declaration"
+        | "enum_declaration"
+        | "method_declaration"
+        | "property_element"
+        | "property_promotion_parameter"
+        | "enum_case" => parent.child_by_field_name("name"),
+        "const_element" => parent
+            .child_by_field_name("name")
+            .or_else(|| parent.named_child(0)),
+        _ => None,
+    };
+    (named == Some(node)).then_some(parent)
+}
+
+/// Resolve the name `node` refers to the way goto-definition does: a member
+/// of `$obj->name` through the type of `$obj`, anything else through the
+/// document's namespace and imports.
+fn resolve(
+    node: Node,
+    doc: &DocumentContext,
+    global: &GlobalIndex,
+    types: &TypeCache,
+) -> Option<ResolvedSymbol> {
+    types.resolve_member(doc, node, global).or_else(|| {
+        let name = resolver::reference_name(node, doc.src)?;
+        let position = parser::node_range(doc.src, node).start;
+        resolver::resolve_in_document(&name, position, doc, global)
+    })
+}
+
+/// The node a variable is local to: the enclosing function, method or
+/// closure, or the whole file outside of them. Arrow functions capture the
+/// variables of their parent, and closures those listed in their `use`.
+pub fn variable_scope<'t>(node: Node<'t>, src: &str) -> Option<Node<'t>> {
+    if node.parent().is_some_and(|p| {
+        matches!(
+            p.kind(),
+            "scoped_property_access_expression"
+                | "property_element"
+                | "property_promotion_parameter"
+        )
+    }) {
+        return None;
+    }
+    let name = text(node, src);
+    let mut current = node;
+    while let Some(parent) = current.parent() {
+        let captured = match parent.kind() {
+            "function_definition" | "method_declaration" => return Some(parent),
+            "arrow_function" => !declares_parameter(parent, name, src),
+            "anonymous_function" => {
+                current.kind() == "anonymous_function_use_clause"
+                    || (!declares_parameter(parent, name, src) && captures(parent, name, src))
+            }
+            _ => true,
+        };
+        if !captured {
+            return Some(parent);
+        }
+        current = parent;
+    }
+    Some(current)
+}
+
+fn declares_parameter(function: Node, name: &str, src: &str) -> bool {
+    function
+        .child_by_field_name("parameters")
+        .is_some_and(|params| {
+            (0..params.named_child_count())
+                .filter_map(|i| params.named_child(i))
+                .filter_map(|p| p.child_by_field_name("name"))
+                .any(|n| text(n, src) == name)
+        })
+}
+
+/// Whether a closure imports `name` from its parent through `use (...)`.
+fn captures(function: Node, name: &str, src: &str) -> bool {
+    (0..function.named_child_count())
+        .filter_map(|i| function.named_child(i))
+        .filter(|c| c.kind() == "anonymous_function_use_clause")
+        .any(|clause| {
+            let mut found = false;
+            walk(clause, &mut |n| {
+                found |= n.kind() == "variable_name" && text(n, src) == name;
+            });
+            found
+        })
+}
+
+/// Visit `node` and all its descendants in source order.
+fn walk<'t>(node: Node<'t>, f: &mut impl FnMut(Node<'t>)) {
+    let mut cursor = node.walk();
+    loop {
+        f(cursor.node());
+        if cursor.goto_first_child() {
+            continue;
+        }
+        loop {
+            if cursor.node() == node {
+                return;
+            }
+            if cursor.goto_next_sibling() {
+                break;
+            }
+            if !cursor.goto_parent() {
+                return;
+            }
+        }
+    }
+}
+
+/// Parameters and assignments declare a variable.
+fn is_variable_declaration(node: Node) -> bool {
+    node.parent().is_some_and(|parent| {
+        let field = match parent.kind() {
+            "simple_parameter" | "variadic_parameter" => "name",
+            "assignment_expression" => "left",
+            _ => return false,
+        };
+        parent.child_by_field_name(field) == Some(node)
+    })
+}
+
+fn text<'a>(node: Node, src: &'a str) -> &'a str {
+    node.utf8_text(src.as_bytes()).unwrap_or("")
+}
+
+fn location(uri: &Url, src: &str, node: Node) -> Location {
+    Location {
+        uri: uri.clone(),
+        range: parser::node_range(src, node),
+    }
+}
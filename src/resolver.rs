@@ -1,7 +1,10 @@
-use crate::indexer::{FileSymbols, GlobalIndex, Symbol, SymbolKind};
-use crate::parser::Ast;
-use regex::Regex;
-use std::collections::HashMap;
+use crate::indexer::{
+    FileSymbols, GlobalIndex, NamespaceScope, Symbol, SymbolKind, load_autoload_class,
+    namespace_scopes,
+};
+use crate::parser::{self, Ast};
+use crate::phpdoc::{DocBlock, Template};
+use std::collections::{HashMap, HashSet, VecDeque};
 use tower_lsp::lsp_types::{Location, Position, Url};
 use tree_sitter::{Node, Point};
 
@@ -10,61 +13,545 @@ pub struct ResolvedSymbol {
     pub name: String,
     pub kind: SymbolKind,
     pub location: Location,
+    /// Further definitions of the same name with the same priority, such as
+    /// both branches of a conditional declaration.
+    pub alternatives: Vec<Location>,
+    /// The declared type of the symbol, see [`Symbol::type_hint`].
+    pub type_hint: Option<String>,
+    /// The docblock of the symbol, see [`Symbol::doc`].
+    pub doc: Option<Box<DocBlock>>,
 }
 
-/// Normalize a symbol name into a fully qualified name using the current
-/// namespace and `use` aliases.
-fn normalize_name(name: &str, namespace: &str, aliases: &HashMap<String, String>) -> String {
-    if name.starts_with("\\") {
-        return name.trim_start_matches('\\').to_string();
+/// The kind of symbol a name refers to. PHP keeps separate import tables for
+/// classes, functions and constants, so the kind decides which one applies.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NameKind {
+    Class,
+    Function,
+    Constant,
+}
+
+/// Imports declared by `use` statements within one namespace block, mapping
+/// each alias to the fully qualified name it stands for.
+#[derive(Debug, Clone, Default)]
+pub struct Imports {
+    pub classes: HashMap<String, String>,
+    pub functions: HashMap<String, String>,
+    pub constants: HashMap<String, String>,
+}
+
+impl Imports {
+    fn table(&self, kind: NameKind) -> &HashMap<String, String> {
+        match kind {
+            NameKind::Class => &self.classes,
+            NameKind::Function => &self.functions,
+            NameKind::Constant => &self.constants,
+        }
+    }
+
+    fn table_mut(&mut self, kind: NameKind) -> &mut HashMap<String, String> {
+        match kind {
+            NameKind::Class => &mut self.classes,
+            NameKind::Function => &mut self.functions,
+            NameKind::Constant => &mut self.constants,
+        }
     }
+}
+
+/// The namespace blocks of a file together with the imports declared in
+/// each of them. Built once per parse from `namespace_use_declaration` nodes;
+/// trait `use` statements inside class bodies are not imports and are ignored.
+#[derive(Debug, Clone, Default)]
+pub struct ImportTable {
+    blocks: Vec<(NamespaceScope, Imports)>,
+    global: Imports,
+}
 
-    let parts: Vec<&str> = name.split("\\").collect();
-    if let Some(first) = parts.first() {
-        if let Some(full) = aliases.get(*first) {
-            let mut out = full.clone();
-            if parts.len() > 1 {
-                out.push_str("\\");
-                out.push_str(&parts[1..].join("\\"));
+impl ImportTable {
+    pub fn build(src: &str, ast: &Ast) -> Self {
+        let mut table = ImportTable {
+            blocks: namespace_scopes(src, ast)
+                .into_iter()
+                .map(|scope| (scope, Imports::default()))
+                .collect(),
+            global: Imports::default(),
+        };
+        let root = ast.0.root_node();
+        for i in 0..root.named_child_count() {
+            let Some(child) = root.named_child(i) else {
+                continue;
+            };
+            match child.kind() {
+                "namespace_use_declaration" => table.add_declaration(src, child),
+                "namespace_definition" => {
+                    if let Some(body) = child.child_by_field_name("body") {
+                        for j in 0..body.named_child_count() {
+                            if let Some(stmt) = body.named_child(j)
+                                && stmt.kind() == "namespace_use_declaration"
+                            {
+                                table.add_declaration(src, stmt);
+                            }
+                        }
+                    }
+                }
+                _ => {}
             }
-            return out;
         }
+        table
     }
 
-    if namespace.is_empty() {
+    /// The namespace in effect at `point`.
+    pub fn namespace_at(&self, point: Point) -> &str {
+        self.block_at(point)
+            .map(|(s, _)| s.name.as_str())
+            .unwrap_or("")
+    }
+
+    /// The imports visible at `point`.
+    pub fn imports_at(&self, point: Point) -> &Imports {
+        self.block_at(point)
+            .map(|(_, imports)| imports)
+            .unwrap_or(&self.global)
+    }
+
+    /// Every alias, in any namespace block of the file, that imports `fqn`.
+    pub fn aliases_of(&self, fqn: &str) -> Vec<&str> {
+        let fqn = fqn.trim_start_matches('\\');
+        self.blocks
+            .iter()
+            .map(|(_, imports)| imports)
+            .chain(std::iter::once(&self.global))
+            .flat_map(|imports| [&imports.classes, &imports.functions, &imports.constants])
+            .flat_map(|table| table.iter())
+            .filter(|(_, full)| full.eq_ignore_ascii_case(fqn))
+            .map(|(alias, _)| alias.as_str())
+            .collect()
+    }
+
+    fn block_at(&self, point: Point) -> Option<&(NamespaceScope, Imports)> {
+        self.blocks.iter().find(|(s, _)| s.contains(point))
+    }
+
+    fn add_declaration(&mut self, src: &str, decl: Node) {
+        let point = decl.start_position();
+        let imports = match self.blocks.iter_mut().find(|(s, _)| s.contains(point)) {
+            Some((_, imports)) => imports,
+            None => &mut self.global,
+        };
+        let mut decl_kind = use_kind(decl);
+        let grouped = decl.child_by_field_name("body").is_some();
+        let (prefix, clauses) = match decl.child_by_field_name("body") {
+            Some(body) => {
+                let prefix = (0..decl.named_child_count())
+                    .filter_map(|i| decl.named_child(i))
+                    .find(|n| n.kind() == "namespace_name")
+                    .and_then(|n| n.utf8_text(src.as_bytes()).ok())
+                    .unwrap_or("");
+                (prefix.trim_matches('\\'), body)
+            }
+            None => {
+                // `use function A, B;` attaches the modifier to the first
+                // clause only, but it applies to every clause.
+                if decl_kind.is_none() {
+                    decl_kind = decl.named_child(0).and_then(use_kind);
+                }
+                ("", decl)
+            }
+        };
+        for i in 0..clauses.named_child_count() {
+            let Some(clause) = clauses.named_child(i) else {
+                continue;
+            };
+            if clause.kind() != "namespace_use_clause" {
+                continue;
+            }
+            let Some(path) = clause
+                .named_child(0)
+                .and_then(|n| n.utf8_text(src.as_bytes()).ok())
+                .map(|p| p.trim_start_matches('\\'))
+            else {
+                continue;
+            };
+            let full = if prefix.is_empty() {
+                path.to_string()
+            } else {
+                format!("{}\\{}", prefix, path)
+            };
+            let alias = clause
+                .child_by_field_name("alias")
+                .and_then(|n| n.utf8_text(src.as_bytes()).ok())
+                .unwrap_or_else(|| path.rsplit('\\').next().unwrap_or(path));
+            let kind = use_kind(clause)
+                .filter(|_| grouped)
+                .or(decl_kind)
+                .unwrap_or(NameKind::Class);
+            imports.table_mut(kind).insert(alias.to_string(), full);
+        }
+    }
+}
+
+/// The `function`/`const` modifier of a use declaration or group clause.
+fn use_kind(node: Node) -> Option<NameKind> {
+    match node.child_by_field_name("type")?.kind() {
+        "function" => Some(NameKind::Function),
+        "const" => Some(NameKind::Constant),
+        _ => None,
+    }
+}
+
+/// The `name`, `qualified_name` or `variable_name` node at `point`, with a
+/// bare `name` widened to the qualified or variable name it is part of.
+pub fn name_node_at(ast: &Ast, point: Point) -> Option<Node<'_>> {
+    let mut node = ast.0.root_node().descendant_for_point_range(point, point)?;
+    while !matches!(node.kind(), "name" | "qualified_name" | "variable_name") {
+        node = node.parent()?;
+    }
+    if node.kind() == "name"
+        && let Some(parent) = node.parent()
+        && matches!(parent.kind(), "variable_name" | "qualified_name")
+    {
+        node = parent;
+    }
+    Some(node)
+}
+
+/// The name a [`name_node_at`] node refers to as written, with the class it
+/// is accessed through prepended for `Foo::bar()`, `Foo::BAR` and
+/// `Foo::$bar`. Members of `$this` are named through `static`, so
+/// `$this->save()` becomes `static::save` and `$this->name` becomes
+/// `static::$name`. Names in `use` clauses are fully qualified, so they come
+/// back with a leading `\`.
+pub fn reference_name(node: Node, src: &str) -> Option<String> {
+    let text = node.utf8_text(src.as_bytes()).ok()?;
+    if let Some(scope) = static_scope(node, src) {
+        return Some(format!("{}::{}", scope, text));
+    }
+    if let Some(access) = this_member(node, src) {
+        return Some(match access.kind() {
+            "member_access_expression" | "nullsafe_member_access_expression" => {
+                format!("static::${}", text)
+            }
+            _ => format!("static::{}", text),
+        });
+    }
+    if let Some(clause) = node.parent()
+        && clause.kind() == "namespace_use_clause"
+        && clause.child_by_field_name("alias") != Some(node)
+    {
+        let path = text.trim_start_matches('\\');
+        let prefix = clause
+            .parent()
+            .filter(|group| group.kind() == "namespace_use_group")
+            .and_then(|group| group.parent())
+            .and_then(|decl| {
+                (0..decl.named_child_count())
+                    .filter_map(|i| decl.named_child(i))
+                    .find(|n| n.kind() == "namespace_name")
+            })
+            .and_then(|n| n.utf8_text(src.as_bytes()).ok());
+        return Some(match prefix {
+            Some(prefix) => format!("\\{}\\{}", prefix.trim_matches('\\'), path),
+            None => format!("\\{}", path),
+        });
+    }
+    Some(text.to_string())
+}
+
+/// Return the class name a member is accessed through when `node` is the
+/// member part of `Foo::bar()`, `Foo::BAR` or `Foo::$bar`.
+fn static_scope(node: Node, src: &str) -> Option<String> {
+    let parent = node.parent()?;
+    let scope = match parent.kind() {
+        "scoped_call_expression" | "scoped_property_access_expression" => {
+            if parent.child_by_field_name("name")? != node {
+                return None;
+            }
+            parent.child_by_field_name("scope")?
+        }
+        "class_constant_access_expression" => {
+            let count = parent.named_child_count();
+            if count < 2 || parent.named_child(count - 1)? != node {
+                return None;
+            }
+            parent.named_child(0)?
+        }
+        _ => return None,
+    };
+    if !matches!(scope.kind(), "name" | "qualified_name" | "relative_scope") {
+        return None;
+    }
+    scope.utf8_text(src.as_bytes()).ok().map(|s| s.to_string())
+}
+
+/// The member access or call expression when `node` is the member part of
+/// `$this->name` or `$this->name()`.
+fn this_member<'t>(node: Node<'t>, src: &str) -> Option<Node<'t>> {
+    let parent = node.parent()?;
+    if !matches!(
+        parent.kind(),
+        "member_access_expression"
+            | "member_call_expression"
+            | "nullsafe_member_access_expression"
+            | "nullsafe_member_call_expression"
+    ) || parent.child_by_field_name("name")? != node
+    {
+        return None;
+    }
+    let object = parent.child_by_field_name("object")?;
+    (object.kind() == "variable_name" && object.utf8_text(src.as_bytes()).ok()? == "$this")
+        .then_some(parent)
+}
+
+/// The fully qualified names of the traits a class-like declaration uses and
+/// the types it extends and implements, resolved through the imports in
+/// effect where it is declared. See [`Symbol::supertypes`].
+pub fn supertypes(src: &str, decl: Node, imports: &ImportTable) -> Vec<String> {
+    let point = decl.start_position();
+    let namespace = imports.namespace_at(point);
+    let block = imports.imports_at(point);
+    let names = |clause: Node| -> Vec<String> {
+        (0..clause.named_child_count())
+            .filter_map(|i| clause.named_child(i))
+            .filter(|n| matches!(n.kind(), "name" | "qualified_name"))
+            .filter_map(|n| n.utf8_text(src.as_bytes()).ok())
+            .map(|name| normalize_name(name, NameKind::Class, namespace, block))
+            .collect()
+    };
+    let mut out = Vec::new();
+    if let Some(body) = decl.child_by_field_name("body") {
+        for i in 0..body.named_child_count() {
+            if let Some(member) = body.named_child(i)
+                && member.kind() == "use_declaration"
+            {
+                out.extend(names(member));
+            }
+        }
+    }
+    for kind in ["base_clause", "class_interface_clause"] {
+        for i in 0..decl.named_child_count() {
+            if let Some(clause) = decl.named_child(i)
+                && clause.kind() == kind
+            {
+                out.extend(names(clause));
+            }
+        }
+    }
+    out
+}
+
+/// The text of the type declaration `node` with class names fully qualified
+/// through the imports in effect there, and `self` and `static` replaced by
+/// `class`, the class the declaration belongs to.
+pub fn declared_type(src: &str, node: Node, imports: &ImportTable, class: Option<&str>) -> String {
+    let children = |separator: &str| {
+        (0..node.named_child_count())
+            .filter_map(|i| node.named_child(i))
+            .map(|child| {
+                let ty = declared_type(src, child, imports, class);
+                if child.kind() == "intersection_type" {
+                    format!("({})", ty)
+                } else {
+                    ty
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(separator)
+    };
+    let text = node.utf8_text(src.as_bytes()).unwrap_or("");
+    match node.kind() {
+        "named_type" => match text.to_ascii_lowercase().as_str() {
+            "self" | "static" => class.unwrap_or(text).to_string(),
+            "parent" => text.to_string(),
+            _ => {
+                let point = node.start_position();
+                normalize_name(
+                    text,
+                    NameKind::Class,
+                    imports.namespace_at(point),
+                    imports.imports_at(point),
+                )
+            }
+        },
+        "optional_type" => match node.named_child(0) {
+            Some(inner) => format!("?{}", declared_type(src, inner, imports, class)),
+            None => text.to_string(),
+        },
+        "union_type" | "type_list" | "disjunctive_normal_form_type" => children("|"),
+        "intersection_type" => children("&"),
+        _ => text.to_string(),
+    }
+}
+
+/// Qualify the class names in a docblock written at `point` like
+/// [`declared_type`] does, with `$this` standing for `class` too. Names of
+/// the `templates` in scope are left alone.
+pub fn qualify_doc(
+    doc: &mut DocBlock,
+    templates: &[Template],
+    imports: &ImportTable,
+    point: Point,
+    class: Option<&str>,
+) {
+    let namespace = imports.namespace_at(point);
+    let table = imports.imports_at(point);
+    doc.qualify(
+        templates,
+        &|name| match name.to_ascii_lowercase().as_str() {
+            "self" | "static" | "$this" => class.unwrap_or(name).to_string(),
+            "parent" => name.to_string(),
+            _ => normalize_name(name, NameKind::Class, namespace, table),
+        },
+    );
+}
+
+/// The class-like declaration enclosing `point`. Anonymous classes have no
+/// name to resolve members through, so they end the search.
+fn enclosing_class(ast: &Ast, point: Point) -> Option<Node<'_>> {
+    let mut node = ast.0.root_node().descendant_for_point_range(point, point)?;
+    loop {
+        match node.kind() {
+            "class_declaration"
+            | "interface_declaration"
+            | "trait_declaration"
+            | "enum_declaration" => return Some(node),
+            "anonymous_class" => return None,
+            _ => node = node.parent()?,
+        }
+    }
+}
+
+/// The fully qualified class `self`, `static` or `parent` stands for at
+/// `point`, or `class` qualified through the namespace and imports.
+pub fn scope_class(class: &str, point: Point, doc: &DocumentContext) -> Option<String> {
+    let namespace = doc.imports.namespace_at(point);
+    let imports = doc.imports.imports_at(point);
+    let relative = class.to_ascii_lowercase();
+    if !matches!(relative.as_str(), "self" | "static" | "parent") {
+        return Some(normalize_name(class, NameKind::Class, namespace, imports));
+    }
+    let decl = enclosing_class(doc.ast, point)?;
+    if relative == "parent" {
+        let base = (0..decl.named_child_count())
+            .filter_map(|i| decl.named_child(i))
+            .find(|n| n.kind() == "base_clause")?
+            .named_child(0)?;
+        let name = base.utf8_text(doc.src.as_bytes()).ok()?;
+        return Some(normalize_name(name, NameKind::Class, namespace, imports));
+    }
+    let name = decl
+        .child_by_field_name("name")?
+        .utf8_text(doc.src.as_bytes())
+        .ok()?;
+    Some(if namespace.is_empty() {
         name.to_string()
     } else {
         format!("{}\\{}", namespace, name)
+    })
+}
+
+/// Decide whether the name at `point` is used as a class, function or
+/// constant, based on the syntax around it.
+fn name_kind_at(ast: &Ast, point: Point) -> NameKind {
+    let Some(mut node) = ast.0.root_node().descendant_for_point_range(point, point) else {
+        return NameKind::Class;
+    };
+    while !matches!(node.kind(), "name" | "qualified_name") {
+        match node.parent() {
+            Some(parent) => node = parent,
+            None => return NameKind::Class,
+        }
+    }
+    if let Some(parent) = node.parent()
+        && parent.kind() == "qualified_name"
+    {
+        node = parent;
+    }
+    let Some(parent) = node.parent() else {
+        return NameKind::Class;
+    };
+    match parent.kind() {
+        "function_call_expression" | "function_definition" => NameKind::Function,
+        "const_element" => NameKind::Constant,
+        "binary_expression" => {
+            if parent.child_by_field_name("right") == Some(node)
+                && parent
+                    .child_by_field_name("operator")
+                    .is_some_and(|op| op.kind() == "instanceof")
+            {
+                NameKind::Class
+            } else {
+                NameKind::Constant
+            }
+        }
+        "echo_statement"
+        | "argument"
+        | "assignment_expression"
+        | "return_statement"
+        | "parenthesized_expression"
+        | "unary_op_expression"
+        | "conditional_expression"
+        | "array_element_initializer"
+        | "expression_statement"
+        | "subscript_expression" => NameKind::Constant,
+        _ => NameKind::Class,
     }
 }
 
-/// Extract the namespace of the file, if any.
-fn extract_namespace(src: &str) -> String {
-    let re = Regex::new(r"(?m)^\s*namespace\s+([^;]+);?").unwrap();
-    if let Some(cap) = re.captures(src) {
-        cap[1].trim().to_string()
+/// Normalize a symbol name into a fully qualified name using the current
+/// namespace and the imports of the kind the name is used as. Member
+/// references such as `User::save` keep their member part and only the class
+/// part is qualified.
+fn normalize_name(name: &str, kind: NameKind, namespace: &str, imports: &Imports) -> String {
+    if let Some((class, member)) = name.split_once("::") {
+        return format!(
+            "{}::{}",
+            normalize_name(class, NameKind::Class, namespace, imports),
+            member
+        );
+    }
+
+    if name.starts_with("\\") {
+        return name.trim_start_matches('\\').to_string();
+    }
+
+    match name.split_once('\\') {
+        Some((first, rest)) => {
+            if let Some(full) = imports.classes.get(first) {
+                return format!("{}\\{}", full, rest);
+            }
+        }
+        None => {
+            if let Some(full) = imports.table(kind).get(name) {
+                return full.clone();
+            }
+        }
+    }
+
+    if namespace.is_empty() {
+        name.to_string()
     } else {
-        String::new()
+        format!("{}\\{}", namespace, name)
     }
 }
 
-/// Extract `use` aliases from the source code.
-fn extract_use_aliases(src: &str) -> HashMap<String, String> {
-    let re = Regex::new(r"(?m)^\s*use\s+([^;]+);?").unwrap();
-    let mut map = HashMap::new();
-    for cap in re.captures_iter(src) {
-        let clause = cap[1].trim();
-        // parse individual use clause
-        let mut parts = clause.split_whitespace();
-        let path = parts.next().unwrap_or("");
-        let alias = if let Some("as") = parts.next() {
-            parts.next().unwrap_or("")
-        } else {
-            path.rsplit('\x5c').next().unwrap_or(path)
-        };
-        map.insert(alias.to_string(), path.to_string());
+/// PHP resolves an unqualified, non-imported function or constant name first
+/// in the current namespace and then in the global namespace. Return the
+/// global candidate when that fallback applies. Class names never fall back.
+fn global_fallback(
+    name: &str,
+    kind: NameKind,
+    namespace: &str,
+    imports: &Imports,
+) -> Option<String> {
+    if kind == NameKind::Class
+        || namespace.is_empty()
+        || name.contains('\\')
+        || name.contains("::")
+        || imports.table(kind).contains_key(name)
+    {
+        return None;
     }
-    map
+    Some(name.to_string())
 }
 
 /// Find a local variable or parameter definition for `name` inside `ast` at
@@ -77,10 +564,7 @@ fn find_local_variable(
     uri: &Url,
 ) -> Option<Symbol> {
     let root = ast.0.root_node();
-    let point = Point {
-        row: pos.line as usize,
-        column: pos.character as usize,
-    };
+    let point = parser::point_at(src, pos);
     let usage = root.descendant_for_point_range(point, point)?;
 
     // ascend to the nearest function-like node
@@ -97,19 +581,20 @@ fn find_local_variable(
         // parameters
         if let Some(params) = current.child_by_field_name("parameters") {
             for i in 0..params.named_child_count() {
-                if let Some(param) = params.named_child(i) {
-                    if let Some(var) = param.child_by_field_name("name") {
-                        if let Ok(text) = var.utf8_text(src.as_bytes()) {
-                            if text.trim_start_matches('$') == name.trim_start_matches('$') {
-                                return Some(Symbol {
-                                    name: text.to_string(),
-                                    kind: SymbolKind::Variable,
-                                    location: node_location(uri, var),
-                                    container: None,
-                                });
-                            }
-                        }
-                    }
+                if let Some(param) = params.named_child(i)
+                    && let Some(var) = param.child_by_field_name("name")
+                    && let Ok(text) = var.utf8_text(src.as_bytes())
+                    && text.trim_start_matches('$') == name.trim_start_matches('$')
+                {
+                    return Some(Symbol {
+                        name: text.to_string(),
+                        kind: SymbolKind::Variable,
+                        location: node_location(uri, src, var),
+                        container: None,
+                        supertypes: Vec::new(),
+                        type_hint: None,
+                        doc: None,
+                    });
                 }
             }
         }
@@ -117,25 +602,26 @@ fn find_local_variable(
     None
 }
 
-fn node_location(uri: &Url, node: Node) -> Location {
-    let start = node.start_position();
-    let end = node.end_position();
+fn node_location(uri: &Url, src: &str, node: Node) -> Location {
     Location {
         uri: uri.clone(),
-        range: tower_lsp::lsp_types::Range {
-            start: Position {
-                line: start.row as u32,
-                character: start.column as u32,
-            },
-            end: Position {
-                line: end.row as u32,
-                character: end.column as u32,
-            },
-        },
+        range: parser::node_range(src, node),
     }
 }
 
+/// Everything the resolver needs to know about the document a name occurs
+/// in. Servers keep the import table cached alongside the parsed tree.
+pub struct DocumentContext<'a> {
+    pub uri: &'a Url,
+    pub src: &'a str,
+    pub ast: &'a Ast,
+    pub symbols: &'a FileSymbols,
+    pub imports: &'a ImportTable,
+}
+
 /// Resolve a symbol using local scope, file symbols and the global index.
+/// Builds the file's import table first; servers that keep it cached call
+/// [`resolve_in_document`] instead.
 pub fn resolve_symbol(
     name: &str,
     uri: &Url,
@@ -145,47 +631,198 @@ pub fn resolve_symbol(
     file_symbols: &FileSymbols,
     global: &GlobalIndex,
 ) -> Option<ResolvedSymbol> {
-    tracing::debug!("Resolving symbol '{}' at {}:{}", name, uri, position.line);
+    let imports = ImportTable::build(src, ast);
+    let doc = DocumentContext {
+        uri,
+        src,
+        ast,
+        symbols: file_symbols,
+        imports: &imports,
+    };
+    resolve_in_document(name, position, &doc, global)
+}
+
+/// Resolve a symbol like [`resolve_symbol`], reusing the document's cached
+/// import table.
+pub fn resolve_in_document(
+    name: &str,
+    position: Position,
+    doc: &DocumentContext,
+    global: &GlobalIndex,
+) -> Option<ResolvedSymbol> {
+    tracing::debug!(
+        "Resolving symbol '{}' at {}:{}",
+        name,
+        doc.uri,
+        position.line
+    );
     // Step 1: local variables/parameters
-    if name.starts_with('$') {
-        if let Some(sym) = find_local_variable(name, src, ast, position, uri) {
-            return Some(ResolvedSymbol {
-                name: sym.name,
-                kind: sym.kind,
-                location: sym.location,
-            });
+    if name.starts_with('$')
+        && let Some(sym) = find_local_variable(name, doc.src, doc.ast, position, doc.uri)
+    {
+        return Some(ResolvedSymbol {
+            name: sym.name,
+            kind: sym.kind,
+            location: sym.location,
+            alternatives: Vec::new(),
+            type_hint: sym.type_hint,
+            doc: sym.doc,
+        });
+    }
+
+    let point = parser::point_at(doc.src, position);
+    if let Some((class, member)) = name.split_once("::") {
+        let class = scope_class(class, point, doc)?;
+        let resolved = lookup_member(&class, member, doc.symbols, global, &mut |_| {});
+        if resolved.is_none() {
+            tracing::debug!("Unable to resolve member '{}::{}'", class, member);
+        }
+        return resolved;
+    }
+    let kind = name_kind_at(doc.ast, point);
+    let candidates = qualified_candidates(name, point, doc);
+
+    let mut class_fqn = None;
+    for candidate in candidates {
+        if let Some(resolved) = lookup(&candidate, doc.symbols, global) {
+            return Some(resolved);
+        }
+        if class_fqn.is_none() && kind == NameKind::Class {
+            class_fqn = Some(candidate);
         }
     }
 
-    let namespace = extract_namespace(src);
-    let aliases = extract_use_aliases(src);
-    let fqn = normalize_name(name, &namespace, &aliases);
-    // resolved fully qualified name from aliases and namespace
+    // Step 4: load the class from its autoload location if it isn't indexed yet
+    if let Some(fqn) = class_fqn
+        && load_autoload_class(global, &fqn).is_some()
+        && let Some(resolved) = lookup(&fqn, doc.symbols, global)
+    {
+        return Some(resolved);
+    }
+
+    tracing::debug!("Unable to resolve symbol '{}'", name);
+    None
+}
 
+/// The fully qualified names `name` may stand for at `point`, in the order
+/// PHP tries them: qualified through the namespace and imports, then the
+/// global function or constant it falls back to. Last comes the target of a
+/// class import of the same alias, so a function imported with a plain `use`
+/// instead of `use function` still resolves.
+pub fn qualified_candidates(name: &str, point: Point, doc: &DocumentContext) -> Vec<String> {
+    let namespace = doc.imports.namespace_at(point);
+    let imports = doc.imports.imports_at(point);
+    let kind = name_kind_at(doc.ast, point);
+    let misimported = (kind != NameKind::Class && !imports.table(kind).contains_key(name))
+        .then(|| imports.classes.get(name).cloned())
+        .flatten();
+    std::iter::once(normalize_name(name, kind, namespace, imports))
+        .chain(global_fallback(name, kind, namespace, imports))
+        .chain(misimported)
+        .collect()
+}
+
+/// Look up a fully qualified name in the current file, then the global index.
+pub fn lookup(
+    fqn: &str,
+    file_symbols: &FileSymbols,
+    global: &GlobalIndex,
+) -> Option<ResolvedSymbol> {
     // Step 2: current file symbols
-    if let Some(sym) = file_symbols.get(&fqn) {
-        let resolved = ResolvedSymbol {
-            name: sym.name.clone(),
-            kind: sym.kind.clone(),
-            location: sym.location.clone(),
-        };
+    if let Some(defs) = file_symbols.get(fqn)
+        && let Some(resolved) = resolved_from(defs.iter())
+    {
         tracing::debug!("Resolved symbol '{}' in current file", resolved.name);
         return Some(resolved);
     }
 
-    // Step 3: global index
-    for entry in global.iter() {
-        if let Some(sym) = entry.value().get(&fqn) {
-            let resolved = ResolvedSymbol {
-                name: sym.name.clone(),
-                kind: sym.kind.clone(),
-                location: sym.location.clone(),
-            };
-            tracing::debug!("Resolved symbol '{}' in global index", resolved.name);
-            return Some(resolved);
+    // Step 3: global index, preferring project code over vendor and stubs
+    let defs = global.definitions(fqn);
+    let layer = defs.first()?.0;
+    let resolved = resolved_from(
+        defs.iter()
+            .take_while(|(l, _)| *l == layer)
+            .map(|(_, sym)| sym),
+    )?;
+    tracing::debug!(
+        "Resolved symbol '{}' in global index ({:?})",
+        resolved.name,
+        layer
+    );
+    Some(resolved)
+}
+
+/// Look up `member` of `class`, falling back to the traits, parent classes
+/// and interfaces it inherits from, nearest first. `trace` sees every name
+/// looked up in the index, so callers caching the result know what it
+/// depends on.
+pub fn lookup_member(
+    class: &str,
+    member: &str,
+    file_symbols: &FileSymbols,
+    global: &GlobalIndex,
+    trace: &mut dyn FnMut(&str),
+) -> Option<ResolvedSymbol> {
+    class_chain(class, file_symbols, global, trace)
+        .into_iter()
+        .find_map(|class| {
+            let key = format!("{}::{}", class, member);
+            trace(&key);
+            lookup(&key, file_symbols, global)
+        })
+}
+
+/// `class` followed by the traits, parent classes and interfaces it
+/// inherits from, nearest first. Classes that aren't indexed yet are loaded
+/// from their autoload location.
+pub fn class_chain(
+    class: &str,
+    file_symbols: &FileSymbols,
+    global: &GlobalIndex,
+    trace: &mut dyn FnMut(&str),
+) -> Vec<String> {
+    let mut queue = VecDeque::from([class.trim_start_matches('\\').to_string()]);
+    let mut seen = HashSet::new();
+    let mut chain = Vec::new();
+    while let Some(class) = queue.pop_front() {
+        if !seen.insert(class.to_ascii_lowercase()) {
+            continue;
+        }
+        trace(&class);
+        let mut supertypes = class_supertypes(&class, file_symbols, global);
+        if supertypes.is_none() && load_autoload_class(global, &class).is_some() {
+            supertypes = class_supertypes(&class, file_symbols, global);
         }
+        queue.extend(supertypes.unwrap_or_default());
+        chain.push(class);
     }
+    chain
+}
 
-    tracing::debug!("Unable to resolve symbol '{}'", name);
-    None
+/// The supertypes recorded for `class`, or `None` when it isn't indexed.
+fn class_supertypes(
+    class: &str,
+    file_symbols: &FileSymbols,
+    global: &GlobalIndex,
+) -> Option<Vec<String>> {
+    if let Some(sym) = file_symbols.get(class).and_then(|defs| defs.first()) {
+        return Some(sym.supertypes.clone());
+    }
+    global
+        .definitions(class)
+        .into_iter()
+        .next()
+        .map(|(_, sym)| sym.supertypes)
+}
+
+fn resolved_from<'a>(mut defs: impl Iterator<Item = &'a Symbol>) -> Option<ResolvedSymbol> {
+    let sym = defs.next()?;
+    Some(ResolvedSymbol {
+        name: sym.name.clone(),
+        kind: sym.kind.clone(),
+        location: sym.location.clone(),
+        type_hint: sym.type_hint.clone(),
+        doc: sym.doc.clone(),
+        alternatives: defs.map(|s| s.location.clone()).collect(),
+    })
 }
@@ -1,15 +1,17 @@
-use bumpalo::Bump;
 use notify::RecommendedWatcher;
+use serde::Deserialize;
 
 use std::collections::HashMap;
-use std::sync::{Arc, Mutex};
-use tower_lsp::jsonrpc::Result;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, RwLock};
+use tower_lsp::jsonrpc::{Error, Result};
 use tower_lsp::lsp_types::*;
 use tower_lsp::{Client, LanguageServer, LspService, Server};
 
 use crate::{
-    analyzer, composer, config, fs, indexer, laravel::LaravelPlugin, parser, plugin::PluginManager,
-    resolver,
+    analyzer, cache, composer, config, diagnostics, filter::FileFilter, fs, fuzzy, hover, indexer,
+    laravel::LaravelPlugin, outline, parser, plugin::PluginManager, references, resolver,
+    signature_help, stubs,
 };
 
 #[derive(Default, Clone)]
@@ -17,19 +19,34 @@ pub struct DocumentState {
     pub text: String,
     pub ast: Option<parser::Ast>,
     pub symbols: indexer::FileSymbols,
+    pub imports: resolver::ImportTable,
+    pub version: Option<i32>,
 }
 
 pub struct Backend {
     client: Client,
     documents: Arc<Mutex<HashMap<Url, DocumentState>>>,
-    bump: Mutex<Bump>,
     index: indexer::GlobalIndex,
+    types: Arc<analyzer::TypeCache>,
     watcher: Mutex<Option<RecommendedWatcher>>,
-    config: config::Config,
-    autoload: HashMap<String, String>,
+    scan: Mutex<Option<PendingScan>>,
+    config: RwLock<config::Config>,
+    /// The workspace folder, once the client has sent it.
+    root: RwLock<Option<PathBuf>>,
+    /// Which files of the workspace folder are indexed as project code.
+    filter: RwLock<Option<Arc<FileFilter>>>,
     plugins: PluginManager,
 }
 
+/// A workspace scan waiting for the client to finish initializing.
+struct PendingScan {
+    root: PathBuf,
+    filter: Arc<FileFilter>,
+    cache: Option<PathBuf>,
+    /// Whether the client accepts server-initiated `$/progress`.
+    progress: bool,
+}
+
 impl Backend {
     pub fn new(client: Client) -> Self {
         crate::logging::init(client.clone());
@@ -37,7 +54,8 @@ impl Backend {
         tracing::info!("running phppp version {}", env!("CARGO_PKG_VERSION"));
         let cwd = std::env::current_dir().unwrap_or_else(|_| std::path::PathBuf::from("."));
         let config = config::load_config(&cwd).unwrap_or_default();
-        let autoload = composer::load_autoload_paths(&cwd).unwrap_or_default();
+        let index = indexer::new_index();
+        index.set_autoload(composer::load_autoload(&cwd).unwrap_or_default());
         let mut plugins = PluginManager::new();
         if config.enable_laravel {
             plugins.add(LaravelPlugin);
@@ -45,11 +63,13 @@ impl Backend {
         Self {
             client,
             documents: Arc::new(Mutex::new(HashMap::new())),
-            bump: Mutex::new(Bump::new()),
-            index: indexer::new_index(),
+            index,
+            types: Arc::new(analyzer::TypeCache::new()),
             watcher: Mutex::new(None),
-            config,
-            autoload,
+            scan: Mutex::new(None),
+            config: RwLock::new(config),
+            root: RwLock::new(None),
+            filter: RwLock::new(None),
             plugins,
         }
     }
@@ -60,15 +80,44 @@ impl LanguageServer for Backend {
     async fn initialize(&self, params: InitializeParams) -> Result<InitializeResult> {
         tracing::debug!("initialize called");
         if let Some(root) = params.root_uri.and_then(|u| u.to_file_path().ok()) {
-            if let Err(e) = indexer::scan_workspace(&root, &self.index) {
-                tracing::error!("workspace scan failed: {}", e);
-                crate::metrics::inc_error("initialize");
+            *self.root.write().unwrap() = Some(root.clone());
+            match composer::load_autoload(&root) {
+                Ok(autoload) => self.index.set_autoload(autoload),
+                Err(e) => tracing::warn!("failed to read composer.json: {}", e),
+            }
+            match config::load_config(&root) {
+                Ok(config) => *self.config.write().unwrap() = config,
+                Err(e) => tracing::warn!("failed to read .phppprc: {}", e),
             }
+            let config = self.config.read().unwrap();
+            let filter = Arc::new(FileFilter::new(&root, &config));
+            *self.filter.write().unwrap() = Some(filter.clone());
+            let progress = params
+                .capabilities
+                .window
+                .as_ref()
+                .and_then(|w| w.work_done_progress)
+                .unwrap_or(false);
+            *self.scan.lock().unwrap() = Some(PendingScan {
+                root: root.clone(),
+                filter: filter.clone(),
+                cache: config.cache.then(|| cache::cache_path(&root)),
+                progress,
+            });
             let idx = self.index.clone();
+            let vendor = composer::vendor_dir(&root);
             if let Ok(w) = fs::watch(&root, move |res| {
                 if let Ok(ev) = res {
-                    for p in ev.paths {
-                        let _ = indexer::index_file(&p, &idx);
+                    for p in &ev.paths {
+                        filter.invalidate(p);
+                    }
+                    // vendor code is read-only and refreshed on restart
+                    for p in ev
+                        .paths
+                        .iter()
+                        .filter(|p| !p.starts_with(&vendor) && filter.accepts(p))
+                    {
+                        let _ = indexer::index_file(p, &idx);
                     }
                 }
             }) {
@@ -76,14 +125,30 @@ impl LanguageServer for Backend {
             }
             self.plugins.register_all(&self.index);
         }
+        stubs::index_stubs(&self.config.read().unwrap().stubs, &self.index);
         Ok(InitializeResult {
             capabilities: ServerCapabilities {
                 text_document_sync: Some(TextDocumentSyncCapability::Kind(
-                    TextDocumentSyncKind::FULL,
+                    TextDocumentSyncKind::INCREMENTAL,
                 )),
-                completion_provider: Some(CompletionOptions::default()),
+                completion_provider: Some(CompletionOptions {
+                    trigger_characters: Some(vec![">".into(), ":".into()]),
+                    ..CompletionOptions::default()
+                }),
+                signature_help_provider: Some(SignatureHelpOptions {
+                    trigger_characters: Some(vec!["(".into(), ",".into()]),
+                    retrigger_characters: None,
+                    work_done_progress_options: Default::default(),
+                }),
                 definition_provider: Some(OneOf::Left(true)),
                 hover_provider: Some(HoverProviderCapability::Simple(true)),
+                workspace_symbol_provider: Some(OneOf::Left(true)),
+                document_symbol_provider: Some(OneOf::Left(true)),
+                references_provider: Some(OneOf::Left(true)),
+                rename_provider: Some(OneOf::Right(RenameOptions {
+                    prepare_provider: Some(true),
+                    work_done_progress_options: WorkDoneProgressOptions::default(),
+                })),
                 execute_command_provider: Some(ExecuteCommandOptions {
                     commands: vec!["phppp.restart".into()],
                     work_done_progress_options: Default::default(),
@@ -94,7 +159,16 @@ impl LanguageServer for Backend {
         })
     }
 
-    async fn initialized(&self, _: InitializedParams) {}
+    async fn initialized(&self, _: InitializedParams) {
+        if let Some(scan) = self.scan.lock().unwrap().take() {
+            tokio::spawn(scan_in_background(
+                self.client.clone(),
+                self.index.clone(),
+                self.documents.clone(),
+                scan,
+            ));
+        }
+    }
 
     async fn shutdown(&self) -> Result<()> {
         tracing::debug!("shutdown called");
@@ -103,15 +177,55 @@ impl LanguageServer for Backend {
 
     async fn did_open(&self, params: DidOpenTextDocumentParams) {
         tracing::debug!("opened {}", params.text_document.uri);
-        self.handle_change(params.text_document.uri.clone(), params.text_document.text)
-            .await;
+        self.handle_change(
+            params.text_document.uri.clone(),
+            params.text_document.text,
+            None,
+            Some(params.text_document.version),
+        )
+        .await;
     }
 
     async fn did_change(&self, params: DidChangeTextDocumentParams) {
-        if let Some(change) = params.content_changes.first() {
-            tracing::debug!("document changed");
-            self.handle_change(params.text_document.uri.clone(), change.text.clone())
-                .await;
+        tracing::debug!("document changed");
+        let uri = params.text_document.uri;
+        let (mut text, mut ast) = match self.documents.lock().unwrap().get(&uri) {
+            Some(doc) => (doc.text.clone(), doc.ast.clone()),
+            None => (String::new(), None),
+        };
+        for change in &params.content_changes {
+            parser::apply_change(&mut text, &mut ast, change);
+        }
+        self.handle_change(uri, text, ast, Some(params.text_document.version))
+            .await;
+    }
+
+    async fn did_close(&self, params: DidCloseTextDocumentParams) {
+        let uri = params.text_document.uri;
+        tracing::debug!("closed {}", uri);
+        self.documents.lock().unwrap().remove(&uri);
+        self.types.forget(&uri);
+        // unsaved edits are dropped with the document, so the file's symbols
+        // go back to what is on disk, if it is indexed at all
+        let filter = self.filter.read().unwrap().clone();
+        let path = uri.to_file_path().ok().filter(|path| {
+            self.index.layer(&uri) == indexer::Layer::Vendor
+                || filter.is_some_and(|f| f.accepts(path))
+        });
+        let Some(path) = path else {
+            self.index.remove(&uri);
+            return;
+        };
+        let index = self.index.clone();
+        let indexed = tokio::task::spawn_blocking(move || indexer::index_file(&path, &index)).await;
+        match indexed {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => tracing::warn!("failed to index {}: {}", uri, e),
+            Err(e) => tracing::error!("indexing {} panicked: {}", uri, e),
+        }
+        // the document may have been opened again while the file was read
+        if let Some(doc) = self.documents.lock().unwrap().get(&uri) {
+            self.index.insert(uri.clone(), doc.symbols.clone());
         }
     }
 
@@ -125,22 +239,19 @@ impl LanguageServer for Backend {
         let position = params.text_document_position_params.position;
         if let Some(doc) = self.get_document(&uri) {
             if let Some(name) = self.symbol_at_position(&doc, position) {
-                if let Some(resolved) = resolver::resolve_symbol(
-                    &name,
-                    &uri,
-                    position,
-                    &doc.text,
-                    doc.ast.as_ref().unwrap(),
-                    &doc.symbols,
-                    &self.index,
-                ) {
+                if let Some(resolved) = self.resolve_at(&uri, &doc, &name, position) {
                     tracing::debug!(
                         "goto_definition found: {} at {:?}",
                         resolved.name,
                         resolved.location
                     );
                     tracing::debug!("goto_definition: returning definition");
-                    return Ok(Some(GotoDefinitionResponse::Scalar(resolved.location)));
+                    if resolved.alternatives.is_empty() {
+                        return Ok(Some(GotoDefinitionResponse::Scalar(resolved.location)));
+                    }
+                    let mut locations = vec![resolved.location];
+                    locations.extend(resolved.alternatives);
+                    return Ok(Some(GotoDefinitionResponse::Array(locations)));
                 } else {
                     tracing::debug!("goto_definition: symbol '{}' not resolved", name);
                 }
@@ -162,25 +273,41 @@ impl LanguageServer for Backend {
         let _timer = crate::metrics::Timer::new("completion");
         tracing::debug!("completion request");
         let uri = params.text_document_position.text_document.uri;
+        let position = params.text_document_position.position;
         let mut items = Vec::new();
+        let mut prefix = String::new();
+        let mut local = indexer::FileSymbols::new();
         if let Some(doc) = self.get_document(&uri) {
-            for sym in doc.symbols.values() {
-                items.push(CompletionItem {
-                    label: sym.name.clone(),
-                    kind: Some(map_completion_kind(&sym.kind)),
-                    ..CompletionItem::default()
-                });
+            prefix = word_before(&doc.text, position).to_lowercase();
+            if let Some(members) = self.member_completions(&uri, &doc, position) {
+                tracing::debug!("completion returned {} members", members.len());
+                return Ok(Some(CompletionResponse::Array(members)));
             }
-        }
-        for entry in self.index.iter() {
-            for sym in entry.value().values() {
-                items.push(CompletionItem {
-                    label: sym.name.clone(),
-                    kind: Some(map_completion_kind(&sym.kind)),
-                    ..CompletionItem::default()
-                });
+            for (name, defs) in &doc.symbols {
+                if let Some(sym) = defs.first() {
+                    items.push(CompletionItem {
+                        label: name.clone(),
+                        ..completion_item(sym)
+                    });
+                }
             }
+            local = doc.symbols;
         }
+        self.index.for_each_name(|name, kind, layer| {
+            // dependencies and stubs only show up once the user starts
+            // typing a matching name, so they don't flood the list
+            if local.contains_key(name)
+                || (layer != indexer::Layer::Project
+                    && (prefix.is_empty() || !short_name(name).to_lowercase().starts_with(&prefix)))
+            {
+                return;
+            }
+            items.push(CompletionItem {
+                label: name.to_string(),
+                kind: Some(map_completion_kind(kind)),
+                ..CompletionItem::default()
+            });
+        });
         tracing::debug!("completion returned {} items", items.len());
         tracing::debug!("completion completed");
         Ok(Some(CompletionResponse::Array(items)))
@@ -191,103 +318,163 @@ impl LanguageServer for Backend {
         tracing::debug!("hover request");
         let uri = params.text_document_position_params.text_document.uri;
         let position = params.text_document_position_params.position;
-        if let Some(doc) = self.get_document(&uri) {
-            if let Some(name) = self.symbol_at_position(&doc, position) {
-                if let Some(resolved) = resolver::resolve_symbol(
-                    &name,
-                    &uri,
-                    position,
-                    &doc.text,
-                    doc.ast.as_ref().unwrap(),
-                    &doc.symbols,
-                    &self.index,
-                ) {
-                    let contents = HoverContents::Scalar(MarkedString::String(format!(
-                        "{} {:?}",
-                        resolved.name, resolved.kind
-                    )));
-                    tracing::debug!("hover: returning information for {}", resolved.name);
-                    return Ok(Some(Hover {
-                        contents,
-                        range: Some(resolved.location.range),
-                    }));
-                }
-            }
+        let doc = self.get_document(&uri);
+        if let Some(doc) = &doc
+            && let Some((range, ty)) = self.variable_type(&uri, doc, position)
+        {
+            let name = doc.text.get(range.clone()).unwrap_or_default();
+            return Ok(Some(markdown_hover(
+                hover::variable(name, &ty.to_string()),
+                None,
+            )));
+        }
+        if let Some(doc) = &doc
+            && let Some(name) = self.symbol_at_position(doc, position)
+            && let Some(resolved) = self.resolve_at(&uri, doc, &name, position)
+        {
+            let source = self.parsed_source(&resolved.location.uri);
+            let root = self.root.read().unwrap().clone();
+            let source = source.as_ref().map(|(src, ast)| (src.as_str(), ast));
+            let value = hover::render(&resolved, source, root.as_deref());
+            let range = doc
+                .ast
+                .as_ref()
+                .and_then(|ast| resolver::name_node_at(ast, parser::point_at(&doc.text, position)))
+                .map(|node| parser::node_range(&doc.text, node));
+            tracing::debug!("hover: returning information for {}", resolved.name);
+            return Ok(Some(markdown_hover(value, range)));
         }
         tracing::debug!("hover: returning None");
         Ok(None)
     }
 
+    async fn signature_help(&self, params: SignatureHelpParams) -> Result<Option<SignatureHelp>> {
+        let _timer = crate::metrics::Timer::new("signature_help");
+        let uri = params.text_document_position_params.text_document.uri;
+        let position = params.text_document_position_params.position;
+        let Some(doc) = self.get_document(&uri) else {
+            tracing::debug!("signature_help: document not found for uri {}", uri);
+            return Ok(None);
+        };
+        let help = self.signature_at(&uri, &doc, position);
+        if help.is_none() {
+            tracing::debug!("signature_help: no call at {:?}", position);
+        }
+        Ok(help)
+    }
+
+    async fn document_symbol(
+        &self,
+        params: DocumentSymbolParams,
+    ) -> Result<Option<DocumentSymbolResponse>> {
+        let _timer = crate::metrics::Timer::new("document_symbol");
+        let uri = params.text_document.uri;
+        let Some(doc) = self.get_document(&uri) else {
+            tracing::debug!("document_symbol: document not found for uri {}", uri);
+            return Ok(None);
+        };
+        let Some(ast) = doc.ast.as_ref() else {
+            return Ok(None);
+        };
+        let symbols = outline::document_symbols(&doc.text, ast);
+        Ok(Some(DocumentSymbolResponse::Nested(symbols)))
+    }
+
+    async fn symbol(
+        &self,
+        params: WorkspaceSymbolParams,
+    ) -> Result<Option<Vec<SymbolInformation>>> {
+        let _timer = crate::metrics::Timer::new("workspace_symbol");
+        let query = params.query.trim().trim_start_matches('\\');
+        // a qualified query is matched against the whole name, otherwise
+        // only the last segment counts
+        let qualified = query.contains('\\') || query.contains("::");
+        let mut matches = Vec::new();
+        self.index.for_each_name(|name, kind, layer| {
+            if *kind == indexer::SymbolKind::Variable {
+                return;
+            }
+            let target = if qualified { name } else { short_name(name) };
+            if let Some(score) = fuzzy::score(query, target) {
+                matches.push((layer, std::cmp::Reverse(score), name.to_string()));
+            }
+        });
+        if matches.len() > MAX_WORKSPACE_SYMBOLS {
+            matches.select_nth_unstable(MAX_WORKSPACE_SYMBOLS);
+            matches.truncate(MAX_WORKSPACE_SYMBOLS);
+        }
+        matches.sort_unstable();
+
+        let mut symbols = Vec::new();
+        for (_, _, name) in matches {
+            for (_, sym) in self.index.definitions(&name) {
+                symbols.push(symbol_information(sym));
+            }
+        }
+        symbols.truncate(MAX_WORKSPACE_SYMBOLS);
+        tracing::debug!("workspace/symbol returned {} symbols", symbols.len());
+        Ok(Some(symbols))
+    }
+
     async fn references(&self, params: ReferenceParams) -> Result<Option<Vec<Location>>> {
+        let _timer = crate::metrics::Timer::new("references");
         let uri = params.text_document_position.text_document.uri;
         let pos = params.text_document_position.position;
-        if let Some(doc) = self.get_document(&uri) {
-            if let Some(name) = self.symbol_at_position(&doc, pos) {
-                let mut out = Vec::new();
-                let docs = self.documents.lock().unwrap();
-                for (u, d) in docs.iter() {
-                    for (i, line) in d.text.lines().enumerate() {
-                        for m in line.match_indices(&name) {
-                            out.push(Location {
-                                uri: u.clone(),
-                                range: Range {
-                                    start: Position {
-                                        line: i as u32,
-                                        character: m.0 as u32,
-                                    },
-                                    end: Position {
-                                        line: i as u32,
-                                        character: m.0 as u32 + name.len() as u32,
-                                    },
-                                },
-                            });
-                        }
-                    }
-                }
-                if !out.is_empty() {
-                    return Ok(Some(out));
-                }
-            }
-        }
-        Ok(None)
+        let Some(target) = self.target_at(&uri, pos) else {
+            tracing::debug!("references: nothing to search for at {:?}", pos);
+            return Ok(None);
+        };
+        let include_declaration = params.context.include_declaration;
+        let locations: Vec<Location> = self
+            .find_occurrences(&uri, target)
+            .await
+            .into_iter()
+            .flat_map(|(uri, _, found)| references::locations(&uri, found, include_declaration))
+            .collect();
+        tracing::debug!("references returned {} locations", locations.len());
+        Ok((!locations.is_empty()).then_some(locations))
+    }
+
+    async fn prepare_rename(
+        &self,
+        params: TextDocumentPositionParams,
+    ) -> Result<Option<PrepareRenameResponse>> {
+        let (_, range) = self.rename_target(&params.text_document.uri, params.position)?;
+        Ok(Some(PrepareRenameResponse::Range(range)))
     }
 
     async fn rename(&self, params: RenameParams) -> Result<Option<WorkspaceEdit>> {
+        let _timer = crate::metrics::Timer::new("rename");
         let uri = params.text_document_position.text_document.uri;
         let pos = params.text_document_position.position;
         let new_name = params.new_name;
-        if let Some(doc) = self.get_document(&uri) {
-            if let Some(name) = self.symbol_at_position(&doc, pos) {
-                let mut changes: HashMap<Url, Vec<TextEdit>> = HashMap::new();
-                let docs = self.documents.lock().unwrap();
-                for (u, d) in docs.iter() {
-                    for (i, line) in d.text.lines().enumerate() {
-                        for m in line.match_indices(&name) {
-                            changes.entry(u.clone()).or_default().push(TextEdit {
-                                range: Range {
-                                    start: Position {
-                                        line: i as u32,
-                                        character: m.0 as u32,
-                                    },
-                                    end: Position {
-                                        line: i as u32,
-                                        character: m.0 as u32 + name.len() as u32,
-                                    },
-                                },
-                                new_text: new_name.clone(),
-                            });
-                        }
-                    }
-                }
-                if !changes.is_empty() {
-                    return Ok(Some(WorkspaceEdit {
-                        changes: Some(changes),
-                        ..WorkspaceEdit::default()
-                    }));
-                }
-            }
+        let (target, _) = self.rename_target(&uri, pos)?;
+        if !references::is_valid_name(&new_name) {
+            return Err(Error::invalid_params(format!(
+                "`{}` is not a valid PHP name",
+                new_name
+            )));
         }
-        Ok(None)
+        let edits: Vec<TextDocumentEdit> = self
+            .find_occurrences(&uri, target.clone())
+            .await
+            .into_iter()
+            .filter_map(|(uri, version, found)| {
+                let edits = references::rename_edits(&target, &found, &new_name);
+                (!edits.is_empty()).then(|| TextDocumentEdit {
+                    text_document: OptionalVersionedTextDocumentIdentifier { uri, version },
+                    edits: edits.into_iter().map(OneOf::Left).collect(),
+                })
+            })
+            .collect();
+        tracing::debug!("rename edits {} documents", edits.len());
+        if edits.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(WorkspaceEdit {
+            document_changes: Some(DocumentChanges::Edits(edits)),
+            ..WorkspaceEdit::default()
+        }))
     }
 
     async fn execute_command(
@@ -302,31 +489,54 @@ impl LanguageServer for Backend {
     }
 }
 
+/// Parameters of the `phppp/stubSource` request.
+#[derive(Debug, Deserialize)]
+pub struct StubSourceParams {
+    pub uri: Url,
+}
+
 impl Backend {
-    async fn handle_change(&self, uri: Url, content: String) {
+    /// Handle `phppp/stubSource`, returning the source of a bundled stub so
+    /// the client can display `phppp-stub:` locations read-only.
+    pub async fn stub_source(&self, params: StubSourceParams) -> Result<Option<String>> {
+        Ok(stubs::source(&params.uri).map(|s| s.to_string()))
+    }
+
+    async fn handle_change(
+        &self,
+        uri: Url,
+        content: String,
+        old: Option<parser::Ast>,
+        version: Option<i32>,
+    ) {
         let _timer = crate::metrics::Timer::new("handle_change");
         tracing::debug!("indexing {}", uri);
 
-        let ast = {
-            let bump = self.bump.lock().unwrap();
-            parser::parse_php(&content, &bump)
-        };
+        let ast = parser::reparse_php(&content, old.as_ref());
+        let diagnostics = diagnostics::syntax_errors(&content, &ast);
         let symbols = indexer::extract_symbols(&content, &ast, &uri);
-        self.index.insert(uri.clone(), symbols.clone());
-        analyzer::resolve_types_parallel(&symbols);
+        let imports = resolver::ImportTable::build(&content, &ast);
 
         {
+            // the index is updated under the documents lock so the
+            // background scan can't restore an older version in between
             let mut docs = self.documents.lock().unwrap();
+            self.index.insert(uri.clone(), symbols.clone());
             docs.insert(
-                uri,
+                uri.clone(),
                 DocumentState {
                     text: content,
                     ast: Some(ast),
                     symbols,
+                    imports,
+                    version,
                 },
             );
         }
 
+        self.client
+            .publish_diagnostics(uri, diagnostics, version)
+            .await;
         tracing::debug!("document indexed");
     }
 
@@ -334,34 +544,514 @@ impl Backend {
         self.documents.lock().unwrap().get(uri).cloned()
     }
 
-    fn symbol_at_position(&self, doc: &DocumentState, pos: Position) -> Option<String> {
-        let ast = doc.ast.as_ref()?;
-        let root = ast.0.root_node();
-        let point = tree_sitter::Point {
-            row: pos.line as usize,
-            column: pos.character as usize,
+    /// Resolve `name` at `position`, looking members of other objects up
+    /// through the inferred type of the object.
+    fn resolve_at(
+        &self,
+        uri: &Url,
+        doc: &DocumentState,
+        name: &str,
+        position: Position,
+    ) -> Option<resolver::ResolvedSymbol> {
+        let ctx = resolver::DocumentContext {
+            uri,
+            src: &doc.text,
+            ast: doc.ast.as_ref()?,
+            symbols: &doc.symbols,
+            imports: &doc.imports,
         };
-        let mut node = root.descendant_for_point_range(point, point)?;
-        while node.kind() != "name"
-            && node.kind() != "qualified_name"
-            && node.kind() != "variable_name"
+        let node = resolver::name_node_at(ctx.ast, parser::point_at(ctx.src, position))?;
+        self.types
+            .resolve_member(&ctx, node, &self.index)
+            .or_else(|| resolver::resolve_in_document(name, position, &ctx, &self.index))
+    }
+
+    /// The byte range and inferred type of the variable at `position`.
+    fn variable_type(
+        &self,
+        uri: &Url,
+        doc: &DocumentState,
+        position: Position,
+    ) -> Option<(std::ops::Range<usize>, analyzer::Type)> {
+        let ctx = resolver::DocumentContext {
+            uri,
+            src: &doc.text,
+            ast: doc.ast.as_ref()?,
+            symbols: &doc.symbols,
+            imports: &doc.imports,
+        };
+        let node = resolver::name_node_at(ctx.ast, parser::point_at(ctx.src, position))?;
+        if node.kind() != "variable_name"
+            || node
+                .parent()
+                .is_some_and(|p| p.kind().starts_with("scoped_property"))
         {
-            if let Some(parent) = node.parent() {
-                node = parent;
-            } else {
-                break;
+            return None;
+        }
+        Some((
+            node.byte_range(),
+            self.types.type_of(&ctx, node, &self.index),
+        ))
+    }
+
+    /// Members of the object or class before a `->`, `?->` or `::` ending
+    /// just before the word being completed, or `None` when the cursor isn't
+    /// after a member operator. `->` offers methods and properties, `::`
+    /// every member.
+    fn member_completions(
+        &self,
+        uri: &Url,
+        doc: &DocumentState,
+        position: Position,
+    ) -> Option<Vec<CompletionItem>> {
+        let line = doc.text.lines().nth(position.line as usize)?;
+        let cursor = parser::point_at(&doc.text, position);
+        let head = line.get(..cursor.column.min(line.len()))?;
+        let head = head.trim_end_matches(|c: char| c.is_alphanumeric() || c == '_' || c == '$');
+        let (head, instance) = if let Some(head) = head.strip_suffix("->") {
+            (head.strip_suffix('?').unwrap_or(head), true)
+        } else {
+            (head.strip_suffix("::")?, false)
+        };
+        let ctx = resolver::DocumentContext {
+            uri,
+            src: &doc.text,
+            ast: doc.ast.as_ref()?,
+            symbols: &doc.symbols,
+            imports: &doc.imports,
+        };
+        // the object is the largest expression ending where the operator
+        // starts
+        let end = tree_sitter::Point {
+            row: position.line as usize,
+            column: head.len(),
+        };
+        let before = tree_sitter::Point {
+            column: end.column.checked_sub(1)?,
+            ..end
+        };
+        let mut object = ctx
+            .ast
+            .0
+            .root_node()
+            .descendant_for_point_range(before, before)?;
+        while let Some(parent) = object.parent()
+            && parent.end_position() == end
+            && parent.kind() != "ERROR"
+        {
+            object = parent;
+        }
+        if object.end_position() != end {
+            return None;
+        }
+        let mut items: Vec<CompletionItem> = self
+            .types
+            .members(&ctx, object, &self.index)
+            .into_iter()
+            .filter_map(|(name, sym)| {
+                let label = if instance {
+                    if !matches!(
+                        sym.kind,
+                        indexer::SymbolKind::Method | indexer::SymbolKind::Property
+                    ) {
+                        return None;
+                    }
+                    name.trim_start_matches('$').to_string()
+                } else {
+                    name
+                };
+                Some(CompletionItem {
+                    label,
+                    detail: sym.type_hint.clone(),
+                    ..completion_item(&sym)
+                })
+            })
+            .collect();
+        items.sort_by(|a, b| a.label.cmp(&b.label));
+        Some(items)
+    }
+
+    /// Signature help for the call whose arguments are around `position`.
+    fn signature_at(
+        &self,
+        uri: &Url,
+        doc: &DocumentState,
+        position: Position,
+    ) -> Option<SignatureHelp> {
+        let offset = parser::byte_offset(&doc.text, parser::point_at(&doc.text, position));
+        let closed;
+        let (src, ast) = match doc.ast.as_ref() {
+            Some(ast) if signature_help::arguments_at(ast, offset).is_some() => {
+                (doc.text.as_str(), ast)
+            }
+            _ => {
+                closed = signature_help::close_call(&doc.text, offset);
+                (closed.0.as_str(), &closed.1)
+            }
+        };
+        let arguments = signature_help::arguments_at(ast, offset)?;
+        let ctx = resolver::DocumentContext {
+            uri,
+            src,
+            ast,
+            symbols: &doc.symbols,
+            imports: &doc.imports,
+        };
+        let callee = self.callee(&ctx, arguments.parent()?)?;
+        let (source, source_ast) = self.parsed_source(&callee.location.uri)?;
+        let argument = signature_help::active_argument(arguments, offset, src);
+        let signature = signature_help::signature(
+            &source,
+            &source_ast,
+            &callee.location,
+            callee.doc.as_deref(),
+            &argument,
+        )?;
+        Some(SignatureHelp {
+            active_parameter: signature.active_parameter,
+            signatures: vec![signature],
+            active_signature: Some(0),
+        })
+    }
+
+    /// The function, method or constructor a call expression calls.
+    fn callee(
+        &self,
+        ctx: &resolver::DocumentContext,
+        call: tree_sitter::Node,
+    ) -> Option<resolver::ResolvedSymbol> {
+        let name = match call.kind() {
+            "object_creation_expression" => {
+                let class = call.named_child(0)?;
+                let class = resolver::scope_class(
+                    class.utf8_text(ctx.src.as_bytes()).ok()?,
+                    class.start_position(),
+                    ctx,
+                )?;
+                return resolver::lookup_member(
+                    &class,
+                    "__construct",
+                    ctx.symbols,
+                    &self.index,
+                    &mut |_| {},
+                );
+            }
+            "member_call_expression" | "nullsafe_member_call_expression" => {
+                return self.types.resolve_member(
+                    ctx,
+                    call.child_by_field_name("name")?,
+                    &self.index,
+                );
+            }
+            "function_call_expression" => call.child_by_field_name("function")?,
+            _ => call.child_by_field_name("name")?,
+        };
+        let reference = resolver::reference_name(name, ctx.src)?;
+        let position = parser::node_range(ctx.src, name).start;
+        resolver::resolve_in_document(&reference, position, ctx, &self.index)
+    }
+
+    /// The text and syntax tree of the file at `uri`: the open document's,
+    /// or a bundled stub or the file on disk, parsed once.
+    fn parsed_source(&self, uri: &Url) -> Option<(String, parser::Ast)> {
+        let src = match (self.get_document(uri), stubs::source(uri)) {
+            (
+                Some(DocumentState {
+                    text,
+                    ast: Some(ast),
+                    ..
+                }),
+                _,
+            ) => return Some((text, ast)),
+            (Some(doc), _) => doc.text,
+            (None, Some(src)) => src.to_string(),
+            (None, None) => std::fs::read_to_string(uri.to_file_path().ok()?).ok()?,
+        };
+        let ast = parser::parse_php(&src);
+        Some((src, ast))
+    }
+
+    fn target_at(&self, uri: &Url, pos: Position) -> Option<references::Target> {
+        let doc = self.get_document(uri)?;
+        let ctx = resolver::DocumentContext {
+            uri,
+            src: &doc.text,
+            ast: doc.ast.as_ref()?,
+            symbols: &doc.symbols,
+            imports: &doc.imports,
+        };
+        references::target_at(&ctx, pos, &self.index, &self.types)
+    }
+
+    /// The target of a rename at `pos`, and the range of the name there.
+    fn rename_target(&self, uri: &Url, pos: Position) -> Result<(references::Target, Range)> {
+        let doc = self
+            .get_document(uri)
+            .ok_or_else(|| Error::invalid_params("Document is not open"))?;
+        let ast = doc
+            .ast
+            .as_ref()
+            .ok_or_else(|| Error::invalid_params("Document is not parsed"))?;
+        let ctx = resolver::DocumentContext {
+            uri,
+            src: &doc.text,
+            ast,
+            symbols: &doc.symbols,
+            imports: &doc.imports,
+        };
+        references::prepare_rename(&ctx, pos, &self.index, &self.types)
+            .map_err(Error::invalid_params)
+    }
+
+    /// Search the open documents and, for targets other files can refer
+    /// to, the project files on disk that aren't open. Vendor code and stubs
+    /// are not searched. Each file with occurrences comes with the version of
+    /// its open document, sorted by URI.
+    async fn find_occurrences(
+        &self,
+        uri: &Url,
+        target: references::Target,
+    ) -> Vec<(Url, Option<i32>, Vec<references::Occurrence>)> {
+        let open: Vec<(Url, DocumentState)> = {
+            let docs = self.documents.lock().unwrap();
+            docs.iter()
+                .filter(|(u, _)| target.is_global() || *u == uri)
+                .map(|(u, d)| (u.clone(), d.clone()))
+                .collect()
+        };
+        let files: Vec<Url> = if target.is_global() {
+            let uris: Vec<Url> = self.index.iter().map(|e| e.key().clone()).collect();
+            uris.into_iter()
+                .filter(|u| {
+                    self.index.layer(u) == indexer::Layer::Project
+                        && !open.iter().any(|(o, _)| o == u)
+                })
+                .collect()
+        } else {
+            Vec::new()
+        };
+        let index = self.index.clone();
+        let types = self.types.clone();
+        let search = tokio::task::spawn_blocking(move || {
+            use rayon::prelude::*;
+            let in_open = open.par_iter().filter_map(|(uri, doc)| {
+                let ctx = resolver::DocumentContext {
+                    uri,
+                    src: &doc.text,
+                    ast: doc.ast.as_ref()?,
+                    symbols: &doc.symbols,
+                    imports: &doc.imports,
+                };
+                let found = references::occurrences_in_document(&target, &ctx, &index, &types);
+                Some((uri.clone(), doc.version, found))
+            });
+            let on_disk = files.par_iter().map(|uri| {
+                let found = references::occurrences_in_file(&target, uri, &index);
+                (uri.clone(), None, found)
+            });
+            let mut out: Vec<_> = in_open
+                .chain(on_disk)
+                .filter(|(_, _, found)| !found.is_empty())
+                .collect();
+            out.sort_by(|a, b| a.0.as_str().cmp(b.0.as_str()));
+            out
+        });
+        search.await.unwrap_or_else(|e| {
+            tracing::warn!("reference search failed: {}", e);
+            Vec::new()
+        })
+    }
+
+    fn symbol_at_position(&self, doc: &DocumentState, pos: Position) -> Option<String> {
+        let ast = doc.ast.as_ref()?;
+        let node = resolver::name_node_at(ast, parser::point_at(&doc.text, pos))?;
+        resolver::reference_name(node, &doc.text)
+    }
+}
+
+/// Index the workspace on the blocking pool, reporting files indexed out of
+/// the total as `$/progress`. Requests are answered from the partially
+/// filled index while the scan runs. Unchanged files are taken from the
+/// index cache, which is rewritten afterwards. Documents open in the editor
+/// keep the symbols of their unsaved text over the ones read from disk.
+async fn scan_in_background(
+    client: Client,
+    index: indexer::GlobalIndex,
+    documents: Arc<Mutex<HashMap<Url, DocumentState>>>,
+    scan: PendingScan,
+) {
+    let _timer = crate::metrics::Timer::new("scan_workspace");
+    let PendingScan {
+        root,
+        filter,
+        cache,
+        progress,
+    } = scan;
+    let files = {
+        let index = index.clone();
+        let cache = cache.clone();
+        tokio::task::spawn_blocking(move || {
+            let files = indexer::workspace_files(&root, &index, &filter)?;
+            let cached = cache
+                .map(|path| cache::IndexCache::load(&path))
+                .unwrap_or_default();
+            Ok::<_, std::io::Error>((files, cached))
+        })
+        .await
+    };
+    let (files, cached) = match files {
+        Ok(Ok(files)) => files,
+        Ok(Err(e)) => {
+            tracing::error!("workspace scan failed: {}", e);
+            crate::metrics::inc_error("scan_workspace");
+            return;
+        }
+        Err(e) => {
+            tracing::error!("workspace scan panicked: {}", e);
+            crate::metrics::inc_error("scan_workspace");
+            return;
+        }
+    };
+    let total = files.len();
+    tracing::info!("indexing {} files", total);
+
+    let token = NumberOrString::String("phppp/scan".into());
+    let progress = progress
+        && client
+            .send_request::<request::WorkDoneProgressCreate>(WorkDoneProgressCreateParams {
+                token: token.clone(),
+            })
+            .await
+            .is_ok();
+    let report = |value: WorkDoneProgress| {
+        let client = client.clone();
+        let token = token.clone();
+        async move {
+            if progress {
+                client
+                    .send_notification::<notification::Progress>(ProgressParams {
+                        token,
+                        value: ProgressParamsValue::WorkDone(value),
+                    })
+                    .await;
             }
         }
-        if node.kind() == "name"
-            || node.kind() == "qualified_name"
-            || node.kind() == "variable_name"
+    };
+    report(WorkDoneProgress::Begin(WorkDoneProgressBegin {
+        title: "Indexing".into(),
+        cancellable: Some(false),
+        message: Some(format!("0/{} files", total)),
+        percentage: Some(0),
+    }))
+    .await;
+
+    // report at most once per percent so large workspaces don't flood the
+    // client
+    let step = (total / 100).max(1);
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+    let scanned = index.clone();
+    let scan = tokio::task::spawn_blocking(move || {
+        let index = scanned;
+        let indexed = indexer::index_files_cached(&files, &index, &cached, |done| {
+            if done % step == 0 || done == total {
+                let _ = tx.send(done);
+            }
+        });
+        if let Some(path) = cache
+            && let Err(e) = cache::IndexCache::from_files(indexed).save(&path)
         {
-            return node
-                .utf8_text(doc.text.as_bytes())
-                .ok()
-                .map(|s| s.to_string());
+            tracing::warn!("failed to write index cache {}: {}", path.display(), e);
         }
-        None
+    });
+    while let Some(done) = rx.recv().await {
+        report(WorkDoneProgress::Report(WorkDoneProgressReport {
+            cancellable: Some(false),
+            message: Some(format!("{}/{} files", done, total)),
+            percentage: Some((done * 100 / total) as u32),
+        }))
+        .await;
+    }
+    if let Err(e) = scan.await {
+        tracing::error!("workspace scan panicked: {}", e);
+        crate::metrics::inc_error("scan_workspace");
+    }
+    for (uri, doc) in documents.lock().unwrap().iter() {
+        index.insert(uri.clone(), doc.symbols.clone());
+    }
+    report(WorkDoneProgress::End(WorkDoneProgressEnd {
+        message: Some(format!("Indexed {} files", total)),
+    }))
+    .await;
+    tracing::info!("workspace scan finished");
+}
+
+/// Most results returned for one `workspace/symbol` query.
+const MAX_WORKSPACE_SYMBOLS: usize = 256;
+
+#[allow(deprecated)]
+fn symbol_information(sym: indexer::Symbol) -> SymbolInformation {
+    let container = sym.container.clone().or_else(|| {
+        sym.name
+            .rsplit_once('\\')
+            .map(|(namespace, _)| namespace.to_string())
+    });
+    SymbolInformation {
+        name: short_name(&sym.name).to_string(),
+        kind: indexer::map_symbol_kind(&sym.kind),
+        tags: None,
+        deprecated: None,
+        location: sym.location,
+        container_name: container,
+    }
+}
+
+fn markdown_hover(value: String, range: Option<Range>) -> Hover {
+    Hover {
+        contents: HoverContents::Markup(MarkupContent {
+            kind: MarkupKind::Markdown,
+            value,
+        }),
+        range,
+    }
+}
+
+/// The identifier fragment immediately before `pos`.
+fn word_before(text: &str, pos: Position) -> &str {
+    let Some(line) = text.lines().nth(pos.line as usize) else {
+        return "";
+    };
+    let end = parser::point_at(text, pos).column.min(line.len());
+    let Some(head) = line.get(..end) else {
+        return "";
+    };
+    let start = head
+        .char_indices()
+        .rfind(|&(_, c)| !(c.is_alphanumeric() || c == '_'))
+        .map(|(i, c)| i + c.len_utf8())
+        .unwrap_or(0);
+    &head[start..]
+}
+
+/// The unqualified part of a symbol name: `Collection` for
+/// `Illuminate\Support\Collection`, `save` for `App\User::save`.
+fn short_name(name: &str) -> &str {
+    let name = name.rsplit("::").next().unwrap_or(name);
+    name.rsplit('\\').next().unwrap_or(name)
+}
+
+/// A completion item for `sym` without its label, marked deprecated when
+/// its docblock says so and documented with the docblock summary.
+fn completion_item(sym: &indexer::Symbol) -> CompletionItem {
+    let doc = sym.doc.as_ref();
+    CompletionItem {
+        kind: Some(map_completion_kind(&sym.kind)),
+        tags: doc
+            .and_then(|d| d.deprecated.as_ref())
+            .map(|_| vec![CompletionItemTag::DEPRECATED]),
+        documentation: doc
+            .filter(|d| !d.summary.is_empty())
+            .map(|d| Documentation::String(d.summary.clone())),
+        ..CompletionItem::default()
     }
 }
 
@@ -369,14 +1059,23 @@ fn map_completion_kind(kind: &indexer::SymbolKind) -> CompletionItemKind {
     match kind {
         indexer::SymbolKind::Function => CompletionItemKind::FUNCTION,
         indexer::SymbolKind::Class => CompletionItemKind::CLASS,
+        indexer::SymbolKind::Interface => CompletionItemKind::INTERFACE,
+        indexer::SymbolKind::Trait => CompletionItemKind::CLASS,
+        indexer::SymbolKind::Enum => CompletionItemKind::ENUM,
         indexer::SymbolKind::Constant => CompletionItemKind::CONSTANT,
         indexer::SymbolKind::Variable => CompletionItemKind::VARIABLE,
+        indexer::SymbolKind::Method => CompletionItemKind::METHOD,
+        indexer::SymbolKind::Property => CompletionItemKind::PROPERTY,
+        indexer::SymbolKind::ClassConstant => CompletionItemKind::CONSTANT,
+        indexer::SymbolKind::EnumCase => CompletionItemKind::ENUM_MEMBER,
     }
 }
 
 pub async fn run_server() {
     let stdin = tokio::io::stdin();
     let stdout = tokio::io::stdout();
-    let (service, socket) = LspService::new(|client| Backend::new(client));
+    let (service, socket) = LspService::build(Backend::new)
+        .custom_method("phppp/stubSource", Backend::stub_source)
+        .finish();
     Server::new(stdin, stdout, socket).serve(service).await;
 }
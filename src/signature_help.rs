@@ -0,0 +1,201 @@
+use tower_lsp::lsp_types::{
+    Documentation, Location, ParameterInformation, ParameterLabel, SignatureInformation,
+};
+use tree_sitter::Node;
+
+use crate::hover;
+use crate::parser::{self, Ast};
+use crate::phpdoc::DocBlock;
+
+/// The call expressions signature help is offered for.
+const CALLS: &[&str] = &[
+    "function_call_expression",
+    "member_call_expression",
+    "nullsafe_member_call_expression",
+    "scoped_call_expression",
+    "object_creation_expression",
+];
+
+/// The argument the cursor is in.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ActiveArgument {
+    /// Its position among the arguments, counted by the commas before it.
+    pub index: usize,
+    /// Its name when it is a named argument, `limit` in `limit: 10`.
+    pub name: Option<String>,
+    /// How many arguments before it are passed by position.
+    pub positional: usize,
+    /// The names of the other named arguments.
+    pub named: Vec<String>,
+}
+
+/// The argument list of the innermost call around byte `offset`, when
+/// `offset` lies between its parentheses.
+pub fn arguments_at(ast: &Ast, offset: usize) -> Option<Node<'_>> {
+    let mut node = ast
+        .0
+        .root_node()
+        .descendant_for_byte_range(offset, offset)?;
+    loop {
+        if node.kind() == "arguments"
+            && node
+                .parent()
+                .is_some_and(|call| CALLS.contains(&call.kind()))
+            && node.start_byte() < offset
+            && (offset < node.end_byte() || !closed(node))
+        {
+            return Some(node);
+        }
+        node = node.parent()?;
+    }
+}
+
+/// Parse `src` with a `)` inserted at `offset`. The parser can't make out a
+/// call whose closing parenthesis hasn't been typed yet, as in `foo(1, `.
+/// An offset inside a character moves back to its start.
+pub fn close_call(src: &str, offset: usize) -> (String, Ast) {
+    let mut offset = offset.min(src.len());
+    while !src.is_char_boundary(offset) {
+        offset -= 1;
+    }
+    let mut closed = src.to_string();
+    closed.insert(offset, ')');
+    let ast = parser::parse_php(&closed);
+    (closed, ast)
+}
+
+/// The argument of `arguments` that byte `offset` is in.
+pub fn active_argument(arguments: Node, offset: usize, src: &str) -> ActiveArgument {
+    let mut active = ActiveArgument::default();
+    let mut passed = Vec::new();
+    for i in 0..arguments.child_count() {
+        let Some(child) = arguments.child(i) else {
+            continue;
+        };
+        if child.kind() == "," && child.end_byte() <= offset {
+            active.index += 1;
+        } else if child.kind() == "argument" {
+            passed.push((active.index, child));
+        }
+    }
+    for (index, argument) in passed {
+        let name = argument_name(argument, src);
+        match name {
+            Some(name) if index == active.index => active.name = Some(name),
+            Some(name) => active.named.push(name),
+            None if index < active.index => active.positional += 1,
+            None => {}
+        }
+    }
+    active
+}
+
+/// The name of a named argument. While its value is still missing, as in
+/// `limit: `, the parser leaves the name as the argument and the colon in
+/// an error after it.
+fn argument_name(argument: Node, src: &str) -> Option<String> {
+    let name = argument.child_by_field_name("name").or_else(|| {
+        let value = argument.named_child(0)?;
+        let rest = src[argument.end_byte()..].trim_start();
+        (argument.named_child_count() == 1
+            && value.kind() == "name"
+            && rest.starts_with(':')
+            && !rest.starts_with("::"))
+        .then_some(value)
+    })?;
+    name.utf8_text(src.as_bytes()).ok().map(str::to_string)
+}
+
+/// The signature of the function or method declared at `location` in
+/// `src`, parsed as `ast`, documented by `doc`, with the parameter
+/// `argument` is passed to as the active one.
+pub fn signature(
+    src: &str,
+    ast: &Ast,
+    location: &Location,
+    doc: Option<&DocBlock>,
+    argument: &ActiveArgument,
+) -> Option<SignatureInformation> {
+    let label = hover::signature(src, ast, location)?;
+    let decl = hover::declaration(src, ast, location)?;
+    if !matches!(decl.kind(), "function_definition" | "method_declaration") {
+        return None;
+    }
+    let list = decl.child_by_field_name("parameters")?;
+    let params: Vec<Node> = (0..list.named_child_count())
+        .filter_map(|i| list.named_child(i))
+        .filter(|p| p.kind() != "comment")
+        .collect();
+    let names: Vec<String> = params
+        .iter()
+        .map(|p| {
+            p.child_by_field_name("name")
+                .and_then(|name| name.utf8_text(src.as_bytes()).ok())
+                .unwrap_or("")
+                .trim_start_matches('$')
+                .to_string()
+        })
+        .collect();
+
+    let mut searched = 0;
+    let parameters = params
+        .iter()
+        .zip(&names)
+        .map(|(param, name)| {
+            let text = hover::one_line(&src[hover::declaration_start(*param)..param.end_byte()]);
+            let label = match label[searched..].find(&text) {
+                Some(start) => {
+                    let start = searched + start;
+                    searched = start + text.len();
+                    ParameterLabel::LabelOffsets([
+                        utf16_len(&label[..start]),
+                        utf16_len(&label[..searched]),
+                    ])
+                }
+                None => ParameterLabel::Simple(text),
+            };
+            let documentation = doc
+                .and_then(|doc| doc.param(&format!("${}", name)))
+                .filter(|tag| !tag.description.is_empty())
+                .map(|tag| Documentation::String(tag.description.clone()));
+            ParameterInformation {
+                label,
+                documentation,
+            }
+        })
+        .collect();
+
+    let variadic = params
+        .last()
+        .is_some_and(|p| p.kind() == "variadic_parameter");
+    let active = match &argument.name {
+        Some(name) => names.iter().position(|n| n == name),
+        // once arguments are named, the next one is the first parameter
+        // they leave out
+        None if !argument.named.is_empty() => names
+            .iter()
+            .enumerate()
+            .position(|(i, n)| i >= argument.positional && !argument.named.contains(n)),
+        None if variadic => Some(argument.index.min(names.len() - 1)),
+        None => Some(argument.index),
+    };
+    Some(SignatureInformation {
+        label,
+        documentation: doc
+            .filter(|doc| !doc.summary.is_empty())
+            .map(|doc| Documentation::String(doc.summary.clone())),
+        parameters: Some(parameters),
+        active_parameter: active.map(|i| i as u32),
+    })
+}
+
+/// Whether an argument list has its closing parenthesis.
+fn closed(arguments: Node) -> bool {
+    arguments
+        .child(arguments.child_count().saturating_sub(1))
+        .is_some_and(|last| last.kind() == ")" && !last.is_missing())
+}
+
+fn utf16_len(text: &str) -> u32 {
+    text.encode_utf16().count() as u32
+}
@@ -0,0 +1,99 @@
+use tower_lsp::lsp_types::Url;
+
+use crate::indexer::{self, GlobalIndex, Layer};
+use crate::parser;
+
+/// URI scheme under which bundled stubs are indexed. Clients resolve these
+/// URIs through the `phppp/stubSource` request and show them read-only.
+pub const SCHEME: &str = "phppp-stub";
+
+struct Stub {
+    extension: &'static str,
+    file: &'static str,
+    source: &'static str,
+}
+
+static STUBS: &[Stub] = &[
+    Stub {
+        extension: "core",
+        file: "Core.php",
+        source: include_str!("../stubs/core/Core.php"),
+    },
+    Stub {
+        extension: "standard",
+        file: "standard.php",
+        source: include_str!("../stubs/standard/standard.php"),
+    },
+    Stub {
+        extension: "date",
+        file: "date.php",
+        source: include_str!("../stubs/date/date.php"),
+    },
+    Stub {
+        extension: "json",
+        file: "json.php",
+        source: include_str!("../stubs/json/json.php"),
+    },
+    Stub {
+        extension: "pcre",
+        file: "pcre.php",
+        source: include_str!("../stubs/pcre/pcre.php"),
+    },
+    Stub {
+        extension: "spl",
+        file: "spl.php",
+        source: include_str!("../stubs/spl/spl.php"),
+    },
+    Stub {
+        extension: "pdo",
+        file: "PDO.php",
+        source: include_str!("../stubs/pdo/PDO.php"),
+    },
+    Stub {
+        extension: "mbstring",
+        file: "mbstring.php",
+        source: include_str!("../stubs/mbstring/mbstring.php"),
+    },
+];
+
+/// Names of every bundled extension, in the order they are indexed.
+pub fn extensions() -> Vec<String> {
+    let mut out: Vec<String> = Vec::new();
+    for stub in STUBS {
+        if !out.iter().any(|e| e == stub.extension) {
+            out.push(stub.extension.to_string());
+        }
+    }
+    out
+}
+
+fn stub_uri(stub: &Stub) -> Url {
+    Url::parse(&format!("{}:///{}/{}", SCHEME, stub.extension, stub.file)).unwrap()
+}
+
+/// Return the PHP source of the stub file behind `uri`.
+pub fn source(uri: &Url) -> Option<&'static str> {
+    STUBS
+        .iter()
+        .find(|stub| stub_uri(stub) == *uri)
+        .map(|stub| stub.source)
+}
+
+/// Parse and index the stubs of the requested extensions into `index`.
+pub fn index_stubs(enabled: &[String], index: &GlobalIndex) {
+    for name in enabled {
+        if !STUBS.iter().any(|stub| stub.extension == name) {
+            tracing::warn!("unknown stub extension '{}'", name);
+        }
+    }
+    for stub in STUBS
+        .iter()
+        .filter(|s| enabled.iter().any(|e| e == s.extension))
+    {
+        let ast = parser::parse_php(stub.source);
+        let uri = stub_uri(stub);
+        let symbols = indexer::extract_symbols(stub.source, &ast, &uri);
+        tracing::debug!("indexed {} stub symbols from {}", symbols.len(), uri);
+        index.insert_in(uri, symbols, Layer::Stub);
+    }
+}
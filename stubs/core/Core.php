@@ -0,0 +1,352 @@
+<?php
+
+// Core language interfaces, classes, functions and constants.
+
+/**
+ * Interface to detect if a class is traversable using foreach.
+ */
+interface Traversable {}
+
+/**
+ * Interface for external iterators or objects that can be iterated themselves
+ * internally.
+ */
+interface Iterator extends Traversable
+{
+    /** Return the current element. */
+    public function current(): mixed;
+
+    /** Move forward to next element. */
+    public function next(): void;
+
+    /** Return the key of the current element. */
+    public function key(): mixed;
+
+    /** Checks if current position is valid. */
+    public function valid(): bool;
+
+    /** Rewind the Iterator to the first element. */
+    public function rewind(): void;
+}
+
+/**
+ * Interface to create an external Iterator.
+ */
+interface IteratorAggregate extends Traversable
+{
+    /** Retrieve an external iterator. */
+    public function getIterator(): Iterator;
+}
+
+/**
+ * Interface to provide accessing objects as arrays.
+ */
+interface ArrayAccess
+{
+    /** Whether an offset exists. */
+    public function offsetExists(mixed $offset): bool;
+
+    /** Offset to retrieve. */
+    public function offsetGet(mixed $offset): mixed;
+
+    /** Assign a value to the specified offset. */
+    public function offsetSet(mixed $offset, mixed $value): void;
+
+    /** Unset an offset. */
+    public function offsetUnset(mixed $offset): void;
+}
+
+/**
+ * Classes implementing Countable can be used with the count() function.
+ */
+interface Countable
+{
+    /** Count elements of an object. */
+    public function count(): int;
+}
+
+/**
+ * Denotes a class as having a __toString() method.
+ */
+interface Stringable
+{
+    public function __toString(): string;
+}
+
+/**
+ * Base interface for any object that can be thrown via a throw statement.
+ */
+interface Throwable extends Stringable
+{
+    /** Gets the message. */
+    public function getMessage(): string;
+
+    /** Gets the exception code. */
+    public function getCode();
+
+    /** Gets the file in which the object was created. */
+    public function getFile(): string;
+
+    /** Gets the line on which the object was instantiated. */
+    public function getLine(): int;
+
+    /** Gets the stack trace. */
+    public function getTrace(): array;
+
+    /** Gets the stack trace as a string. */
+    public function getTraceAsString(): string;
+
+    /** Returns the previous Throwable. */
+    public function getPrevious(): ?Throwable;
+}
+
+/**
+ * Base class for all user exceptions.
+ */
+class Exception implements Throwable
+{
+    protected $message = '';
+    protected $code = 0;
+    protected string $file = '';
+    protected int $line = 0;
+
+    public function __construct(string $message = "", int $code = 0, ?Throwable $previous = null) {}
+    final public function getMessage(): string {}
+    final public function getCode() {}
+    final public function getFile(): string {}
+    final public function getLine(): int {}
+    final public function getTrace(): array {}
+    final public function getTraceAsString(): string {}
+    final public function getPrevious(): ?Throwable {}
+    public function __toString(): string {}
+}
+
+/**
+ * An Error Exception.
+ */
+class ErrorException extends Exception
+{
+    public function __construct(string $message = "", int $code = 0, int $severity = E_ERROR, ?string $filename = null, ?int $line = null, ?Throwable $previous = null) {}
+    final public function getSeverity(): int {}
+}
+
+/**
+ * Base class for all internal PHP errors.
+ */
+class Error implements Throwable
+{
+    protected $message = '';
+    protected $code = 0;
+    protected string $file = '';
+    protected int $line = 0;
+
+    public function __construct(string $message = "", int $code = 0, ?Throwable $previous = null) {}
+    final public function getMessage(): string {}
+    final public function getCode() {}
+    final public function getFile(): string {}
+    final public function getLine(): int {}
+    final public function getTrace(): array {}
+    final public function getTraceAsString(): string {}
+    final public function getPrevious(): ?Throwable {}
+    public function __toString(): string {}
+}
+
+class TypeError extends Error {}
+
+class ValueError extends Error {}
+
+class ArgumentCountError extends TypeError {}
+
+class ArithmeticError extends Error {}
+
+class DivisionByZeroError extends ArithmeticError {}
+
+class UnhandledMatchError extends Error {}
+
+/**
+ * Class used to represent anonymous functions.
+ */
+final class Closure
+{
+    /** Duplicates a closure with a specific bound object and class scope. */
+    public static function bind(Closure $closure, ?object $newThis, object|string|null $newScope = "static"): ?Closure {}
+
+    /** Duplicates the closure with a new bound object and class scope. */
+    public function bindTo(?object $newThis, object|string|null $newScope = "static"): ?Closure {}
+
+    /** Binds and calls the closure. */
+    public function call(object $newThis, mixed ...$args): mixed {}
+
+    /** Converts a callable into a closure. */
+    public static function fromCallable(callable $callback): Closure {}
+}
+
+/**
+ * Generator objects are returned from generators.
+ */
+final class Generator implements Iterator
+{
+    public function current(): mixed {}
+    public function next(): void {}
+    public function key(): mixed {}
+    public function valid(): bool {}
+    public function rewind(): void {}
+
+    /** Send a value to the generator. */
+    public function send(mixed $value): mixed {}
+
+    /** Get the return value of a generator. */
+    public function getReturn(): mixed {}
+}
+
+/**
+ * A generic empty class with dynamic properties.
+ */
+class stdClass {}
+
+/**
+ * Marks a class as an attribute.
+ */
+final class Attribute
+{
+    const TARGET_CLASS = 1;
+    const TARGET_FUNCTION = 2;
+    const TARGET_METHOD = 4;
+    const TARGET_PROPERTY = 8;
+    const TARGET_CLASS_CONSTANT = 16;
+    const TARGET_PARAMETER = 32;
+    const TARGET_ALL = 63;
+    const IS_REPEATABLE = 64;
+
+    public function __construct(int $flags = Attribute::TARGET_ALL) {}
+}
+
+/**
+ * Implemented by every enumeration.
+ */
+interface UnitEnum
+{
+    /** Generates a list of cases on an enum. */
+    public static function cases(): array;
+}
+
+/**
+ * Implemented by every enumeration backed by a scalar value.
+ */
+interface BackedEnum extends UnitEnum
+{
+    /** Maps a scalar to an enum instance. */
+    public static function from(int|string $value): static;
+
+    /** Maps a scalar to an enum instance or null. */
+    public static function tryFrom(int|string $value): ?static;
+}
+
+/**
+ * Get the length of a string in bytes.
+ */
+function strlen(string $string): int {}
+
+/**
+ * Returns an array comprising a function's argument list.
+ */
+function func_get_args(): array {}
+
+/**
+ * Return true if the given function has been defined.
+ */
+function function_exists(string $function): bool {}
+
+/**
+ * Checks if the class has been defined.
+ */
+function class_exists(string $class, bool $autoload = true): bool {}
+
+/**
+ * Checks if the interface has been defined.
+ */
+function interface_exists(string $interface, bool $autoload = true): bool {}
+
+/**
+ * Checks if the class method exists.
+ */
+function method_exists($object_or_class, string $method): bool {}
+
+/**
+ * Checks if the object or class has a property.
+ */
+function property_exists($object_or_class, string $property): bool {}
+
+/**
+ * Defines a named constant.
+ */
+function define(string $constant_name, mixed $value, bool $case_insensitive = false): bool {}
+
+/**
+ * Checks whether a given named constant exists.
+ */
+function defined(string $constant_name): bool {}
+
+/**
+ * Returns the name of the class of an object.
+ */
+function get_class(object $object): string {}
+
+/**
+ * Retrieves the parent class name for object or class.
+ */
+function get_parent_class(object|string $object_or_class): string|false {}
+
+/**
+ * Checks whether the object is of a given type or subtype.
+ */
+function is_a(mixed $object_or_class, string $class, bool $allow_string = false): bool {}
+
+/**
+ * Checks if the object has this class as one of its parents or implements it.
+ */
+function is_subclass_of(mixed $object_or_class, string $class, bool $allow_string = true): bool {}
+
+/**
+ * Generates a user-level error/warning/notice message.
+ */
+function trigger_error(string $message, int $error_level = E_USER_NOTICE): bool {}
+
+/**
+ * Sets which PHP errors are reported.
+ */
+function error_reporting(?int $error_level = null): int {}
+
+/**
+ * Sets a user-defined error handler function.
+ */
+function set_error_handler(?callable $callback, int $error_levels = E_ALL) {}
+
+/**
+ * Sets a user-defined exception handler function.
+ */
+function set_exception_handler(?callable $callback) {}
+
+const PHP_VERSION = "8.3.0";
+const PHP_MAJOR_VERSION = 8;
+const PHP_MINOR_VERSION = 3;
+const PHP_OS = "Linux";
+const PHP_OS_FAMILY = "Linux";
+const PHP_EOL = "\n";
+const PHP_INT_MAX = 9223372036854775807;
+const PHP_INT_MIN = -9223372036854775808;
+const PHP_INT_SIZE = 8;
+const PHP_FLOAT_EPSILON = 2.220446049250313E-16;
+const PHP_FLOAT_MAX = 1.7976931348623157E+308;
+const PHP_FLOAT_MIN = 2.2250738585072014E-308;
+const DIRECTORY_SEPARATOR = "/";
+const E_ERROR = 1;
+const E_WARNING = 2;
+const E_PARSE = 4;
+const E_NOTICE = 8;
+const E_USER_ERROR = 256;
+const E_USER_WARNING = 512;
+const E_USER_NOTICE = 1024;
+const E_DEPRECATED = 8192;
+const E_USER_DEPRECATED = 16384;
+const E_ALL = 32767;
@@ -0,0 +1,188 @@
+<?php
+
+// Classes, functions and constants of the date extension.
+
+/**
+ * Common interface of DateTime and DateTimeImmutable.
+ */
+interface DateTimeInterface
+{
+    const ATOM = "Y-m-d\TH:i:sP";
+    const ISO8601 = "Y-m-d\TH:i:sO";
+    const RFC3339 = "Y-m-d\TH:i:sP";
+    const RFC3339_EXTENDED = "Y-m-d\TH:i:s.vP";
+    const RFC7231 = "D, d M Y H:i:s \G\M\T";
+    const W3C = "Y-m-d\TH:i:sP";
+
+    /** Returns date formatted according to given format. */
+    public function format(string $format): string;
+
+    /** Gets the Unix timestamp. */
+    public function getTimestamp(): int;
+
+    /** Return time zone relative to given DateTime. */
+    public function getTimezone(): DateTimeZone|false;
+
+    /** Returns the difference between two DateTime objects. */
+    public function diff(DateTimeInterface $targetObject, bool $absolute = false): DateInterval;
+}
+
+/**
+ * Representation of date and time.
+ */
+class DateTime implements DateTimeInterface
+{
+    public function __construct(string $datetime = "now", ?DateTimeZone $timezone = null) {}
+
+    /** Parses a time string according to a specified format. */
+    public static function createFromFormat(string $format, string $datetime, ?DateTimeZone $timezone = null): DateTime|false {}
+
+    /** Returns new DateTime object encapsulating the given DateTimeImmutable object. */
+    public static function createFromImmutable(DateTimeImmutable $object): static {}
+
+    public function format(string $format): string {}
+    public function getTimestamp(): int {}
+    public function getTimezone(): DateTimeZone|false {}
+    public function diff(DateTimeInterface $targetObject, bool $absolute = false): DateInterval {}
+
+    /** Alters the timestamp. */
+    public function modify(string $modifier): DateTime|false {}
+
+    /** Adds an amount of days, months, years, hours, minutes and seconds. */
+    public function add(DateInterval $interval): DateTime {}
+
+    /** Subtracts an amount of days, months, years, hours, minutes and seconds. */
+    public function sub(DateInterval $interval): DateTime {}
+
+    /** Sets the date. */
+    public function setDate(int $year, int $month, int $day): DateTime {}
+
+    /** Sets the time. */
+    public function setTime(int $hour, int $minute, int $second = 0, int $microsecond = 0): DateTime {}
+
+    /** Sets the date and time based on a Unix timestamp. */
+    public function setTimestamp(int $timestamp): DateTime {}
+
+    /** Sets the time zone. */
+    public function setTimezone(DateTimeZone $timezone): DateTime {}
+}
+
+/**
+ * Immutable representation of date and time.
+ */
+class DateTimeImmutable implements DateTimeInterface
+{
+    public function __construct(string $datetime = "now", ?DateTimeZone $timezone = null) {}
+
+    /** Parses a time string according to a specified format. */
+    public static function createFromFormat(string $format, string $datetime, ?DateTimeZone $timezone = null): DateTimeImmutable|false {}
+
+    /** Returns new DateTimeImmutable object encapsulating the given DateTime object. */
+    public static function createFromMutable(DateTime $object): static {}
+
+    public function format(string $format): string {}
+    public function getTimestamp(): int {}
+    public function getTimezone(): DateTimeZone|false {}
+    public function diff(DateTimeInterface $targetObject, bool $absolute = false): DateInterval {}
+
+    /** Creates a new object with modified timestamp. */
+    public function modify(string $modifier): DateTimeImmutable|false {}
+
+    /** Returns a new object with the interval added. */
+    public function add(DateInterval $interval): DateTimeImmutable {}
+
+    /** Returns a new object with the interval subtracted. */
+    public function sub(DateInterval $interval): DateTimeImmutable {}
+
+    /** Sets the date. */
+    public function setDate(int $year, int $month, int $day): DateTimeImmutable {}
+
+    /** Sets the time. */
+    public function setTime(int $hour, int $minute, int $second = 0, int $microsecond = 0): DateTimeImmutable {}
+
+    /** Sets the date and time based on a Unix timestamp. */
+    public function setTimestamp(int $timestamp): DateTimeImmutable {}
+
+    /** Sets the time zone. */
+    public function setTimezone(DateTimeZone $timezone): DateTimeImmutable {}
+}
+
+/**
+ * Representation of time zone.
+ */
+class DateTimeZone
+{
+    const UTC = 1024;
+
+    public function __construct(string $timezone) {}
+
+    /** Returns the name of the timezone. */
+    public function getName(): string {}
+
+    /** Returns the timezone offset from GMT. */
+    public function getOffset(DateTimeInterface $datetime): int {}
+}
+
+/**
+ * Represents a date interval.
+ */
+class DateInterval
+{
+    public int $y;
+    public int $m;
+    public int $d;
+    public int $h;
+    public int $i;
+    public int $s;
+    public float $f;
+    public int $invert;
+    public mixed $days;
+
+    public function __construct(string $duration) {}
+
+    /** Formats the interval. */
+    public function format(string $format): string {}
+}
+
+/**
+ * Represents a date period.
+ */
+class DatePeriod implements IteratorAggregate
+{
+    const EXCLUDE_START_DATE = 1;
+    const INCLUDE_END_DATE = 2;
+
+    public function __construct(DateTimeInterface $start, DateInterval $interval, DateTimeInterface|int $end, int $options = 0) {}
+
+    public function getIterator(): Iterator {}
+}
+
+/**
+ * Format a Unix timestamp.
+ */
+function date(string $format, ?int $timestamp = null): string {}
+
+/**
+ * Parse about any English textual datetime description into a Unix timestamp.
+ */
+function strtotime(string $datetime, ?int $baseTimestamp = null): int|false {}
+
+/**
+ * Get Unix timestamp for a date.
+ */
+function mktime(int $hour, ?int $minute = null, ?int $second = null, ?int $month = null, ?int $day = null, ?int $year = null): int|false {}
+
+/**
+ * Validate a Gregorian date.
+ */
+function checkdate(int $month, int $day, int $year): bool {}
+
+/**
+ * Sets the default timezone used by all date/time functions in a script.
+ */
+function date_default_timezone_set(string $timezoneId): bool {}
+
+/**
+ * Gets the default timezone used by all date/time functions in a script.
+ */
+function date_default_timezone_get(): string {}
@@ -0,0 +1,59 @@
+<?php
+
+// Classes, functions and constants of the json extension.
+
+/**
+ * Objects implementing JsonSerializable can customize their JSON representation.
+ */
+interface JsonSerializable
+{
+    /** Specify data which should be serialized to JSON. */
+    public function jsonSerialize(): mixed;
+}
+
+/**
+ * Exception thrown when JSON_THROW_ON_ERROR is set and an error occurs.
+ */
+class JsonException extends Exception {}
+
+/**
+ * Returns the JSON representation of a value.
+ */
+function json_encode(mixed $value, int $flags = 0, int $depth = 512): string|false {}
+
+/**
+ * Decodes a JSON string.
+ */
+function json_decode(string $json, ?bool $associative = null, int $depth = 512, int $flags = 0): mixed {}
+
+/**
+ * Returns the last error occurred.
+ */
+function json_last_error(): int {}
+
+/**
+ * Returns the error string of the last json_encode() or json_decode() call.
+ */
+function json_last_error_msg(): string {}
+
+/**
+ * Checks if a string contains valid JSON.
+ */
+function json_validate(string $json, int $depth = 512, int $flags = 0): bool {}
+
+const JSON_HEX_TAG = 1;
+const JSON_HEX_AMP = 2;
+const JSON_HEX_APOS = 4;
+const JSON_HEX_QUOT = 8;
+const JSON_FORCE_OBJECT = 16;
+const JSON_NUMERIC_CHECK = 32;
+const JSON_UNESCAPED_SLASHES = 64;
+const JSON_PRETTY_PRINT = 128;
+const JSON_UNESCAPED_UNICODE = 256;
+const JSON_PRESERVE_ZERO_FRACTION = 1024;
+const JSON_OBJECT_AS_ARRAY = 1;
+const JSON_BIGINT_AS_STRING = 2;
+const JSON_THROW_ON_ERROR = 4194304;
+const JSON_ERROR_NONE = 0;
+const JSON_ERROR_DEPTH = 1;
+const JSON_ERROR_SYNTAX = 4;
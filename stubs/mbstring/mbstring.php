@@ -0,0 +1,73 @@
+<?php
+
+// Functions and constants of the mbstring extension.
+
+/**
+ * Get string length.
+ */
+function mb_strlen(string $string, ?string $encoding = null): int {}
+
+/**
+ * Get part of string.
+ */
+function mb_substr(string $string, int $start, ?int $length = null, ?string $encoding = null): string {}
+
+/**
+ * Make a string lowercase.
+ */
+function mb_strtolower(string $string, ?string $encoding = null): string {}
+
+/**
+ * Make a string uppercase.
+ */
+function mb_strtoupper(string $string, ?string $encoding = null): string {}
+
+/**
+ * Perform case folding on a string.
+ */
+function mb_convert_case(string $string, int $mode, ?string $encoding = null): string {}
+
+/**
+ * Find position of first occurrence of string in a string.
+ */
+function mb_strpos(string $haystack, string $needle, int $offset = 0, ?string $encoding = null): int|false {}
+
+/**
+ * Find position of last occurrence of a string in a string.
+ */
+function mb_strrpos(string $haystack, string $needle, int $offset = 0, ?string $encoding = null): int|false {}
+
+/**
+ * Given a multibyte string, return an array of its characters.
+ */
+function mb_str_split(string $string, int $length = 1, ?string $encoding = null): array {}
+
+/**
+ * Pad a multibyte string to a certain length with another multibyte string.
+ */
+function mb_str_pad(string $string, int $length, string $pad_string = " ", int $pad_type = STR_PAD_RIGHT, ?string $encoding = null): string {}
+
+/**
+ * Return width of string.
+ */
+function mb_strwidth(string $string, ?string $encoding = null): int {}
+
+/**
+ * Convert a string from one character encoding to another.
+ */
+function mb_convert_encoding(array|string $string, string $to_encoding, array|string|null $from_encoding = null): array|string|false {}
+
+/**
+ * Check if strings are valid for the specified encoding.
+ */
+function mb_check_encoding(array|string|null $value = null, ?string $encoding = null): bool {}
+
+/**
+ * Set/Get internal character encoding.
+ */
+function mb_internal_encoding(?string $encoding = null): string|bool {}
+
+const MB_CASE_UPPER = 0;
+const MB_CASE_LOWER = 1;
+const MB_CASE_TITLE = 2;
+const MB_CASE_FOLD = 3;
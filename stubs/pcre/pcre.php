@@ -0,0 +1,51 @@
+<?php
+
+// Functions and constants of the pcre extension.
+
+/**
+ * Perform a regular expression match.
+ */
+function preg_match(string $pattern, string $subject, &$matches = null, int $flags = 0, int $offset = 0): int|false {}
+
+/**
+ * Perform a global regular expression match.
+ */
+function preg_match_all(string $pattern, string $subject, &$matches = null, int $flags = 0, int $offset = 0): int|false {}
+
+/**
+ * Perform a regular expression search and replace.
+ */
+function preg_replace(string|array $pattern, string|array $replacement, string|array $subject, int $limit = -1, &$count = null): string|array|null {}
+
+/**
+ * Perform a regular expression search and replace using a callback.
+ */
+function preg_replace_callback(string|array $pattern, callable $callback, string|array $subject, int $limit = -1, &$count = null, int $flags = 0): string|array|null {}
+
+/**
+ * Split string by a regular expression.
+ */
+function preg_split(string $pattern, string $subject, int $limit = -1, int $flags = 0): array|false {}
+
+/**
+ * Quote regular expression characters.
+ */
+function preg_quote(string $str, ?string $delimiter = null): string {}
+
+/**
+ * Return array entries that match the pattern.
+ */
+function preg_grep(string $pattern, array $array, int $flags = 0): array|false {}
+
+/**
+ * Returns the error code of the last PCRE regex execution.
+ */
+function preg_last_error(): int {}
+
+const PREG_PATTERN_ORDER = 1;
+const PREG_SET_ORDER = 2;
+const PREG_OFFSET_CAPTURE = 256;
+const PREG_UNMATCHED_AS_NULL = 512;
+const PREG_SPLIT_NO_EMPTY = 1;
+const PREG_SPLIT_DELIM_CAPTURE = 2;
+const PREG_SPLIT_OFFSET_CAPTURE = 4;
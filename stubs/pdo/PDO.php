@@ -0,0 +1,114 @@
+<?php
+
+// Classes of the PDO extension.
+
+/**
+ * Represents an error raised by PDO.
+ */
+class PDOException extends RuntimeException
+{
+    public ?array $errorInfo = null;
+}
+
+/**
+ * Represents a connection between PHP and a database server.
+ */
+class PDO
+{
+    const PARAM_NULL = 0;
+    const PARAM_INT = 1;
+    const PARAM_STR = 2;
+    const PARAM_LOB = 3;
+    const PARAM_BOOL = 5;
+    const FETCH_DEFAULT = 0;
+    const FETCH_ASSOC = 2;
+    const FETCH_NUM = 3;
+    const FETCH_BOTH = 4;
+    const FETCH_OBJ = 5;
+    const FETCH_COLUMN = 7;
+    const FETCH_CLASS = 8;
+    const FETCH_ORI_NEXT = 0;
+    const ATTR_ERRMODE = 3;
+    const ATTR_DEFAULT_FETCH_MODE = 19;
+    const ATTR_EMULATE_PREPARES = 20;
+    const ERRMODE_SILENT = 0;
+    const ERRMODE_WARNING = 1;
+    const ERRMODE_EXCEPTION = 2;
+
+    /** Creates a PDO instance representing a connection to a database. */
+    public function __construct(string $dsn, ?string $username = null, ?string $password = null, ?array $options = null) {}
+
+    /** Prepares a statement for execution and returns a statement object. */
+    public function prepare(string $query, array $options = []): PDOStatement|false {}
+
+    /** Prepares and executes an SQL statement without placeholders. */
+    public function query(string $query, ?int $fetchMode = null, mixed ...$fetchModeArgs): PDOStatement|false {}
+
+    /** Execute an SQL statement and return the number of affected rows. */
+    public function exec(string $statement): int|false {}
+
+    /** Initiates a transaction. */
+    public function beginTransaction(): bool {}
+
+    /** Commits a transaction. */
+    public function commit(): bool {}
+
+    /** Rolls back a transaction. */
+    public function rollBack(): bool {}
+
+    /** Checks if inside a transaction. */
+    public function inTransaction(): bool {}
+
+    /** Returns the ID of the last inserted row or sequence value. */
+    public function lastInsertId(?string $name = null): string|false {}
+
+    /** Quotes a string for use in a query. */
+    public function quote(string $string, int $type = PDO::PARAM_STR): string|false {}
+
+    /** Set an attribute. */
+    public function setAttribute(int $attribute, mixed $value): bool {}
+
+    /** Retrieve a database connection attribute. */
+    public function getAttribute(int $attribute): mixed {}
+
+    /** Fetch extended error information associated with the last operation. */
+    public function errorInfo(): array {}
+}
+
+/**
+ * Represents a prepared statement and, after the statement is executed, an
+ * associated result set.
+ */
+class PDOStatement implements IteratorAggregate
+{
+    public string $queryString;
+
+    /** Executes a prepared statement. */
+    public function execute(?array $params = null): bool {}
+
+    /** Fetches the next row from a result set. */
+    public function fetch(int $mode = PDO::FETCH_DEFAULT, int $cursorOrientation = PDO::FETCH_ORI_NEXT, int $cursorOffset = 0): mixed {}
+
+    /** Fetches the remaining rows from a result set. */
+    public function fetchAll(int $mode = PDO::FETCH_DEFAULT, mixed ...$args): array {}
+
+    /** Returns a single column from the next row of a result set. */
+    public function fetchColumn(int $column = 0): mixed {}
+
+    /** Fetches the next row and returns it as an object. */
+    public function fetchObject(?string $class = "stdClass", array $constructorArgs = []): object|false {}
+
+    /** Binds a value to a parameter. */
+    public function bindValue(int|string $param, mixed $value, int $type = PDO::PARAM_STR): bool {}
+
+    /** Binds a parameter to the specified variable name. */
+    public function bindParam(int|string $param, mixed &$var, int $type = PDO::PARAM_STR, int $maxLength = 0, mixed $driverOptions = null): bool {}
+
+    /** Returns the number of rows affected by the last SQL statement. */
+    public function rowCount(): int {}
+
+    /** Closes the cursor, enabling the statement to be executed again. */
+    public function closeCursor(): bool {}
+
+    public function getIterator(): Iterator {}
+}
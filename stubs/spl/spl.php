@@ -0,0 +1,179 @@
+<?php
+
+// Classes, interfaces and functions of the Standard PHP Library.
+
+class LogicException extends Exception {}
+
+class BadFunctionCallException extends LogicException {}
+
+class BadMethodCallException extends BadFunctionCallException {}
+
+class DomainException extends LogicException {}
+
+class InvalidArgumentException extends LogicException {}
+
+class LengthException extends LogicException {}
+
+class OutOfRangeException extends LogicException {}
+
+class RuntimeException extends Exception {}
+
+class OutOfBoundsException extends RuntimeException {}
+
+class OverflowException extends RuntimeException {}
+
+class RangeException extends RuntimeException {}
+
+class UnderflowException extends RuntimeException {}
+
+class UnexpectedValueException extends RuntimeException {}
+
+/**
+ * Allows objects to work as arrays.
+ */
+class ArrayObject implements IteratorAggregate, ArrayAccess, Countable
+{
+    const STD_PROP_LIST = 1;
+    const ARRAY_AS_PROPS = 2;
+
+    public function __construct(object|array $array = [], int $flags = 0, string $iteratorClass = ArrayIterator::class) {}
+    public function offsetExists(mixed $key): bool {}
+    public function offsetGet(mixed $key): mixed {}
+    public function offsetSet(mixed $key, mixed $value): void {}
+    public function offsetUnset(mixed $key): void {}
+
+    /** Appends the value. */
+    public function append(mixed $value): void {}
+
+    /** Creates a copy of the ArrayObject. */
+    public function getArrayCopy(): array {}
+
+    public function count(): int {}
+    public function getIterator(): Iterator {}
+}
+
+/**
+ * Allows to unset and modify values and keys while iterating over arrays and
+ * objects.
+ */
+class ArrayIterator implements Iterator, ArrayAccess, Countable
+{
+    public function __construct(object|array $array = [], int $flags = 0) {}
+    public function offsetExists(mixed $key): bool {}
+    public function offsetGet(mixed $key): mixed {}
+    public function offsetSet(mixed $key, mixed $value): void {}
+    public function offsetUnset(mixed $key): void {}
+    public function getArrayCopy(): array {}
+    public function count(): int {}
+    public function current(): mixed {}
+    public function next(): void {}
+    public function key(): mixed {}
+    public function valid(): bool {}
+    public function rewind(): void {}
+}
+
+/**
+ * Provides a map from objects to data or an object set.
+ */
+class SplObjectStorage implements Countable, Iterator, ArrayAccess
+{
+    /** Adds an object in the storage. */
+    public function attach(object $object, mixed $info = null): void {}
+
+    /** Removes an object from the storage. */
+    public function detach(object $object): void {}
+
+    /** Checks if the storage contains a specific object. */
+    public function contains(object $object): bool {}
+
+    public function offsetExists($object): bool {}
+    public function offsetGet($object): mixed {}
+    public function offsetSet($object, mixed $info = null): void {}
+    public function offsetUnset($object): void {}
+    public function count(int $mode = COUNT_NORMAL): int {}
+    public function current(): object {}
+    public function next(): void {}
+    public function key(): int {}
+    public function valid(): bool {}
+    public function rewind(): void {}
+}
+
+/**
+ * Provides the main functionalities of a doubly linked list.
+ */
+class SplDoublyLinkedList implements Iterator, Countable, ArrayAccess
+{
+    /** Pushes an element at the end of the list. */
+    public function push(mixed $value): void {}
+
+    /** Pops a node from the end of the list. */
+    public function pop(): mixed {}
+
+    /** Shifts a node from the beginning of the list. */
+    public function shift(): mixed {}
+
+    /** Checks whether the list is empty. */
+    public function isEmpty(): bool {}
+
+    public function offsetExists($index): bool {}
+    public function offsetGet($index): mixed {}
+    public function offsetSet($index, mixed $value): void {}
+    public function offsetUnset($index): void {}
+    public function count(): int {}
+    public function current(): mixed {}
+    public function next(): void {}
+    public function key(): int {}
+    public function valid(): bool {}
+    public function rewind(): void {}
+}
+
+/**
+ * A stack implemented using a doubly linked list.
+ */
+class SplStack extends SplDoublyLinkedList
+{
+    /** Peeks at the node from the top of the stack. */
+    public function top(): mixed {}
+}
+
+/**
+ * A queue implemented using a doubly linked list.
+ */
+class SplQueue extends SplDoublyLinkedList
+{
+    /** Adds an element to the queue. */
+    public function enqueue(mixed $value): void {}
+
+    /** Dequeues a node from the queue. */
+    public function dequeue(): mixed {}
+}
+
+/**
+ * Register given function as __autoload() implementation.
+ */
+function spl_autoload_register(?callable $callback = null, bool $throw = true, bool $prepend = false): bool {}
+
+/**
+ * Return hash id for given object.
+ */
+function spl_object_hash(object $object): string {}
+
+/**
+ * Return the integer object handle for given object.
+ */
+function spl_object_id(object $object): int {}
+
+/**
+ * Copy the iterator into an array.
+ */
+function iterator_to_array(Traversable|array $iterator, bool $preserve_keys = true): array {}
+
+/**
+ * Count the elements in an iterator.
+ */
+function iterator_count(Traversable|array $iterator): int {}
+
+/**
+ * Return the interfaces which are implemented by the given class or interface.
+ */
+function class_implements($object_or_class, bool $autoload = true): array|false {}
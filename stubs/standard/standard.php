@@ -0,0 +1,433 @@
+<?php
+
+// Functions and constants of the standard extension.
+
+/**
+ * Applies the callback to the elements of the given arrays.
+ */
+function array_map(?callable $callback, array $array, array ...$arrays): array {}
+
+/**
+ * Filters elements of an array using a callback function.
+ */
+function array_filter(array $array, ?callable $callback = null, int $mode = 0): array {}
+
+/**
+ * Iteratively reduce the array to a single value using a callback function.
+ */
+function array_reduce(array $array, callable $callback, mixed $initial = null): mixed {}
+
+/**
+ * Return all the keys or a subset of the keys of an array.
+ */
+function array_keys(array $array, mixed $filter_value = null, bool $strict = false): array {}
+
+/**
+ * Return all the values of an array.
+ */
+function array_values(array $array): array {}
+
+/**
+ * Merge one or more arrays.
+ */
+function array_merge(array ...$arrays): array {}
+
+/**
+ * Checks if the given key or index exists in the array.
+ */
+function array_key_exists($key, array $array): bool {}
+
+/**
+ * Gets the first key of an array.
+ */
+function array_key_first(array $array): int|string|null {}
+
+/**
+ * Gets the last key of an array.
+ */
+function array_key_last(array $array): int|string|null {}
+
+/**
+ * Searches the array for a given value and returns the first corresponding key.
+ */
+function array_search(mixed $needle, array $haystack, bool $strict = false): int|string|false {}
+
+/**
+ * Return the values from a single column in the input array.
+ */
+function array_column(array $array, int|string|null $column_key, int|string|null $index_key = null): array {}
+
+/**
+ * Creates an array by using one array for keys and another for its values.
+ */
+function array_combine(array $keys, array $values): array {}
+
+/**
+ * Removes duplicate values from an array.
+ */
+function array_unique(array $array, int $flags = SORT_STRING): array {}
+
+/**
+ * Extract a slice of the array.
+ */
+function array_slice(array $array, int $offset, ?int $length = null, bool $preserve_keys = false): array {}
+
+/**
+ * Remove a portion of the array and replace it with something else.
+ */
+function array_splice(array &$array, int $offset, ?int $length = null, mixed $replacement = []): array {}
+
+/**
+ * Push one or more elements onto the end of array.
+ */
+function array_push(array &$array, mixed ...$values): int {}
+
+/**
+ * Pop the element off the end of array.
+ */
+function array_pop(array &$array): mixed {}
+
+/**
+ * Shift an element off the beginning of array.
+ */
+function array_shift(array &$array): mixed {}
+
+/**
+ * Prepend one or more elements to the beginning of an array.
+ */
+function array_unshift(array &$array, mixed ...$values): int {}
+
+/**
+ * Exchanges all keys with their associated values in an array.
+ */
+function array_flip(array $array): array {}
+
+/**
+ * Return an array with elements in reverse order.
+ */
+function array_reverse(array $array, bool $preserve_keys = false): array {}
+
+/**
+ * Checks if a value exists in an array.
+ */
+function in_array(mixed $needle, array $haystack, bool $strict = false): bool {}
+
+/**
+ * Counts all elements in an array or in a Countable object.
+ */
+function count(Countable|array $value, int $mode = COUNT_NORMAL): int {}
+
+/**
+ * Sort an array in ascending order.
+ */
+function sort(array &$array, int $flags = SORT_REGULAR): bool {}
+
+/**
+ * Sort an array by values using a user-defined comparison function.
+ */
+function usort(array &$array, callable $callback): bool {}
+
+/**
+ * Sort an array by key in ascending order.
+ */
+function ksort(array &$array, int $flags = SORT_REGULAR): bool {}
+
+/**
+ * Create an array containing a range of elements.
+ */
+function range($start, $end, int|float $step = 1): array {}
+
+/**
+ * Join array elements with a string.
+ */
+function implode(array|string $separator, ?array $array = null): string {}
+
+/**
+ * Split a string by a string.
+ */
+function explode(string $separator, string $string, int $limit = PHP_INT_MAX): array {}
+
+/**
+ * Strip whitespace (or other characters) from the beginning and end of a string.
+ */
+function trim(string $string, string $characters = " \n\r\t\v\x00"): string {}
+
+/**
+ * Strip whitespace (or other characters) from the beginning of a string.
+ */
+function ltrim(string $string, string $characters = " \n\r\t\v\x00"): string {}
+
+/**
+ * Strip whitespace (or other characters) from the end of a string.
+ */
+function rtrim(string $string, string $characters = " \n\r\t\v\x00"): string {}
+
+/**
+ * Make a string lowercase.
+ */
+function strtolower(string $string): string {}
+
+/**
+ * Make a string uppercase.
+ */
+function strtoupper(string $string): string {}
+
+/**
+ * Make a string's first character uppercase.
+ */
+function ucfirst(string $string): string {}
+
+/**
+ * Find the position of the first occurrence of a substring in a string.
+ */
+function strpos(string $haystack, string $needle, int $offset = 0): int|false {}
+
+/**
+ * Determine if a string contains a given substring.
+ */
+function str_contains(string $haystack, string $needle): bool {}
+
+/**
+ * Checks if a string starts with a given substring.
+ */
+function str_starts_with(string $haystack, string $needle): bool {}
+
+/**
+ * Checks if a string ends with a given substring.
+ */
+function str_ends_with(string $haystack, string $needle): bool {}
+
+/**
+ * Replace all occurrences of the search string with the replacement string.
+ */
+function str_replace(array|string $search, array|string $replace, string|array $subject, &$count = null): string|array {}
+
+/**
+ * Repeat a string.
+ */
+function str_repeat(string $string, int $times): string {}
+
+/**
+ * Pad a string to a certain length with another string.
+ */
+function str_pad(string $string, int $length, string $pad_string = " ", int $pad_type = STR_PAD_RIGHT): string {}
+
+/**
+ * Return part of a string.
+ */
+function substr(string $string, int $offset, ?int $length = null): string {}
+
+/**
+ * Return a formatted string.
+ */
+function sprintf(string $format, mixed ...$values): string {}
+
+/**
+ * Output a formatted string.
+ */
+function printf(string $format, mixed ...$values): int {}
+
+/**
+ * Format a number with grouped thousands.
+ */
+function number_format(float $num, int $decimals = 0, ?string $decimal_separator = ".", ?string $thousands_separator = ","): string {}
+
+/**
+ * Convert special characters to HTML entities.
+ */
+function htmlspecialchars(string $string, int $flags = ENT_QUOTES | ENT_SUBSTITUTE | ENT_HTML401, ?string $encoding = null, bool $double_encode = true): string {}
+
+/**
+ * Absolute value.
+ */
+function abs(int|float $num): int|float {}
+
+/**
+ * Round fractions up.
+ */
+function ceil(int|float $num): float {}
+
+/**
+ * Round fractions down.
+ */
+function floor(int|float $num): float {}
+
+/**
+ * Rounds a float.
+ */
+function round(int|float $num, int $precision = 0, int $mode = PHP_ROUND_HALF_UP): float {}
+
+/**
+ * Find highest value.
+ */
+function max(mixed $value, mixed ...$values): mixed {}
+
+/**
+ * Find lowest value.
+ */
+function min(mixed $value, mixed ...$values): mixed {}
+
+/**
+ * Get a cryptographically secure, uniformly selected integer.
+ */
+function random_int(int $min, int $max): int {}
+
+/**
+ * Get the integer value of a variable.
+ */
+function intval(mixed $value, int $base = 10): int {}
+
+/**
+ * Finds whether a variable is an array.
+ */
+function is_array(mixed $value): bool {}
+
+/**
+ * Find whether the type of a variable is string.
+ */
+function is_string(mixed $value): bool {}
+
+/**
+ * Find whether the type of a variable is integer.
+ */
+function is_int(mixed $value): bool {}
+
+/**
+ * Finds whether a variable is a number or a numeric string.
+ */
+function is_numeric(mixed $value): bool {}
+
+/**
+ * Finds whether a variable is null.
+ */
+function is_null(mixed $value): bool {}
+
+/**
+ * Verify that a value can be called as a function.
+ */
+function is_callable(mixed $value, bool $syntax_only = false, &$callable_name = null): bool {}
+
+/**
+ * Dumps information about a variable.
+ */
+function var_dump(mixed $value, mixed ...$values): void {}
+
+/**
+ * Outputs or returns a parsable string representation of a variable.
+ */
+function var_export(mixed $value, bool $return = false): ?string {}
+
+/**
+ * Prints human-readable information about a variable.
+ */
+function print_r(mixed $value, bool $return = false): string|bool {}
+
+/**
+ * Generates a storable representation of a value.
+ */
+function serialize(mixed $value): string {}
+
+/**
+ * Creates a PHP value from a stored representation.
+ */
+function unserialize(string $data, array $options = []): mixed {}
+
+/**
+ * Reads entire file into a string.
+ */
+function file_get_contents(string $filename, bool $use_include_path = false, $context = null, int $offset = 0, ?int $length = null): string|false {}
+
+/**
+ * Write data to a file.
+ */
+function file_put_contents(string $filename, mixed $data, int $flags = 0, $context = null): int|false {}
+
+/**
+ * Checks whether a file or directory exists.
+ */
+function file_exists(string $filename): bool {}
+
+/**
+ * Tells whether the filename is a directory.
+ */
+function is_dir(string $filename): bool {}
+
+/**
+ * Returns trailing name component of path.
+ */
+function basename(string $path, string $suffix = ""): string {}
+
+/**
+ * Returns a parent directory's path.
+ */
+function dirname(string $path, int $levels = 1): string {}
+
+/**
+ * Return current Unix timestamp.
+ */
+function time(): int {}
+
+/**
+ * Return current Unix timestamp with microseconds.
+ */
+function microtime(bool $as_float = false): string|float {}
+
+/**
+ * Delay execution.
+ */
+function sleep(int $seconds): int {}
+
+/**
+ * Gets the value of an environment variable.
+ */
+function getenv(?string $name = null, bool $local_only = false): array|string|false {}
+
+/**
+ * Creates a password hash.
+ */
+function password_hash(string $password, string|int|null $algo, array $options = []): string {}
+
+/**
+ * Verifies that a password matches a hash.
+ */
+function password_verify(string $password, string $hash): bool {}
+
+/**
+ * Calculate the md5 hash of a string.
+ */
+function md5(string $string, bool $binary = false): string {}
+
+/**
+ * Encodes data with MIME base64.
+ */
+function base64_encode(string $string): string {}
+
+/**
+ * Decodes data encoded with MIME base64.
+ */
+function base64_decode(string $string, bool $strict = false): string|false {}
+
+/**
+ * Call the callback given by the first parameter.
+ */
+function call_user_func(callable $callback, mixed ...$args): mixed {}
+
+/**
+ * Call a callback with an array of parameters.
+ */
+function call_user_func_array(callable $callback, array $args): mixed {}
+
+const COUNT_NORMAL = 0;
+const COUNT_RECURSIVE = 1;
+const SORT_REGULAR = 0;
+const SORT_NUMERIC = 1;
+const SORT_STRING = 2;
+const STR_PAD_LEFT = 0;
+const STR_PAD_RIGHT = 1;
+const STR_PAD_BOTH = 2;
+const ENT_QUOTES = 3;
+const ENT_SUBSTITUTE = 8;
+const ENT_HTML401 = 0;
+const PHP_ROUND_HALF_UP = 1;
+const PASSWORD_DEFAULT = "2y";
+const PASSWORD_BCRYPT = "2y";
@@ -0,0 +1,253 @@
+use tower_lsp::lsp_types::Url;
+use tree_sitter::Point;
+
+use phppp::analyzer::{Type, TypeCache};
+use phppp::indexer::{self, FileSymbols, GlobalIndex, SymbolKind};
+use phppp::parser::{self, Ast};
+use phppp::resolver::{self, DocumentContext, ImportTable};
+
+struct Doc {
+    uri: Url,
+    src: String,
+    ast: Ast,
+    symbols: FileSymbols,
+    imports: ImportTable,
+}
+
+impl Doc {
+    fn open(uri: &str, src: &str, index: &GlobalIndex) -> Self {
+        let uri = Url::parse(uri).unwrap();
+        let ast = parser::parse_php(src);
+        let symbols = indexer::extract_symbols(src, &ast, &uri);
+        index.insert(uri.clone(), symbols.clone());
+        let imports = ImportTable::build(src, &ast);
+        Doc {
+            uri,
+            src: src.to_string(),
+            ast,
+            symbols,
+            imports,
+        }
+    }
+
+    fn ctx(&self) -> DocumentContext<'_> {
+        DocumentContext {
+            uri: &self.uri,
+            src: &self.src,
+            ast: &self.ast,
+            symbols: &self.symbols,
+            imports: &self.imports,
+        }
+    }
+
+    /// The type of the variable or expression whose name is at `row:column`.
+    fn type_at(&self, row: usize, column: usize, types: &TypeCache, index: &GlobalIndex) -> String {
+        let ctx = self.ctx();
+        let node = resolver::name_node_at(&self.ast, Point { row, column }).unwrap();
+        types.type_of(&ctx, node, index).to_string()
+    }
+
+    /// The member the name at `row:column` resolves to through the type of
+    /// its object.
+    fn member_at(
+        &self,
+        row: usize,
+        column: usize,
+        types: &TypeCache,
+        index: &GlobalIndex,
+    ) -> Option<String> {
+        let ctx = self.ctx();
+        let node = resolver::name_node_at(&self.ast, Point { row, column }).unwrap();
+        types.resolve_member(&ctx, node, index).map(|r| r.name)
+    }
+}
+
+const USER: &str = r#"<?php
+namespace App;
+class Avatar { public function url(): string {} }
+class Profile { public function avatar(): Avatar {} }
+class User {
+    public ?Profile $profile;
+    public function profile(): Profile {}
+    public static function find(int $id): static {}
+}
+"#;
+
+#[test]
+fn parses_declared_types() {
+    assert_eq!(Type::parse("?App\\User").to_string(), "null|App\\User");
+    let ty = Type::parse("App\\A|(App\\B&App\\C)|int");
+    assert_eq!(
+        ty.classes().collect::<Vec<_>>(),
+        ["App\\A", "App\\B", "App\\C"]
+    );
+    assert!(Type::parse("").is_empty());
+}
+
+#[test]
+fn infers_new_parameters_and_assignments() {
+    let index = indexer::new_index();
+    Doc::open("file:///user.php", USER, &index);
+    let doc = Doc::open(
+        "file:///a.php",
+        r#"<?php
+use App\User;
+function show(User $user, $id) {
+    $copy = $user;
+    $fresh = new User();
+    $found = User::find($id);
+    $name = 'x';
+    $name = $fresh ?? null;
+}
+"#,
+        &index,
+    );
+    let types = TypeCache::new();
+    assert_eq!(doc.type_at(3, 5, &types, &index), "App\\User");
+    assert_eq!(doc.type_at(4, 5, &types, &index), "App\\User");
+    assert_eq!(doc.type_at(5, 5, &types, &index), "App\\User");
+    assert_eq!(doc.type_at(2, 27, &types, &index), "mixed");
+    assert_eq!(doc.type_at(7, 5, &types, &index), "App\\User|null");
+}
+
+#[test]
+fn follows_property_and_return_types() {
+    let index = indexer::new_index();
+    Doc::open("file:///user.php", USER, &index);
+    let doc = Doc::open(
+        "file:///a.php",
+        r#"<?php
+use App\User;
+$user = new User();
+$user->profile()->avatar()->url();
+$user->profile->avatar();
+"#,
+        &index,
+    );
+    let types = TypeCache::new();
+    assert_eq!(
+        doc.member_at(3, 20, &types, &index).as_deref(),
+        Some("App\\Profile::avatar")
+    );
+    assert_eq!(
+        doc.member_at(3, 30, &types, &index).as_deref(),
+        Some("App\\Avatar::url")
+    );
+    assert_eq!(
+        doc.member_at(4, 8, &types, &index).as_deref(),
+        Some("App\\User::$profile")
+    );
+    assert_eq!(
+        doc.member_at(4, 17, &types, &index).as_deref(),
+        Some("App\\Profile::avatar")
+    );
+}
+
+#[test]
+fn narrows_through_instanceof() {
+    let index = indexer::new_index();
+    Doc::open("file:///user.php", USER, &index);
+    let doc = Doc::open(
+        "file:///a.php",
+        r#"<?php
+use App\Profile;
+function f($value) {
+    if ($value instanceof Profile && true) {
+        $value->avatar();
+    }
+    $value->avatar();
+}
+"#,
+        &index,
+    );
+    let types = TypeCache::new();
+    assert_eq!(doc.type_at(4, 9, &types, &index), "App\\Profile");
+    assert_eq!(doc.type_at(6, 5, &types, &index), "mixed");
+}
+
+#[test]
+fn cached_types_follow_dependency_changes() {
+    let index = indexer::new_index();
+    Doc::open("file:///user.php", USER, &index);
+    let doc = Doc::open(
+        "file:///a.php",
+        "<?php\n$p = (new \\App\\User())->profile();\n$p;\n",
+        &index,
+    );
+    let types = TypeCache::new();
+    assert_eq!(doc.type_at(2, 1, &types, &index), "App\\Profile");
+
+    Doc::open(
+        "file:///user.php",
+        &USER.replace("profile(): Profile", "profile(): Avatar"),
+        &index,
+    );
+    assert_eq!(doc.type_at(2, 1, &types, &index), "App\\Avatar");
+}
+
+#[test]
+fn lists_inherited_members() {
+    let index = indexer::new_index();
+    let doc = Doc::open(
+        "file:///a.php",
+        r#"<?php
+class Base { const LIMIT = 1; public function save() {} }
+class Post extends Base { public $title; }
+$post = new Post();
+"#,
+        &index,
+    );
+    let types = TypeCache::new();
+    let ctx = doc.ctx();
+    let node = resolver::name_node_at(&doc.ast, Point { row: 3, column: 1 }).unwrap();
+    let members: Vec<(String, SymbolKind)> = types
+        .members(&ctx, node, &index)
+        .into_iter()
+        .map(|(name, sym)| (name, sym.kind))
+        .collect();
+    assert_eq!(
+        members,
+        vec![
+            ("$title".to_string(), SymbolKind::Property),
+            ("LIMIT".to_string(), SymbolKind::ClassConstant),
+            ("save".to_string(), SymbolKind::Method),
+        ]
+    );
+}
+
+#[test]
+fn reads_types_from_docblocks() {
+    let index = indexer::new_index();
+    Doc::open("file:///user.php", USER, &index);
+    let doc = Doc::open(
+        "file:///a.php",
+        r#"<?php
+use App\User;
+use App\Profile;
+/**
+ * @template T of Profile
+ * @param User|null $user
+ * @param T $profile
+ * @return User[]
+ */
+function load($user, $profile) {
+    /** @var Profile $found */
+    $found = find();
+    /** @var User $current */
+    $current->profile;
+    $user->profile();
+    $profile->avatar();
+}
+"#,
+        &index,
+    );
+    let types = TypeCache::new();
+    assert_eq!(doc.type_at(11, 5, &types, &index), "App\\Profile");
+    assert_eq!(doc.type_at(13, 5, &types, &index), "App\\User");
+    assert_eq!(doc.type_at(14, 5, &types, &index), "App\\User|null");
+    assert_eq!(doc.type_at(15, 5, &types, &index), "App\\Profile");
+    assert_eq!(
+        index.definitions("load")[0].1.type_hint.as_deref(),
+        Some("array")
+    );
+}
@@ -0,0 +1,132 @@
+use std::collections::HashMap;
+
+use tower_lsp::lsp_types::Url;
+
+use phppp::cache::{self, CachedFile, FileStamp, IndexCache};
+use phppp::indexer::{self, Layer};
+
+fn has(index: &indexer::WorkspaceIndex, fqn: &str) -> bool {
+    index.iter().any(|e| e.value().contains_key(fqn))
+}
+
+#[test]
+fn cache_round_trips_through_disk() {
+    let dir = tempfile::tempdir().unwrap();
+    let file = dir.path().join("A.php");
+    std::fs::write(
+        &file,
+        "<?php\nnamespace App;\nclass A { public function run() {} }\n",
+    )
+    .unwrap();
+
+    let index = indexer::new_index();
+    let files = vec![(file.clone(), Layer::Project)];
+    let indexed = indexer::index_files_cached(&files, &index, &IndexCache::default(), |_| {});
+    let stamp = indexed[0].1.stamp;
+    let path = dir.path().join("cache/index.json");
+    IndexCache::from_files(indexed).save(&path).unwrap();
+
+    let loaded = IndexCache::load(&path);
+    assert_eq!(loaded.len(), 1);
+    let cached = loaded.get(&file).unwrap();
+    assert_eq!(cached.stamp, stamp);
+    assert!(cached.symbols.contains_key("App\\A::run"));
+}
+
+#[test]
+fn unchanged_files_come_from_cache() {
+    let dir = tempfile::tempdir().unwrap();
+    let file = dir.path().join("A.php");
+    let src = "<?php\nclass Real {}\n";
+    std::fs::write(&file, src).unwrap();
+
+    // seed the cache with symbols the file does not contain, so a cache hit
+    // is observable
+    let uri = Url::from_file_path(&file).unwrap();
+    let mut fake = HashMap::new();
+    let real = indexer::new_index();
+    indexer::index_file(&file, &real).unwrap();
+    let mut sym = real.get(&uri).unwrap()["Real"][0].clone();
+    sym.name = "Fake".into();
+    fake.insert("Fake".to_string(), vec![sym]);
+    let mut stamp = FileStamp::of(&file, Layer::Project).unwrap();
+    stamp.hash = cache::content_hash(src.as_bytes());
+    let seeded = |stamp| {
+        IndexCache::from_files([(
+            file.clone(),
+            CachedFile {
+                stamp,
+                symbols: fake.clone(),
+            },
+        )])
+    };
+    let cached = seeded(stamp);
+
+    let files = vec![(file.clone(), Layer::Project)];
+    let index = indexer::new_index();
+    indexer::index_files_cached(&files, &index, &cached, |_| {});
+    assert!(has(&index, "Fake"));
+
+    // same content with a new mtime is still a hit through the hash
+    let mut touched = stamp;
+    touched.mtime += 1;
+    let cached = seeded(touched);
+    let index = indexer::new_index();
+    indexer::index_files_cached(&files, &index, &cached, |_| {});
+    assert!(has(&index, "Fake"));
+
+    // changed content is parsed again
+    std::fs::write(&file, "<?php\nclass Changed {}\n").unwrap();
+    let index = indexer::new_index();
+    indexer::index_files_cached(&files, &index, &cached, |_| {});
+    assert!(has(&index, "Changed"));
+    assert!(!has(&index, "Fake"));
+}
+
+#[test]
+fn cache_holds_the_symbols_read_from_disk() {
+    let dir = tempfile::tempdir().unwrap();
+    let file = dir.path().join("A.php");
+    std::fs::write(&file, "<?php\nclass OnDisk {}\n").unwrap();
+    let index = indexer::new_index();
+    let files = vec![(file.clone(), Layer::Project)];
+    let indexed = indexer::index_files_cached(&files, &index, &IndexCache::default(), |_| {});
+
+    // an editor buffer replaces the file's symbols before the cache is
+    // written
+    let uri = Url::from_file_path(&file).unwrap();
+    let src = "<?php\nclass Unsaved {}\n";
+    let ast = phppp::parser::parse_php(src);
+    index.insert(uri.clone(), indexer::extract_symbols(src, &ast, &uri));
+
+    let cache = IndexCache::from_files(indexed);
+    let cached = cache.get(&file).unwrap();
+    assert!(cached.symbols.contains_key("OnDisk"));
+    assert!(!cached.symbols.contains_key("Unsaved"));
+}
+
+#[test]
+fn outdated_cache_is_discarded() {
+    let dir = tempfile::tempdir().unwrap();
+    let file = dir.path().join("A.php");
+    std::fs::write(&file, "<?php\nclass A {}\n").unwrap();
+    let index = indexer::new_index();
+    let files = vec![(file, Layer::Project)];
+    let indexed = indexer::index_files_cached(&files, &index, &IndexCache::default(), |_| {});
+    let path = dir.path().join("index.json");
+    IndexCache::from_files(indexed).save(&path).unwrap();
+    assert_eq!(IndexCache::load(&path).len(), 1);
+
+    let text = std::fs::read_to_string(&path).unwrap();
+    let old = text.replace(
+        &format!("\"version\":{}", cache::FORMAT_VERSION),
+        "\"version\":0",
+    );
+    assert_ne!(old, text);
+    std::fs::write(&path, old).unwrap();
+    assert!(IndexCache::load(&path).is_empty());
+
+    std::fs::write(&path, "not json").unwrap();
+    assert!(IndexCache::load(&path).is_empty());
+    assert!(IndexCache::load(&dir.path().join("missing.json")).is_empty());
+}
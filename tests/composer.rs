@@ -1,4 +1,4 @@
-use phppp::composer::load_autoload_paths;
+use phppp::composer::{NamespaceMapping, load_autoload};
 use std::fs;
 use tempfile::tempdir;
 
@@ -7,6 +7,85 @@ fn parse_autoload_psr4() {
     let dir = tempdir().unwrap();
     let path = dir.path().join("composer.json");
     fs::write(&path, r#"{"autoload": {"psr-4": {"App\\": "src/"}}}"#).unwrap();
-    let map = load_autoload_paths(dir.path()).unwrap();
-    assert_eq!(map.get("App\\").unwrap(), "src/");
+    let autoload = load_autoload(dir.path()).unwrap();
+    assert_eq!(
+        autoload.psr4,
+        vec![NamespaceMapping {
+            prefix: "App\\".into(),
+            paths: vec![dir.path().join("src")],
+        }]
+    );
+}
+
+#[test]
+fn parse_full_autoload() {
+    let dir = tempdir().unwrap();
+    let root = dir.path();
+    fs::write(
+        root.join("composer.json"),
+        r#"{
+            "autoload": {
+                "psr-4": {"App\\": ["src/", "lib/"]},
+                "psr-0": {"Legacy_": "old/"},
+                "classmap": ["database/"],
+                "files": ["helpers.php"]
+            },
+            "autoload-dev": {
+                "psr-4": {"Tests\\": "tests/"}
+            }
+        }"#,
+    )
+    .unwrap();
+    let autoload = load_autoload(root).unwrap();
+
+    let app = autoload.psr4.iter().find(|m| m.prefix == "App\\").unwrap();
+    assert_eq!(app.paths, vec![root.join("src"), root.join("lib")]);
+    assert!(autoload.psr4.iter().any(|m| m.prefix == "Tests\\"));
+    assert_eq!(autoload.classmap, vec![root.join("database")]);
+    assert_eq!(autoload.files, vec![root.join("helpers.php")]);
+
+    let candidates = autoload.class_candidates("App\\Models\\User");
+    assert_eq!(
+        candidates,
+        vec![
+            root.join("src/Models/User.php"),
+            root.join("lib/Models/User.php")
+        ]
+    );
+    assert_eq!(
+        autoload.class_candidates("Legacy_Mail_Sender"),
+        vec![root.join("old/Legacy/Mail/Sender.php")]
+    );
+}
+
+#[test]
+fn parse_installed_packages() {
+    let dir = tempdir().unwrap();
+    let vendor = dir.path().join("vendor");
+    fs::create_dir_all(vendor.join("composer")).unwrap();
+    fs::write(
+        vendor.join("composer/installed.json"),
+        r#"{"packages": [
+            {"name": "illuminate/support", "install-path": "../illuminate/support",
+             "autoload": {"psr-4": {"Illuminate\\Support\\": ["src/", "lib/"]},
+                          "files": ["helpers.php"]}},
+            {"name": "legacy/lib", "autoload": {"classmap": ["classes/"]}}
+        ]}"#,
+    )
+    .unwrap();
+
+    let packages = phppp::composer::load_installed_packages(&vendor)
+        .unwrap()
+        .expect("installed.json not found");
+    assert_eq!(packages.len(), 2);
+    let support = &packages[0];
+    assert_eq!(support.install_path, vendor.join("illuminate/support"));
+    let paths = support.autoload.paths();
+    assert!(paths.contains(&vendor.join("illuminate/support/src")));
+    assert!(paths.contains(&vendor.join("illuminate/support/lib")));
+    assert!(paths.contains(&vendor.join("illuminate/support/helpers.php")));
+    assert_eq!(
+        packages[1].autoload.classmap,
+        vec![vendor.join("legacy/lib/classes")]
+    );
 }
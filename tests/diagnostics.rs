@@ -0,0 +1,47 @@
+use phppp::{diagnostics, parser};
+use tower_lsp::lsp_types::{DiagnosticSeverity, Position, Range};
+
+#[test]
+fn valid_source_has_no_diagnostics() {
+    let src = "<?php\nfunction foo() { return 1; }\n";
+    let ast = parser::parse_php(src);
+    assert!(diagnostics::syntax_errors(src, &ast).is_empty());
+}
+
+#[test]
+fn reports_missing_and_unexpected_tokens() {
+    let src = "<?php\n$a = 1\n$b = 2;\nfunction () {}\n";
+    let ast = parser::parse_php(src);
+    let diags = diagnostics::syntax_errors(src, &ast);
+    assert!(!diags.is_empty());
+    assert!(
+        diags
+            .iter()
+            .all(|d| d.severity == Some(DiagnosticSeverity::ERROR))
+    );
+    let unexpected = diags
+        .iter()
+        .find(|d| d.message == "unexpected `1`")
+        .expect("unexpected token not reported");
+    assert_eq!(unexpected.range.start.line, 1);
+    let missing = diags
+        .iter()
+        .find(|d| d.message == "missing `;`")
+        .expect("missing semicolon not reported");
+    assert_eq!(missing.range.start.line, 3);
+}
+
+#[test]
+fn ranges_count_utf16_columns() {
+    let src = "<?php\n$s = '😀é'; $a = 1\n$b = 2;\n";
+    let ast = parser::parse_php(src);
+    let diags = diagnostics::syntax_errors(src, &ast);
+    let unexpected = diags
+        .iter()
+        .find(|d| d.message == "unexpected `1`")
+        .expect("unexpected token not reported");
+    assert_eq!(
+        unexpected.range,
+        Range::new(Position::new(1, 17), Position::new(1, 18))
+    );
+}
@@ -1,4 +1,3 @@
-use bumpalo::Bump;
 use std::{fs, process::Command};
 use tower_lsp::lsp_types::Url;
 use which::which;
@@ -13,8 +12,7 @@ fn user_example() {
     }
     let path = fs::canonicalize("examples/user.php").unwrap();
     let text = fs::read_to_string(&path).unwrap();
-    let bump = Bump::new();
-    let ast = parser::parse_php(&text, &bump);
+    let ast = parser::parse_php(&text);
     assert!(!ast.0.root_node().has_error(), "Parse error");
 
     let uri = Url::from_file_path(&path).unwrap();
@@ -0,0 +1,120 @@
+use std::path::Path;
+
+use phppp::config::Config;
+use phppp::filter::FileFilter;
+use phppp::indexer;
+
+fn write(root: &Path, rel: &str, text: &str) {
+    let path = root.join(rel);
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(path, text).unwrap();
+}
+
+fn walked(filter: &FileFilter, root: &Path) -> Vec<String> {
+    let mut files: Vec<String> = filter
+        .walk(root)
+        .map(|p| {
+            p.strip_prefix(root)
+                .unwrap()
+                .to_string_lossy()
+                .replace('\\', "/")
+        })
+        .collect();
+    files.sort();
+    files
+}
+
+#[test]
+fn scan_and_watch_agree() {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path();
+    write(root, ".gitignore", "/cache/\n*.generated.php\n");
+    write(root, "src/.gitignore", "!keep.generated.php\n");
+    write(root, "src/App.php", "<?php");
+    write(root, "src/legacy.inc", "<?php");
+    write(root, "src/view.phtml", "<?php");
+    write(root, "src/Model.generated.php", "<?php");
+    write(root, "src/keep.generated.php", "<?php");
+    write(root, "cache/Compiled.php", "<?php");
+    write(root, "node_modules/pkg/x.php", "<?php");
+    write(root, "storage/framework/views/abc.php", "<?php");
+    write(root, "tests/fixtures/Broken.php", "<?php");
+    write(root, "README.md", "");
+
+    let config = Config {
+        exclude: vec![
+            "**/node_modules".into(),
+            "storage/framework/views/".into(),
+            "tests/fixtures/**".into(),
+        ],
+        extensions: vec![".inc".into(), "phtml".into()],
+        ..Config::default()
+    };
+    let filter = FileFilter::new(root, &config);
+    let expected = vec![
+        "src/App.php",
+        "src/keep.generated.php",
+        "src/legacy.inc",
+        "src/view.phtml",
+    ];
+    assert_eq!(walked(&filter, root), expected);
+
+    for rel in [
+        "src/App.php",
+        "src/legacy.inc",
+        "src/view.phtml",
+        "src/Model.generated.php",
+        "src/keep.generated.php",
+        "cache/Compiled.php",
+        "node_modules/pkg/x.php",
+        "storage/framework/views/abc.php",
+        "tests/fixtures/Broken.php",
+        "README.md",
+    ] {
+        assert_eq!(
+            filter.accepts(&root.join(rel)),
+            expected.contains(&rel),
+            "{}",
+            rel
+        );
+    }
+}
+
+#[test]
+fn include_globs_and_gitignore_switch() {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path();
+    write(root, ".gitignore", "build/\n");
+    write(root, "app/A.php", "<?php");
+    write(root, "build/B.php", "<?php");
+    write(root, "scripts/c.php", "<?php");
+
+    let config = Config {
+        include: vec!["app/**".into(), "build/**".into()],
+        gitignore: false,
+        ..Config::default()
+    };
+    let filter = FileFilter::new(root, &config);
+    assert_eq!(walked(&filter, root), vec!["app/A.php", "build/B.php"]);
+    assert!(!filter.accepts(&root.join("scripts/c.php")));
+}
+
+#[test]
+fn scan_workspace_reads_phppprc() {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path();
+    write(
+        root,
+        ".phppprc",
+        r#"{"exclude": ["legacy"], "extensions": ["module"]}"#,
+    );
+    write(root, "src/A.php", "<?php class A {}");
+    write(root, "src/hooks.module", "<?php function hooks() {}");
+    write(root, "legacy/Old.php", "<?php class Old {}");
+
+    let index = indexer::new_index();
+    indexer::scan_workspace(root, &index).unwrap();
+    assert!(!index.definitions("A").is_empty());
+    assert!(!index.definitions("hooks").is_empty());
+    assert!(index.definitions("Old").is_empty());
+}
@@ -0,0 +1,30 @@
+use phppp::fuzzy::score;
+
+#[test]
+fn camel_case_abbreviations_match() {
+    assert!(score("UsRe", "UserRepository").is_some());
+    assert!(score("usre", "UserRepository").is_some());
+    assert!(score("URep", "UserRepository").is_some());
+    assert!(score("http_get", "http_get_request").is_some());
+    assert!(score("UsRe", "Customer").is_none());
+    assert!(score("xyz", "UserRepository").is_none());
+}
+
+#[test]
+fn word_starts_rank_higher() {
+    let best = score("UsRe", "UserRepository").unwrap();
+    assert!(best > score("UsRe", "UnusedResult").unwrap());
+    assert!(best > score("UsRe", "MyUserRepository").unwrap());
+    let acronym = score("HRC", "HttpRequestContext").unwrap();
+    assert!(acronym > score("HRC", "Hardcore").unwrap());
+}
+
+#[test]
+fn exact_and_prefix_matches_rank_first() {
+    let exact = score("User", "User").unwrap();
+    let prefix = score("User", "UserRepository").unwrap();
+    let inner = score("User", "AppUser").unwrap();
+    assert!(exact > prefix);
+    assert!(prefix > inner);
+    assert!(score("", "Anything").is_some());
+}
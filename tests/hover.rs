@@ -0,0 +1,112 @@
+use std::path::Path;
+
+use tower_lsp::lsp_types::Url;
+
+use phppp::hover;
+use phppp::indexer::{self, FileSymbols};
+use phppp::parser;
+use phppp::resolver::ResolvedSymbol;
+
+const SRC: &str = r#"<?php
+namespace App\Models;
+
+/**
+ * A registered user.
+ *
+ * @property-read string $email
+ */
+#[Entity]
+final class User extends Model implements \JsonSerializable
+{
+    final public const int LIMIT = 10, OTHER = 2;
+
+    public static ?Profile $current = null;
+
+    public function __construct(private readonly int $id = 0) {}
+
+    /**
+     * Find a user.
+     *
+     * @param int $id the id
+     * @return static|null
+     * @throws NotFound when missing
+     * @deprecated use lookup()
+     */
+    #[Pure]
+    public static function find(
+        int $id,
+        string ...$columns,
+    ): ?static {
+        return null;
+    }
+}
+"#;
+
+fn symbols() -> FileSymbols {
+    let uri = Url::from_file_path("/work/app/Models/User.php").unwrap();
+    indexer::extract_symbols(SRC, &parser::parse_php(SRC), &uri)
+}
+
+fn resolved(symbols: &FileSymbols, name: &str) -> ResolvedSymbol {
+    let sym = symbols[name][0].clone();
+    ResolvedSymbol {
+        name: sym.name,
+        kind: sym.kind,
+        location: sym.location,
+        alternatives: Vec::new(),
+        type_hint: sym.type_hint,
+        doc: sym.doc,
+    }
+}
+
+#[test]
+fn signatures_show_the_declaration_on_one_line() {
+    let symbols = symbols();
+    let ast = parser::parse_php(SRC);
+    let signature = |name: &str| hover::signature(SRC, &ast, &symbols[name][0].location).unwrap();
+    assert_eq!(
+        signature("App\\Models\\User"),
+        "final class User extends Model implements \\JsonSerializable"
+    );
+    assert_eq!(
+        signature("App\\Models\\User::find"),
+        "public static function find(int $id, string ...$columns): ?static"
+    );
+    assert_eq!(
+        signature("App\\Models\\User::OTHER"),
+        "final public const int OTHER = 2"
+    );
+    assert_eq!(
+        signature("App\\Models\\User::$current"),
+        "public static ?Profile $current = null"
+    );
+    assert_eq!(
+        signature("App\\Models\\User::$id"),
+        "private readonly int $id = 0"
+    );
+    assert_eq!(signature("App\\Models\\User::$email"), "string $email");
+}
+
+#[test]
+fn renders_docblock_container_and_file() {
+    let symbols = symbols();
+    let find = resolved(&symbols, "App\\Models\\User::find");
+    let ast = parser::parse_php(SRC);
+    let markdown = hover::render(&find, Some((SRC, &ast)), Some(Path::new("/work")));
+    assert_eq!(
+        markdown,
+        "```php\npublic static function find(int $id, string ...$columns): ?static\n```\n\n\
+         **Deprecated** use lookup()\n\n\
+         Find a user.\n\n\
+         *@param* `int $id` — the id  \n\
+         *@return* `App\\Models\\User|null`  \n\
+         *@throws* `App\\Models\\NotFound` — when missing\n\n\
+         ---\n\n\
+         in `App\\Models\\User`, defined in `app/Models/User.php`"
+    );
+
+    let class = resolved(&symbols, "App\\Models\\User");
+    let markdown = hover::render(&class, None, None);
+    assert!(markdown.starts_with("```php\nApp\\Models\\User\n```\n\nA registered user."));
+    assert!(markdown.ends_with("in `App\\Models`, defined in `/work/app/Models/User.php`"));
+}
@@ -0,0 +1,97 @@
+use phppp::parser;
+use tower_lsp::lsp_types::{Position, Range, TextDocumentContentChangeEvent};
+
+fn change(start: (u32, u32), end: (u32, u32), text: &str) -> TextDocumentContentChangeEvent {
+    TextDocumentContentChangeEvent {
+        range: Some(Range {
+            start: Position::new(start.0, start.1),
+            end: Position::new(end.0, end.1),
+        }),
+        range_length: None,
+        text: text.into(),
+    }
+}
+
+/// Apply `changes` incrementally and check both the text and the tree match
+/// a parse of `expected` from scratch.
+fn assert_incremental(src: &str, changes: &[TextDocumentContentChangeEvent], expected: &str) {
+    let mut text = src.to_string();
+    let mut ast = Some(parser::parse_php(&text));
+    for c in changes {
+        parser::apply_change(&mut text, &mut ast, c);
+    }
+    assert_eq!(text, expected);
+    let incremental = parser::reparse_php(&text, ast.as_ref());
+    let full = parser::parse_php(expected);
+    assert_eq!(
+        incremental.0.root_node().to_sexp(),
+        full.0.root_node().to_sexp()
+    );
+    assert_eq!(
+        incremental.0.root_node().end_position(),
+        full.0.root_node().end_position()
+    );
+}
+
+#[test]
+fn single_line_insert() {
+    assert_incremental(
+        "<?php\nfunction foo() {}\n",
+        &[change((1, 13), (1, 13), "$a, $b")],
+        "<?php\nfunction foo($a, $b) {}\n",
+    );
+}
+
+#[test]
+fn multi_line_insert_and_delete() {
+    let src = "<?php\nclass A {\n    public function a() {}\n}\n";
+    assert_incremental(
+        src,
+        &[
+            change(
+                (2, 26),
+                (2, 26),
+                "\n    public function b() {\n        return 1;\n    }",
+            ),
+            change((2, 0), (3, 0), ""),
+        ],
+        "<?php\nclass A {\n    public function b() {\n        return 1;\n    }\n}\n",
+    );
+}
+
+#[test]
+fn sequential_keystrokes() {
+    let mut expected = String::from("<?php\n");
+    let mut changes = Vec::new();
+    for (i, c) in "$x = foo(1);".chars().enumerate() {
+        changes.push(change((1, i as u32), (1, i as u32), &c.to_string()));
+        expected.push(c);
+    }
+    assert_incremental("<?php\n", &changes, &expected);
+}
+
+#[test]
+fn utf16_columns() {
+    assert_incremental(
+        "<?php\n$s = \"héllo😀\"; $t = 1;\n",
+        &[change((1, 16), (1, 23), "$u = 2;")],
+        "<?php\n$s = \"héllo😀\"; $u = 2;\n",
+    );
+}
+
+#[test]
+fn full_replacement_drops_tree() {
+    let mut text = String::from("<?php\necho 1;\n");
+    let mut ast = Some(parser::parse_php(&text));
+    parser::apply_change(
+        &mut text,
+        &mut ast,
+        &TextDocumentContentChangeEvent {
+            range: None,
+            range_length: None,
+            text: "<?php\necho 2;\n".into(),
+        },
+    );
+    assert_eq!(text, "<?php\necho 2;\n");
+    assert!(ast.is_none());
+}
@@ -1,6 +1,7 @@
-use bumpalo::Bump;
 use tower_lsp::lsp_types::Url;
 
+use phppp::config::Config;
+use phppp::filter::FileFilter;
 use phppp::{indexer, parser};
 
 #[test]
@@ -12,8 +13,7 @@ class Baz {}
 const MYCONST = 1;
 $var = 2;
 "#;
-    let bump = Bump::new();
-    let ast = parser::parse_php(src, &bump);
+    let ast = parser::parse_php(src);
     let uri = Url::parse("file:///test.php").unwrap();
     let symbols = indexer::extract_symbols(src, &ast, &uri);
     assert!(symbols.contains_key("Foo\\bar"));
@@ -21,3 +21,383 @@ $var = 2;
     assert!(symbols.contains_key("Foo\\MYCONST"));
     assert!(symbols.contains_key("Foo\\$var"));
 }
+
+#[test]
+fn extract_class_members() {
+    let src = r#"<?php
+namespace App;
+class User {
+    const TABLE = 'users';
+    private static $count = 0;
+    public function __construct(private string $name) {}
+    public function save() {}
+}
+enum Status {
+    case Active;
+}
+"#;
+    let ast = parser::parse_php(src);
+    let uri = Url::parse("file:///test.php").unwrap();
+    let symbols = indexer::extract_symbols(src, &ast, &uri);
+
+    let save = &symbols.get("App\\User::save").expect("method not indexed")[0];
+    assert_eq!(save.kind, indexer::SymbolKind::Method);
+    assert_eq!(save.container.as_deref(), Some("App\\User"));
+    assert_eq!(
+        symbols["App\\User::TABLE"][0].kind,
+        indexer::SymbolKind::ClassConstant
+    );
+    assert_eq!(
+        symbols["App\\User::$count"][0].kind,
+        indexer::SymbolKind::Property
+    );
+    assert_eq!(
+        symbols["App\\User::$name"][0].kind,
+        indexer::SymbolKind::Property
+    );
+    assert_eq!(
+        symbols["App\\Status::Active"][0].kind,
+        indexer::SymbolKind::EnumCase
+    );
+}
+
+#[test]
+fn extract_interfaces_traits_enums() {
+    let src = r#"<?php
+namespace App\Domain;
+interface UserRepositoryInterface {
+    public function find(int $id);
+}
+trait HasTimestamps {}
+enum Status: string {
+    case Active = 'active';
+}
+"#;
+    let ast = parser::parse_php(src);
+    let uri = Url::parse("file:///test.php").unwrap();
+    let symbols = indexer::extract_symbols(src, &ast, &uri);
+
+    assert_eq!(
+        symbols["App\\Domain\\UserRepositoryInterface"][0].kind,
+        indexer::SymbolKind::Interface
+    );
+    assert_eq!(
+        symbols["App\\Domain\\HasTimestamps"][0].kind,
+        indexer::SymbolKind::Trait
+    );
+    assert_eq!(
+        symbols["App\\Domain\\Status"][0].kind,
+        indexer::SymbolKind::Enum
+    );
+    assert!(symbols.contains_key("App\\Domain\\UserRepositoryInterface::find"));
+}
+
+#[test]
+fn extract_multiple_namespaces() {
+    let src = r#"<?php
+namespace Foo {
+    function a() {}
+    class A {}
+}
+namespace Bar {
+    function b() {}
+}
+namespace {
+    function c() {}
+}
+"#;
+    let ast = parser::parse_php(src);
+    let uri = Url::parse("file:///test.php").unwrap();
+    let symbols = indexer::extract_symbols(src, &ast, &uri);
+    assert!(symbols.contains_key("Foo\\a"));
+    assert!(symbols.contains_key("Foo\\A"));
+    assert!(symbols.contains_key("Bar\\b"));
+    assert!(symbols.contains_key("c"));
+}
+
+#[test]
+fn scan_indexes_vendor_as_separate_layer() {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path();
+    std::fs::create_dir_all(root.join("src")).unwrap();
+    std::fs::write(root.join("src/App.php"), "<?php\nclass App {}\n").unwrap();
+    let package = root.join("vendor/acme/util");
+    std::fs::create_dir_all(package.join("src")).unwrap();
+    std::fs::create_dir_all(package.join("tests")).unwrap();
+    std::fs::create_dir_all(root.join("vendor/composer")).unwrap();
+    std::fs::write(
+        root.join("vendor/composer/installed.json"),
+        r#"{"packages": [{"name": "acme/util", "install-path": "../acme/util",
+            "autoload": {"psr-4": {"Acme\\Util\\": "src/"}}}]}"#,
+    )
+    .unwrap();
+    std::fs::write(
+        package.join("src/Collection.php"),
+        "<?php\nnamespace Acme\\Util;\nclass Collection {}\n",
+    )
+    .unwrap();
+    std::fs::write(
+        package.join("tests/CollectionTest.php"),
+        "<?php\nclass CollectionTest {}\n",
+    )
+    .unwrap();
+
+    let index = indexer::new_index();
+    indexer::scan_workspace(root, &index).unwrap();
+
+    let find = |fqn: &str| {
+        index
+            .iter()
+            .find(|e| e.value().contains_key(fqn))
+            .map(|e| index.layer(e.key()))
+    };
+    assert_eq!(find("App"), Some(indexer::Layer::Project));
+    assert_eq!(find("Acme\\Util\\Collection"), Some(indexer::Layer::Vendor));
+    assert_eq!(find("CollectionTest"), None);
+}
+
+#[test]
+fn overlapping_vendor_roots_list_files_once() {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path();
+    let package = root.join("vendor/acme/util");
+    std::fs::create_dir_all(package.join("src/Legacy")).unwrap();
+    std::fs::create_dir_all(root.join("vendor/composer")).unwrap();
+    std::fs::write(
+        root.join("vendor/composer/installed.json"),
+        r#"{"packages": [{"name": "acme/util", "install-path": "../acme/util",
+            "autoload": {"psr-4": {"Acme\\Util\\": "src/"}, "classmap": ["src/Legacy"]}}]}"#,
+    )
+    .unwrap();
+    std::fs::write(package.join("src/Map.php"), "<?php\nclass Map {}\n").unwrap();
+    std::fs::write(
+        package.join("src/Legacy/OldMap.php"),
+        "<?php\nclass OldMap {}\n",
+    )
+    .unwrap();
+
+    let index = indexer::new_index();
+    let filter = FileFilter::new(root, &Config::default());
+    let files = indexer::workspace_files(root, &index, &filter).unwrap();
+    let mut names: Vec<_> = files
+        .iter()
+        .map(|(path, _)| path.file_name().unwrap().to_owned())
+        .collect();
+    names.sort();
+    assert_eq!(names, ["Map.php", "OldMap.php"]);
+}
+
+#[test]
+fn index_files_reports_progress() {
+    let dir = tempfile::tempdir().unwrap();
+    for i in 0..25 {
+        std::fs::write(
+            dir.path().join(format!("C{}.php", i)),
+            format!("<?php\nclass C{} {{}}\n", i),
+        )
+        .unwrap();
+    }
+    std::fs::write(dir.path().join("README.md"), "not php").unwrap();
+
+    let index = indexer::new_index();
+    let filter = FileFilter::new(dir.path(), &Config::default());
+    let files = indexer::workspace_files(dir.path(), &index, &filter).unwrap();
+    assert_eq!(files.len(), 25);
+    let reported = std::sync::Mutex::new(Vec::new());
+    indexer::index_files(&files, &index, |done| reported.lock().unwrap().push(done));
+
+    let mut reported = reported.into_inner().unwrap();
+    reported.sort();
+    assert_eq!(reported, (1..=25).collect::<Vec<_>>());
+    assert_eq!(index.len(), 25);
+    assert!(index.iter().any(|e| e.value().contains_key("C24")));
+}
+
+#[test]
+fn conditional_declarations_keep_every_definition() {
+    let src = r#"<?php
+if (!function_exists('str_contains')) {
+    function str_contains($haystack, $needle) {}
+} else {
+    function str_contains($haystack, $needle) {}
+}
+if (PHP_VERSION_ID < 80000):
+    class Polyfill {}
+endif;
+"#;
+    let ast = parser::parse_php(src);
+    let uri = Url::parse("file:///polyfill.php").unwrap();
+    let symbols = indexer::extract_symbols(src, &ast, &uri);
+    let defs = &symbols["str_contains"];
+    assert_eq!(defs.len(), 2);
+    assert_eq!(defs[0].location.range.start.line, 2);
+    assert_eq!(defs[1].location.range.start.line, 4);
+    assert!(symbols.contains_key("Polyfill"));
+}
+
+#[test]
+fn reverse_index_tracks_replacements() {
+    let index = indexer::new_index();
+    let extract = |src: &str, uri: &Url| {
+        let ast = parser::parse_php(src);
+        indexer::extract_symbols(src, &ast, uri)
+    };
+    let a = Url::parse("file:///a.php").unwrap();
+    let b = Url::parse("file:///b.php").unwrap();
+    let stub = Url::parse("phppp-stub:///core/Core.php").unwrap();
+    index.insert_in(
+        stub.clone(),
+        extract("<?php function helper() {}", &stub),
+        indexer::Layer::Stub,
+    );
+    index.insert(a.clone(), extract("<?php function helper() {}", &a));
+    index.insert(b.clone(), extract("<?php function helper() {}", &b));
+
+    let defs = index.definitions("helper");
+    assert_eq!(defs.len(), 3);
+    assert_eq!(defs[0].0, indexer::Layer::Project);
+    assert_eq!(defs[1].0, indexer::Layer::Project);
+    assert_eq!(defs[2].1.location.uri, stub);
+
+    // replacing a file drops names it no longer defines
+    index.insert(a.clone(), extract("<?php function other() {}", &a));
+    assert_eq!(index.definitions("helper").len(), 2);
+    assert_eq!(index.definitions("other")[0].1.location.uri, a);
+
+    index.remove(&b);
+    let defs = index.definitions("helper");
+    assert_eq!(defs.len(), 1);
+    assert_eq!(defs[0].0, indexer::Layer::Stub);
+
+    let mut names = Vec::new();
+    index.for_each_name(|name, _, layer| names.push((name.to_string(), layer)));
+    names.sort();
+    assert_eq!(
+        names,
+        vec![
+            ("helper".to_string(), indexer::Layer::Stub),
+            ("other".to_string(), indexer::Layer::Project),
+        ]
+    );
+
+    index.clear();
+    assert!(index.definitions("other").is_empty());
+}
+
+#[test]
+fn members_are_listed_per_class() {
+    let index = indexer::new_index();
+    let extract = |src: &str, uri: &Url| {
+        let ast = parser::parse_php(src);
+        indexer::extract_symbols(src, &ast, uri)
+    };
+    let a = Url::parse("file:///a.php").unwrap();
+    let b = Url::parse("file:///b.php").unwrap();
+    index.insert(
+        a.clone(),
+        extract("<?php class Box { public $size; function open() {} }", &a),
+    );
+    index.insert(
+        b.clone(),
+        extract("<?php class Bag { function open() {} }", &b),
+    );
+
+    let mut members = index.members_of("box");
+    members.sort();
+    assert_eq!(members, vec!["Box::$size", "Box::open"]);
+    assert_eq!(index.members_of("Bag"), vec!["Bag::open"]);
+
+    // members a file no longer defines are dropped with it
+    index.insert(
+        a.clone(),
+        extract("<?php class Box { function open() {} }", &a),
+    );
+    assert_eq!(index.members_of("Box"), vec!["Box::open"]);
+    index.remove(&b);
+    assert!(index.members_of("Bag").is_empty());
+}
+
+#[test]
+fn class_like_symbols_record_supertypes() {
+    let src = r#"<?php
+namespace App;
+use Lib\Model;
+use Lib\Contracts as C;
+class User extends Model implements C\Jsonable, \Countable {
+    use HasName, \Lib\Timestamps;
+}
+interface Repo extends C\Repository {}
+function helper() {}
+"#;
+    let uri = Url::parse("file:///user.php").unwrap();
+    let ast = parser::parse_php(src);
+    let symbols = indexer::extract_symbols(src, &ast, &uri);
+    assert_eq!(
+        symbols["App\\User"][0].supertypes,
+        vec![
+            "App\\HasName",
+            "Lib\\Timestamps",
+            "Lib\\Model",
+            "Lib\\Contracts\\Jsonable",
+            "Countable",
+        ]
+    );
+    assert_eq!(
+        symbols["App\\Repo"][0].supertypes,
+        vec!["Lib\\Contracts\\Repository"]
+    );
+    assert!(symbols["App\\helper"][0].supertypes.is_empty());
+}
+
+#[test]
+fn docblocks_are_attached_and_type_members() {
+    let src = r#"<?php
+namespace App;
+use Lib\Collection;
+/**
+ * A registered user.
+ *
+ * @property-read string $email
+ * @method static Collection<User> active()
+ * @method itemsList items()
+ */
+class User {
+    /** @var Profile|null */
+    public $profile;
+
+    /**
+     * @deprecated use profile() instead
+     * @return $this
+     */
+    public function legacy() {}
+
+    /** @param Profile $profile */
+    public function __construct(public $profile2) {}
+}
+"#;
+    let uri = Url::parse("file:///user.php").unwrap();
+    let ast = parser::parse_php(src);
+    let symbols = indexer::extract_symbols(src, &ast, &uri);
+    let user = &symbols["App\\User"][0];
+    assert_eq!(user.doc.as_ref().unwrap().summary, "A registered user.");
+
+    let profile = &symbols["App\\User::$profile"][0];
+    assert_eq!(profile.type_hint.as_deref(), Some("App\\Profile|null"));
+    let legacy = &symbols["App\\User::legacy"][0];
+    assert_eq!(legacy.type_hint.as_deref(), Some("App\\User"));
+    let deprecated = legacy.doc.as_ref().unwrap().deprecated.as_deref();
+    assert_eq!(deprecated, Some("use profile() instead"));
+
+    let email = &symbols["App\\User::$email"][0];
+    assert_eq!(email.type_hint.as_deref(), Some("string"));
+    assert_eq!(email.location.range.start.line, 6);
+    assert_eq!(email.location.range.start.character, 25);
+    let active = &symbols["App\\User::active"][0];
+    assert_eq!(active.type_hint.as_deref(), Some("Lib\\Collection"));
+    assert_eq!(active.location.range.start.line, 7);
+    assert_eq!(active.location.range.start.character, 35);
+    // the name is found after the type, even where the type spells it
+    let items = &symbols["App\\User::items"][0].location.range;
+    assert_eq!((items.start.line, items.start.character), (8, 21));
+    assert_eq!(items.end.character, 26);
+}
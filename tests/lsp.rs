@@ -1,14 +1,18 @@
 use phppp::server::Backend;
 use tower_lsp::lsp_types::{
-    CompletionParams, CompletionResponse, DidOpenTextDocumentParams, GotoDefinitionParams,
-    GotoDefinitionResponse, HoverParams, Position, ReferenceContext, ReferenceParams, RenameParams,
-    TextDocumentIdentifier, TextDocumentItem, TextDocumentPositionParams, Url,
+    CompletionItemTag, CompletionParams, CompletionResponse, DidChangeTextDocumentParams,
+    DidCloseTextDocumentParams, DidOpenTextDocumentParams, DocumentChanges, DocumentSymbolParams,
+    DocumentSymbolResponse, Documentation, GotoDefinitionParams, GotoDefinitionResponse,
+    HoverParams, InitializeParams, InitializedParams, OneOf, Position, PrepareRenameResponse,
+    Range, ReferenceContext, ReferenceParams, RenameParams, SignatureHelpParams,
+    TextDocumentContentChangeEvent, TextDocumentIdentifier, TextDocumentItem,
+    TextDocumentPositionParams, Url, VersionedTextDocumentIdentifier, WorkspaceSymbolParams,
 };
 use tower_lsp::{LanguageServer, LspService};
 
 #[tokio::test]
 async fn goto_definition_basic() {
-    let (service, _) = LspService::new(|c| Backend::new(c));
+    let (service, _) = LspService::new(Backend::new);
     let backend = service.inner();
     let uri = Url::parse("file:///test.php").unwrap();
     let text = "<?php function foo() {}\nfoo();";
@@ -44,7 +48,7 @@ async fn goto_definition_basic() {
 
 #[tokio::test]
 async fn completion_returns_items() {
-    let (service, _) = LspService::new(|c| Backend::new(c));
+    let (service, _) = LspService::new(Backend::new);
     let backend = service.inner();
     let uri = Url::parse("file:///test.php").unwrap();
     let text = "<?php function foo() {}";
@@ -78,9 +82,44 @@ async fn completion_returns_items() {
     assert!(items.iter().any(|i| i.label == "foo"));
 }
 
+#[tokio::test]
+async fn completion_after_multibyte_text() {
+    let (service, _) = LspService::new(Backend::new);
+    let backend = service.inner();
+    let uri = Url::parse("file:///test.php").unwrap();
+    let text = "<?php function abc() {}\necho \"€ab";
+    backend
+        .did_open(DidOpenTextDocumentParams {
+            text_document: TextDocumentItem {
+                uri: uri.clone(),
+                language_id: "php".into(),
+                version: 1,
+                text: text.into(),
+            },
+        })
+        .await;
+    let resp = backend
+        .completion(CompletionParams {
+            text_document_position: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier { uri: uri.clone() },
+                position: Position::new(1, 9),
+            },
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+            context: None,
+        })
+        .await
+        .unwrap()
+        .unwrap();
+    let CompletionResponse::Array(items) = resp else {
+        panic!("unexpected");
+    };
+    assert!(items.iter().any(|i| i.label == "abc"));
+}
+
 #[tokio::test]
 async fn hover_shows_symbol() {
-    let (service, _) = LspService::new(|c| Backend::new(c));
+    let (service, _) = LspService::new(Backend::new);
     let backend = service.inner();
     let uri = Url::parse("file:///test.php").unwrap();
     let text = "<?php function foo() {}\nfoo();";
@@ -107,20 +146,70 @@ async fn hover_shows_symbol() {
     };
     let resp = backend.hover(params).await.unwrap().unwrap();
     let contents = match resp.contents {
-        tower_lsp::lsp_types::HoverContents::Scalar(s) => s,
+        tower_lsp::lsp_types::HoverContents::Markup(m) => m,
         _ => panic!("unexpected"),
     };
-    match contents {
-        tower_lsp::lsp_types::MarkedString::String(s) => {
-            assert!(s.contains("foo"));
+    assert_eq!(contents.kind, tower_lsp::lsp_types::MarkupKind::Markdown);
+    assert!(contents.value.starts_with("```php\nfunction foo()\n```"));
+    assert!(contents.value.contains("defined in `/test.php`"));
+    assert_eq!(
+        resp.range,
+        Some(Range::new(Position::new(1, 0), Position::new(1, 3)))
+    );
+}
+
+#[tokio::test]
+async fn positions_are_utf16_on_non_ascii_lines() {
+    let (service, _) = LspService::new(Backend::new);
+    let backend = service.inner();
+    let uri = Url::parse("file:///test.php").unwrap();
+    let text = "<?php /* é */ function foo() {}\n$s = 'é'; foo();";
+    backend
+        .did_open(DidOpenTextDocumentParams {
+            text_document: TextDocumentItem {
+                uri: uri.clone(),
+                language_id: "php".into(),
+                version: 1,
+                text: text.into(),
+            },
+        })
+        .await;
+
+    let position = TextDocumentPositionParams {
+        text_document: TextDocumentIdentifier { uri: uri.clone() },
+        position: Position::new(1, 11),
+    };
+    let resp = backend
+        .hover(HoverParams {
+            text_document_position_params: position.clone(),
+            work_done_progress_params: Default::default(),
+        })
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        resp.range,
+        Some(Range::new(Position::new(1, 10), Position::new(1, 13)))
+    );
+    let resp = backend
+        .goto_definition(GotoDefinitionParams {
+            text_document_position_params: position,
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        })
+        .await
+        .unwrap();
+    match resp.unwrap() {
+        GotoDefinitionResponse::Scalar(loc) => {
+            assert_eq!(loc.range.start, Position::new(0, 14))
         }
-        _ => panic!("unexpected"),
+        _ => panic!("unexpected response"),
     }
 }
 
 #[tokio::test]
 async fn references_returns_locations() {
-    let (service, _) = LspService::new(|c| Backend::new(c));
+    let (service, _) = LspService::new(Backend::new);
     let backend = service.inner();
     let uri = Url::parse("file:///test.php").unwrap();
     let text = "<?php function foo() {}\nfoo();\nfoo();";
@@ -153,9 +242,75 @@ async fn references_returns_locations() {
     assert!(refs.len() >= 2);
 }
 
+#[tokio::test]
+async fn references_without_declaration() {
+    let (service, _) = LspService::new(Backend::new);
+    let backend = service.inner();
+    let uri = Url::parse("file:///test.php").unwrap();
+    let text = "<?php function foo() {}\nfoobar();\nfoo(); // foo\n";
+    backend
+        .did_open(DidOpenTextDocumentParams {
+            text_document: TextDocumentItem {
+                uri: uri.clone(),
+                language_id: "php".into(),
+                version: 1,
+                text: text.into(),
+            },
+        })
+        .await;
+
+    let params = ReferenceParams {
+        text_document_position: TextDocumentPositionParams {
+            text_document: TextDocumentIdentifier { uri: uri.clone() },
+            position: Position {
+                line: 2,
+                character: 1,
+            },
+        },
+        work_done_progress_params: Default::default(),
+        partial_result_params: Default::default(),
+        context: ReferenceContext {
+            include_declaration: false,
+        },
+    };
+    let refs = backend.references(params).await.unwrap().unwrap();
+    assert_eq!(refs.len(), 1);
+    assert_eq!(refs[0].range.start, Position::new(2, 0));
+}
+
+#[tokio::test]
+async fn prepare_rename_returns_name_range() {
+    let (service, _) = LspService::new(Backend::new);
+    let backend = service.inner();
+    let uri = Url::parse("file:///test.php").unwrap();
+    let text = "<?php function foo() {}\nfoo();";
+    backend
+        .did_open(DidOpenTextDocumentParams {
+            text_document: TextDocumentItem {
+                uri: uri.clone(),
+                language_id: "php".into(),
+                version: 1,
+                text: text.into(),
+            },
+        })
+        .await;
+
+    let at = |line, character| TextDocumentPositionParams {
+        text_document: TextDocumentIdentifier { uri: uri.clone() },
+        position: Position { line, character },
+    };
+    let resp = backend.prepare_rename(at(1, 1)).await.unwrap();
+    let Some(PrepareRenameResponse::Range(range)) = resp else {
+        panic!("unexpected response");
+    };
+    assert_eq!(range, Range::new(Position::new(1, 0), Position::new(1, 3)));
+    // the `function` keyword is not a symbol
+    assert!(backend.prepare_rename(at(0, 8)).await.is_err());
+}
+
 #[tokio::test]
 async fn rename_returns_edit() {
-    let (service, _) = LspService::new(|c| Backend::new(c));
+    let (service, _) = LspService::new(Backend::new);
     let backend = service.inner();
     let uri = Url::parse("file:///test.php").unwrap();
     let text = "<?php function foo() {}\nfoo();\nfoo();";
@@ -182,6 +337,645 @@ async fn rename_returns_edit() {
         work_done_progress_params: Default::default(),
     };
     let edit = backend.rename(params).await.unwrap().unwrap();
-    let changes = edit.changes.unwrap();
-    assert!(changes.get(&uri).unwrap().len() >= 3);
+    let Some(DocumentChanges::Edits(changes)) = edit.document_changes else {
+        panic!("expected document changes");
+    };
+    assert_eq!(changes.len(), 1);
+    assert_eq!(changes[0].text_document.uri, uri);
+    assert_eq!(changes[0].text_document.version, Some(1));
+    assert_eq!(changes[0].edits.len(), 3);
+}
+
+#[tokio::test]
+async fn rename_updates_typed_member_calls() {
+    let (service, _) = LspService::new(Backend::new);
+    let backend = service.inner();
+    let uri = Url::parse("file:///test.php").unwrap();
+    let text = "<?php\nclass User { function save() {} }\n$u = new User();\n$u->save();\nfunction f(User $x) { $x->save(); }\n";
+    backend
+        .did_open(DidOpenTextDocumentParams {
+            text_document: TextDocumentItem {
+                uri: uri.clone(),
+                language_id: "php".into(),
+                version: 1,
+                text: text.into(),
+            },
+        })
+        .await;
+
+    let edit = backend
+        .rename(RenameParams {
+            text_document_position: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier { uri: uri.clone() },
+                position: Position::new(3, 5),
+            },
+            new_name: "store".into(),
+            work_done_progress_params: Default::default(),
+        })
+        .await
+        .unwrap()
+        .unwrap();
+    let Some(DocumentChanges::Edits(changes)) = edit.document_changes else {
+        panic!("expected document changes");
+    };
+    let mut starts: Vec<Position> = changes[0]
+        .edits
+        .iter()
+        .map(|e| match e {
+            OneOf::Left(edit) => edit.range.start,
+            OneOf::Right(edit) => edit.text_edit.range.start,
+        })
+        .collect();
+    starts.sort_by_key(|p| (p.line, p.character));
+    assert_eq!(
+        starts,
+        [
+            Position::new(1, 22),
+            Position::new(3, 4),
+            Position::new(4, 26)
+        ]
+    );
+}
+
+#[tokio::test]
+async fn goto_definition_static_member() {
+    let (service, _) = LspService::new(Backend::new);
+    let backend = service.inner();
+    let uri = Url::parse("file:///test.php").unwrap();
+    let text = "<?php\nclass User {\n    public static function create() {}\n}\nUser::create();";
+    backend
+        .did_open(DidOpenTextDocumentParams {
+            text_document: TextDocumentItem {
+                uri: uri.clone(),
+                language_id: "php".into(),
+                version: 1,
+                text: text.into(),
+            },
+        })
+        .await;
+
+    let params = GotoDefinitionParams {
+        text_document_position_params: TextDocumentPositionParams {
+            text_document: TextDocumentIdentifier { uri: uri.clone() },
+            position: Position {
+                line: 4,
+                character: 8,
+            },
+        },
+        work_done_progress_params: Default::default(),
+        partial_result_params: Default::default(),
+    };
+    let resp = backend.goto_definition(params).await.unwrap();
+    match resp.unwrap() {
+        GotoDefinitionResponse::Scalar(loc) => {
+            assert_eq!(loc.range.start.line, 2);
+        }
+        _ => panic!("unexpected response"),
+    }
+}
+
+#[tokio::test]
+async fn goto_definition_builtin_stub() {
+    let (service, _) = LspService::new(Backend::new);
+    let backend = service.inner();
+    backend
+        .initialize(InitializeParams::default())
+        .await
+        .unwrap();
+    let uri = Url::parse("file:///test.php").unwrap();
+    let text = "<?php\nnamespace App;\n$n = strlen('abc');";
+    backend
+        .did_open(DidOpenTextDocumentParams {
+            text_document: TextDocumentItem {
+                uri: uri.clone(),
+                language_id: "php".into(),
+                version: 1,
+                text: text.into(),
+            },
+        })
+        .await;
+
+    let params = GotoDefinitionParams {
+        text_document_position_params: TextDocumentPositionParams {
+            text_document: TextDocumentIdentifier { uri: uri.clone() },
+            position: Position {
+                line: 2,
+                character: 7,
+            },
+        },
+        work_done_progress_params: Default::default(),
+        partial_result_params: Default::default(),
+    };
+    let resp = backend.goto_definition(params).await.unwrap();
+    match resp.unwrap() {
+        GotoDefinitionResponse::Scalar(loc) => {
+            assert_eq!(loc.uri.scheme(), "phppp-stub");
+            assert!(phppp::stubs::source(&loc.uri).is_some());
+        }
+        _ => panic!("unexpected response"),
+    }
+}
+
+#[tokio::test]
+async fn incremental_change_updates_document() {
+    let (service, _) = LspService::new(Backend::new);
+    let backend = service.inner();
+    let uri = Url::parse("file:///incremental.php").unwrap();
+    backend
+        .did_open(DidOpenTextDocumentParams {
+            text_document: TextDocumentItem {
+                uri: uri.clone(),
+                language_id: "php".into(),
+                version: 1,
+                text: "<?php function foo() {}\n".into(),
+            },
+        })
+        .await;
+    backend
+        .did_change(DidChangeTextDocumentParams {
+            text_document: VersionedTextDocumentIdentifier {
+                uri: uri.clone(),
+                version: 2,
+            },
+            content_changes: vec![
+                TextDocumentContentChangeEvent {
+                    range: Some(Range::new(Position::new(0, 15), Position::new(0, 18))),
+                    range_length: None,
+                    text: "bar".into(),
+                },
+                TextDocumentContentChangeEvent {
+                    range: Some(Range::new(Position::new(1, 0), Position::new(1, 0))),
+                    range_length: None,
+                    text: "bar();".into(),
+                },
+            ],
+        })
+        .await;
+
+    let resp = backend
+        .goto_definition(GotoDefinitionParams {
+            text_document_position_params: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier { uri: uri.clone() },
+                position: Position::new(1, 1),
+            },
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        })
+        .await
+        .unwrap();
+    match resp.unwrap() {
+        GotoDefinitionResponse::Scalar(loc) => {
+            assert_eq!(loc.range.start, Position::new(0, 6));
+        }
+        _ => panic!("unexpected response"),
+    }
+}
+
+#[tokio::test]
+async fn workspace_is_scanned_in_background() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(
+        dir.path().join("Service.php"),
+        "<?php\nnamespace App;\nclass Service {}\n",
+    )
+    .unwrap();
+    let (service, _) = LspService::new(Backend::new);
+    let backend = service.inner();
+    backend
+        .initialize(InitializeParams {
+            root_uri: Some(Url::from_directory_path(dir.path()).unwrap()),
+            ..InitializeParams::default()
+        })
+        .await
+        .unwrap();
+    backend.initialized(InitializedParams {}).await;
+
+    let uri = Url::parse("file:///main.php").unwrap();
+    backend
+        .did_open(DidOpenTextDocumentParams {
+            text_document: TextDocumentItem {
+                uri: uri.clone(),
+                language_id: "php".into(),
+                version: 1,
+                text: "<?php\nnew \\App\\Service();".into(),
+            },
+        })
+        .await;
+    let params = GotoDefinitionParams {
+        text_document_position_params: TextDocumentPositionParams {
+            text_document: TextDocumentIdentifier { uri: uri.clone() },
+            position: Position {
+                line: 1,
+                character: 10,
+            },
+        },
+        work_done_progress_params: Default::default(),
+        partial_result_params: Default::default(),
+    };
+    let mut found = None;
+    for _ in 0..100 {
+        found = backend.goto_definition(params.clone()).await.unwrap();
+        if found.is_some() {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    }
+    match found.expect("workspace file never indexed") {
+        GotoDefinitionResponse::Scalar(loc) => {
+            assert!(loc.uri.path().ends_with("Service.php"));
+        }
+        _ => panic!("unexpected response"),
+    }
+}
+
+#[tokio::test]
+async fn background_scan_keeps_open_documents() {
+    let dir = tempfile::tempdir().unwrap();
+    let service_php = dir.path().join("Service.php");
+    std::fs::write(&service_php, "<?php\nclass OnDisk {}\n").unwrap();
+    std::fs::write(dir.path().join("Other.php"), "<?php\nclass Other {}\n").unwrap();
+    let (service, _) = LspService::new(Backend::new);
+    let backend = service.inner();
+    backend
+        .initialize(InitializeParams {
+            root_uri: Some(Url::from_directory_path(dir.path()).unwrap()),
+            ..InitializeParams::default()
+        })
+        .await
+        .unwrap();
+    backend
+        .did_open(DidOpenTextDocumentParams {
+            text_document: TextDocumentItem {
+                uri: Url::from_file_path(&service_php).unwrap(),
+                language_id: "php".into(),
+                version: 1,
+                text: "<?php\nclass Unsaved {}\n".into(),
+            },
+        })
+        .await;
+    backend.initialized(InitializedParams {}).await;
+
+    let found = |query: &'static str| async move {
+        let symbols = backend
+            .symbol(WorkspaceSymbolParams {
+                query: query.into(),
+                work_done_progress_params: Default::default(),
+                partial_result_params: Default::default(),
+            })
+            .await
+            .unwrap()
+            .unwrap_or_default();
+        symbols.iter().any(|s| s.name == query)
+    };
+    let mut settled = false;
+    for _ in 0..100 {
+        if found("Other").await && found("Unsaved").await && !found("OnDisk").await {
+            settled = true;
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    }
+    assert!(settled, "open document replaced by its file on disk");
+}
+
+#[tokio::test]
+async fn workspace_symbol_fuzzy_search() {
+    let (service, _) = LspService::new(Backend::new);
+    let backend = service.inner();
+    backend
+        .initialize(InitializeParams::default())
+        .await
+        .unwrap();
+    let uri = Url::parse("file:///repo.php").unwrap();
+    let text = "<?php\nnamespace App;\nclass UserRepository { public function findAll() {} }\nclass UnusedResult {}\nfunction strlen_or_zero($s) {}\n";
+    backend
+        .did_open(DidOpenTextDocumentParams {
+            text_document: TextDocumentItem {
+                uri: uri.clone(),
+                language_id: "php".into(),
+                version: 1,
+                text: text.into(),
+            },
+        })
+        .await;
+    let search = |query: &str| WorkspaceSymbolParams {
+        query: query.into(),
+        work_done_progress_params: Default::default(),
+        partial_result_params: Default::default(),
+    };
+
+    let symbols = backend.symbol(search("UsRe")).await.unwrap().unwrap();
+    assert_eq!(symbols[0].name, "UserRepository");
+    assert_eq!(symbols[0].container_name.as_deref(), Some("App"));
+    assert_eq!(symbols[0].location.uri, uri);
+    assert!(symbols.iter().any(|s| s.name == "UnusedResult"));
+
+    let symbols = backend.symbol(search("fiAl")).await.unwrap().unwrap();
+    assert_eq!(symbols[0].name, "findAll");
+    assert_eq!(
+        symbols[0].container_name.as_deref(),
+        Some("App\\UserRepository")
+    );
+
+    // project code comes before the bundled stubs, even for a weaker match
+    let symbols = backend.symbol(search("strlen")).await.unwrap().unwrap();
+    assert_eq!(symbols[0].name, "strlen_or_zero");
+    assert!(
+        symbols
+            .iter()
+            .any(|s| s.name == "strlen" && s.location.uri.scheme() == "phppp-stub")
+    );
+
+    let symbols = backend.symbol(search("")).await.unwrap().unwrap();
+    assert!(symbols.len() <= 256);
+}
+
+#[tokio::test]
+async fn closing_a_document_goes_back_to_the_indexed_files() {
+    let dir = tempfile::tempdir().unwrap();
+    let service_php = dir.path().join("Service.php");
+    std::fs::write(&service_php, "<?php\nclass OnDisk {}\n").unwrap();
+    let notes = dir.path().join("notes.txt");
+    std::fs::write(&notes, "<?php\nclass InNotes {}\n").unwrap();
+    let (service, _) = LspService::new(Backend::new);
+    let backend = service.inner();
+    backend
+        .initialize(InitializeParams {
+            root_uri: Some(Url::from_directory_path(dir.path()).unwrap()),
+            ..InitializeParams::default()
+        })
+        .await
+        .unwrap();
+    let found = |query: &'static str| async move {
+        let symbols = backend
+            .symbol(WorkspaceSymbolParams {
+                query: query.into(),
+                work_done_progress_params: Default::default(),
+                partial_result_params: Default::default(),
+            })
+            .await
+            .unwrap()
+            .unwrap_or_default();
+        symbols.iter().any(|s| s.name == query)
+    };
+    for (path, text) in [
+        (&service_php, "<?php\nclass Unsaved {}\n"),
+        (&notes, "<?php\nclass InNotes {}\n"),
+    ] {
+        backend
+            .did_open(DidOpenTextDocumentParams {
+                text_document: TextDocumentItem {
+                    uri: Url::from_file_path(path).unwrap(),
+                    language_id: "php".into(),
+                    version: 1,
+                    text: text.into(),
+                },
+            })
+            .await;
+    }
+    assert!(found("Unsaved").await && found("InNotes").await);
+
+    for path in [&service_php, &notes] {
+        backend
+            .did_close(DidCloseTextDocumentParams {
+                text_document: TextDocumentIdentifier {
+                    uri: Url::from_file_path(path).unwrap(),
+                },
+            })
+            .await;
+    }
+    // unsaved edits are dropped, and files the workspace doesn't index
+    // leave the index with their document
+    assert!(found("OnDisk").await);
+    assert!(!found("Unsaved").await);
+    assert!(!found("InNotes").await);
+}
+
+#[tokio::test]
+async fn document_symbol_returns_outline() {
+    let (service, _) = LspService::new(Backend::new);
+    let backend = service.inner();
+    let uri = Url::parse("file:///outline.php").unwrap();
+    backend
+        .did_open(DidOpenTextDocumentParams {
+            text_document: TextDocumentItem {
+                uri: uri.clone(),
+                language_id: "php".into(),
+                version: 1,
+                text: "<?php\nnamespace App;\nclass A { public function run(int $n): void {} }\n"
+                    .into(),
+            },
+        })
+        .await;
+    let resp = backend
+        .document_symbol(DocumentSymbolParams {
+            text_document: TextDocumentIdentifier { uri: uri.clone() },
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        })
+        .await
+        .unwrap();
+    let symbols = match resp.unwrap() {
+        DocumentSymbolResponse::Nested(symbols) => symbols,
+        _ => panic!("unexpected response"),
+    };
+    assert_eq!(symbols[0].name, "App");
+    let class = &symbols[0].children.as_ref().unwrap()[0];
+    assert_eq!(class.name, "A");
+    let run = &class.children.as_ref().unwrap()[0];
+    assert_eq!(run.name, "run");
+    assert_eq!(run.detail.as_deref(), Some("public (int $n): void"));
+}
+
+#[tokio::test]
+async fn completes_and_resolves_members_through_inferred_types() {
+    let (service, _) = LspService::new(Backend::new);
+    let backend = service.inner();
+    let uri = Url::parse("file:///test.php").unwrap();
+    let text = r#"<?php
+class Avatar { public $url; public function resize() {} }
+class Profile { public function avatar(): Avatar {} }
+class User { public function profile(): Profile {} }
+$user = new User();
+$user->profile()->avatar();
+$user->profile()->avatar()->
+"#;
+    backend
+        .did_open(DidOpenTextDocumentParams {
+            text_document: TextDocumentItem {
+                uri: uri.clone(),
+                language_id: "php".into(),
+                version: 1,
+                text: text.into(),
+            },
+        })
+        .await;
+
+    let position = |line, character| TextDocumentPositionParams {
+        text_document: TextDocumentIdentifier { uri: uri.clone() },
+        position: Position { line, character },
+    };
+    let resp = backend
+        .goto_definition(GotoDefinitionParams {
+            text_document_position_params: position(5, 20),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        })
+        .await
+        .unwrap();
+    match resp.unwrap() {
+        GotoDefinitionResponse::Scalar(loc) => assert_eq!(loc.range.start.line, 2),
+        _ => panic!("unexpected response"),
+    }
+
+    let resp = backend
+        .completion(CompletionParams {
+            text_document_position: position(6, 28),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+            context: None,
+        })
+        .await
+        .unwrap()
+        .unwrap();
+    let CompletionResponse::Array(items) = resp else {
+        panic!("unexpected");
+    };
+    let labels: Vec<&str> = items.iter().map(|i| i.label.as_str()).collect();
+    assert_eq!(labels, ["resize", "url"]);
+}
+
+#[tokio::test]
+async fn completion_marks_deprecated_symbols() {
+    let (service, _) = LspService::new(Backend::new);
+    let backend = service.inner();
+    let uri = Url::parse("file:///test.php").unwrap();
+    let text = "<?php\n/**\n * Old helper.\n * @deprecated\n */\nfunction old() {}\nfunction current() {}\n";
+    backend
+        .did_open(DidOpenTextDocumentParams {
+            text_document: TextDocumentItem {
+                uri: uri.clone(),
+                language_id: "php".into(),
+                version: 1,
+                text: text.into(),
+            },
+        })
+        .await;
+    let resp = backend
+        .completion(CompletionParams {
+            text_document_position: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier { uri: uri.clone() },
+                position: Position::new(7, 0),
+            },
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+            context: None,
+        })
+        .await
+        .unwrap()
+        .unwrap();
+    let CompletionResponse::Array(items) = resp else {
+        panic!("unexpected");
+    };
+    let old = items.iter().find(|i| i.label == "old").unwrap();
+    assert_eq!(old.tags, Some(vec![CompletionItemTag::DEPRECATED]));
+    assert_eq!(
+        old.documentation,
+        Some(Documentation::String("Old helper.".into()))
+    );
+    let current = items.iter().find(|i| i.label == "current").unwrap();
+    assert_eq!(current.tags, None);
+}
+
+#[tokio::test]
+async fn signature_help_for_constructors_and_methods() {
+    let (service, _) = LspService::new(Backend::new);
+    let backend = service.inner();
+    let uri = Url::parse("file:///test.php").unwrap();
+    let text = r#"<?php
+class User {
+    public function __construct(string $name, int $age = 0) {}
+    public function posts(int $limit, string $order = 'id') {}
+    public static function find(int $id) {}
+}
+$user = new User('a');
+$user->posts(order: 'x', 0);
+User::find(1);
+new User('a', 
+"#;
+    backend
+        .did_open(DidOpenTextDocumentParams {
+            text_document: TextDocumentItem {
+                uri: uri.clone(),
+                language_id: "php".into(),
+                version: 1,
+                text: text.into(),
+            },
+        })
+        .await;
+
+    let help = |line, character| {
+        backend.signature_help(SignatureHelpParams {
+            context: None,
+            text_document_position_params: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier { uri: uri.clone() },
+                position: Position { line, character },
+            },
+            work_done_progress_params: Default::default(),
+        })
+    };
+    let resp = help(9, 14).await.unwrap().unwrap();
+    assert_eq!(
+        resp.signatures[0].label,
+        "public function __construct(string $name, int $age = 0)"
+    );
+    assert_eq!(resp.active_parameter, Some(1));
+
+    let resp = help(7, 25).await.unwrap().unwrap();
+    assert_eq!(
+        resp.signatures[0].label,
+        "public function posts(int $limit, string $order = 'id')"
+    );
+    assert_eq!(resp.active_parameter, Some(0));
+
+    let resp = help(8, 12).await.unwrap().unwrap();
+    assert_eq!(
+        resp.signatures[0].label,
+        "public static function find(int $id)"
+    );
+    assert_eq!(resp.active_parameter, Some(0));
+
+    assert!(help(1, 0).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn signature_help_after_multibyte_text() {
+    let (service, _) = LspService::new(Backend::new);
+    let backend = service.inner();
+    let uri = Url::parse("file:///test.php").unwrap();
+    let text = "<?php\nfunction foo(string $s) {}\nfoo(\"é";
+    backend
+        .did_open(DidOpenTextDocumentParams {
+            text_document: TextDocumentItem {
+                uri: uri.clone(),
+                language_id: "php".into(),
+                version: 1,
+                text: text.into(),
+            },
+        })
+        .await;
+    let resp = backend
+        .signature_help(SignatureHelpParams {
+            context: None,
+            text_document_position_params: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier { uri: uri.clone() },
+                position: Position::new(2, 6),
+            },
+            work_done_progress_params: Default::default(),
+        })
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(resp.signatures[0].label, "function foo(string $s)");
+    assert_eq!(resp.active_parameter, Some(0));
 }
@@ -0,0 +1,137 @@
+use tower_lsp::lsp_types::{DocumentSymbol, Position, SymbolKind};
+
+use phppp::{outline, parser};
+
+fn outline(src: &str) -> Vec<DocumentSymbol> {
+    let ast = parser::parse_php(src);
+    outline::document_symbols(src, &ast)
+}
+
+fn children(symbol: &DocumentSymbol) -> &[DocumentSymbol] {
+    symbol.children.as_deref().unwrap_or_default()
+}
+
+#[test]
+fn classes_nest_under_namespaces() {
+    let src = r#"<?php
+namespace App\Models;
+
+use Foo\Bar;
+
+abstract class User extends Model implements \JsonSerializable {
+    public const TABLE = 'users';
+    private static ?int $count = 0;
+
+    public function __construct(private readonly int $id) {}
+
+    abstract protected function find(
+        int $id,
+        string ...$columns
+    ): ?static;
+}
+
+function helper(): void {}
+"#;
+    let symbols = outline(src);
+    assert_eq!(symbols.len(), 1);
+    let namespace = &symbols[0];
+    assert_eq!(namespace.name, "App\\Models");
+    assert_eq!(namespace.kind, SymbolKind::NAMESPACE);
+    assert_eq!(namespace.range.start, Position::new(1, 0));
+    assert_eq!(namespace.range.end, Position::new(17, 26));
+
+    let names: Vec<_> = children(namespace)
+        .iter()
+        .map(|s| s.name.as_str())
+        .collect();
+    assert_eq!(names, vec!["User", "helper"]);
+    let class = &children(namespace)[0];
+    assert_eq!(class.kind, SymbolKind::CLASS);
+    assert_eq!(
+        class.detail.as_deref(),
+        Some("abstract extends Model implements \\JsonSerializable")
+    );
+    assert_eq!(class.range.start, Position::new(5, 0));
+    assert_eq!(class.range.end, Position::new(15, 1));
+    assert_eq!(class.selection_range.start, Position::new(5, 15));
+    assert_eq!(class.selection_range.end, Position::new(5, 19));
+
+    let members: Vec<_> = children(class)
+        .iter()
+        .map(|s| (s.name.as_str(), s.kind, s.detail.as_deref()))
+        .collect();
+    assert_eq!(
+        members,
+        vec![
+            ("TABLE", SymbolKind::CONSTANT, Some("public = 'users'")),
+            ("$count", SymbolKind::PROPERTY, Some("private static ?int")),
+            (
+                "__construct",
+                SymbolKind::CONSTRUCTOR,
+                Some("public (private readonly int $id)")
+            ),
+            ("$id", SymbolKind::PROPERTY, Some("private readonly int")),
+            (
+                "find",
+                SymbolKind::METHOD,
+                Some("abstract protected (int $id, string ...$columns): ?static")
+            ),
+        ]
+    );
+    let find = &children(class)[4];
+    assert_eq!(find.range.start, Position::new(11, 4));
+    assert_eq!(find.range.end, Position::new(14, 15));
+    assert_eq!(find.selection_range.start, Position::new(11, 32));
+}
+
+#[test]
+fn braced_namespaces_and_enums() {
+    let src = r#"<?php
+namespace Shop {
+    enum Status: string {
+        case Active = 'active';
+        case Closed = 'closed';
+    }
+    interface HasStatus { public function status(): Status; }
+    trait Timestamps { protected $createdAt; }
+}
+namespace {
+    const VERSION = '1.0';
+    if (!function_exists('polyfill')) {
+        function polyfill() {}
+    }
+}
+"#;
+    let symbols = outline(src);
+    let names: Vec<_> = symbols.iter().map(|s| (s.name.as_str(), s.kind)).collect();
+    assert_eq!(
+        names,
+        vec![
+            ("Shop", SymbolKind::NAMESPACE),
+            ("VERSION", SymbolKind::CONSTANT),
+            ("polyfill", SymbolKind::FUNCTION),
+        ]
+    );
+    let shop = children(&symbols[0]);
+    let status = &shop[0];
+    assert_eq!(status.kind, SymbolKind::ENUM);
+    assert_eq!(status.detail.as_deref(), Some(": string"));
+    let cases: Vec<_> = children(status)
+        .iter()
+        .map(|s| (s.name.as_str(), s.kind, s.detail.as_deref()))
+        .collect();
+    assert_eq!(
+        cases,
+        vec![
+            ("Active", SymbolKind::ENUM_MEMBER, Some("= 'active'")),
+            ("Closed", SymbolKind::ENUM_MEMBER, Some("= 'closed'")),
+        ]
+    );
+    assert_eq!(shop[1].kind, SymbolKind::INTERFACE);
+    assert_eq!(
+        children(&shop[1])[0].detail.as_deref(),
+        Some("public (): Status")
+    );
+    assert_eq!(children(&shop[2])[0].name, "$createdAt");
+    assert_eq!(symbols[1].detail.as_deref(), Some("= '1.0'"));
+}
@@ -1,4 +1,3 @@
-use bumpalo::Bump;
 use phppp::parser;
 use std::fs;
 
@@ -9,8 +8,7 @@ fn parse_all_examples() {
         let path = entry.path();
         if path.extension().map(|e| e == "php").unwrap_or(false) {
             let text = fs::read_to_string(&path).unwrap();
-            let bump = Bump::new();
-            let ast = parser::parse_php(&text, &bump);
+            let ast = parser::parse_php(&text);
             assert!(
                 !ast.0.root_node().has_error(),
                 "{} failed to parse",
@@ -0,0 +1,128 @@
+use phppp::phpdoc::{self, DocType};
+
+fn ty(text: &str) -> String {
+    phpdoc::parse_type(text)
+        .unwrap_or_else(|| panic!("`{}` didn't parse", text))
+        .to_string()
+}
+
+#[test]
+fn parses_type_syntax() {
+    assert_eq!(ty("?User"), "?User");
+    assert_eq!(ty("int | string|null"), "int|string|null");
+    assert_eq!(ty("Countable&Traversable"), "Countable&Traversable");
+    assert_eq!(ty("(A&B)|null"), "(A&B)|null");
+    assert_eq!(ty("array<int, User>"), "array<int, User>");
+    assert_eq!(ty("Collection<User>"), "Collection<User>");
+    assert_eq!(ty("User[][]"), "User[][]");
+    assert_eq!(ty("(int|string)[]"), "(int|string)[]");
+    assert_eq!(
+        ty("array{id: int, 'full name'?: string, tags: list<string>}"),
+        "array{id: int, 'full name'?: string, tags: list<string>}"
+    );
+    assert_eq!(ty("array{int, string}"), "array{int, string}");
+    assert_eq!(ty("callable(int, string): void"), "callable");
+    assert_eq!(ty("class-string<Model>"), "class-string<Model>");
+    assert!(phpdoc::parse_type("array<int").is_none());
+}
+
+#[test]
+fn flattens_types_into_hints() {
+    let hint = |text: &str| phpdoc::parse_type(text).unwrap().hint(&[]);
+    assert_eq!(hint("?User"), "?User");
+    assert_eq!(hint("Collection<User>|null"), "Collection|null");
+    assert_eq!(hint("User[]"), "array");
+    assert_eq!(hint("array{id: int}"), "array");
+    assert_eq!(hint("positive-int|class-string"), "int|string");
+    assert_eq!(hint("A|(B&C)"), "A|(B&C)");
+}
+
+#[test]
+fn parses_tags() {
+    let doc = phpdoc::parse(
+        r#"/**
+     * Find users.
+     *
+     * Looks them up by id,
+     * in the primary database.
+     *
+     * @template T of Model
+     * @param int|int[] $ids  the ids
+     * @param array{
+     *     limit: int,
+     *     order?: string
+     * } $options
+     * @param mixed ...$rest
+     * @return Collection<T>
+     * @throws NotFound when one is missing
+     * @deprecated
+     * @method static User|null find(int $id, string ...$columns) Find one
+     * @method save()
+     * @property-read string $name
+     * @unknown whatever
+     */"#,
+    );
+    assert_eq!(doc.summary, "Find users.");
+    assert_eq!(
+        doc.description,
+        "Looks them up by id,\nin the primary database."
+    );
+    assert_eq!(doc.templates[0].name, "T");
+    assert_eq!(
+        doc.templates[0].bound.as_ref().unwrap().to_string(),
+        "Model"
+    );
+
+    let ids = doc.param("$ids").unwrap();
+    assert_eq!(ids.ty.as_ref().unwrap().to_string(), "int|int[]");
+    assert_eq!(ids.description, "the ids");
+    let options = doc.param("$options").unwrap();
+    assert_eq!(
+        options.ty.as_ref().unwrap().to_string(),
+        "array{limit: int, order?: string}"
+    );
+    assert!(doc.param("$rest").is_some());
+    assert_eq!(
+        doc.returns
+            .as_ref()
+            .unwrap()
+            .ty
+            .as_ref()
+            .unwrap()
+            .to_string(),
+        "Collection<T>"
+    );
+    assert_eq!(doc.throws[0].description, "when one is missing");
+    assert_eq!(doc.deprecated.as_deref(), Some(""));
+
+    let find = &doc.methods[0];
+    assert!(find.is_static);
+    assert_eq!(find.name, "find");
+    assert_eq!(find.returns.as_ref().unwrap().to_string(), "User|null");
+    assert_eq!(find.params.len(), 2);
+    assert_eq!(find.params[1].name.as_deref(), Some("$columns"));
+    assert_eq!(find.description, "Find one");
+    let save = &doc.methods[1];
+    assert_eq!((save.name.as_str(), save.returns.is_none()), ("save", true));
+
+    assert_eq!(doc.properties[0].name.as_deref(), Some("$name"));
+    assert_eq!(doc.properties[0].line, 18);
+}
+
+#[test]
+fn qualifies_class_names_but_not_keywords_or_templates() {
+    let mut doc =
+        phpdoc::parse("/**\n * @template T\n * @param array<int, User>|T $a\n * @return int\n */");
+    doc.qualify(&[], &|name| format!("App\\{}", name));
+    assert_eq!(
+        doc.params[0].ty.as_ref().unwrap().to_string(),
+        "array<int, App\\User>|T"
+    );
+    assert_eq!(
+        doc.returns.as_ref().unwrap().ty,
+        Some(DocType::Named {
+            name: "int".to_string(),
+            args: Vec::new(),
+        })
+    );
+}
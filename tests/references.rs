@@ -0,0 +1,306 @@
+use tower_lsp::lsp_types::{Location, Position, Url};
+
+use phppp::analyzer::TypeCache;
+use phppp::indexer::{self, FileSymbols, GlobalIndex, Layer};
+use phppp::parser::{self, Ast};
+use phppp::references::{self, Target};
+use phppp::resolver::{DocumentContext, ImportTable};
+
+struct Doc {
+    uri: Url,
+    src: String,
+    ast: Ast,
+    symbols: FileSymbols,
+    imports: ImportTable,
+}
+
+impl Doc {
+    fn open(uri: &str, src: &str, index: &GlobalIndex) -> Self {
+        let uri = Url::parse(uri).unwrap();
+        let ast = parser::parse_php(src);
+        let symbols = indexer::extract_symbols(src, &ast, &uri);
+        index.insert(uri.clone(), symbols.clone());
+        let imports = ImportTable::build(src, &ast);
+        Doc {
+            uri,
+            src: src.to_string(),
+            ast,
+            symbols,
+            imports,
+        }
+    }
+
+    fn ctx(&self) -> DocumentContext<'_> {
+        DocumentContext {
+            uri: &self.uri,
+            src: &self.src,
+            ast: &self.ast,
+            symbols: &self.symbols,
+            imports: &self.imports,
+        }
+    }
+
+    fn target(&self, line: u32, character: u32, index: &GlobalIndex) -> Target {
+        let position = Position { line, character };
+        references::target_at(&self.ctx(), position, index, &TypeCache::new()).expect("no target")
+    }
+
+    fn find(&self, target: &Target, index: &GlobalIndex, declaration: bool) -> Vec<(u32, u32)> {
+        let found =
+            references::occurrences_in_document(target, &self.ctx(), index, &TypeCache::new());
+        starts(references::locations(&self.uri, found, declaration))
+    }
+}
+
+fn starts(locations: Vec<Location>) -> Vec<(u32, u32)> {
+    locations
+        .iter()
+        .map(|l| (l.range.start.line, l.range.start.character))
+        .collect()
+}
+
+#[test]
+fn ignores_longer_names_strings_and_comments() {
+    let index = indexer::new_index();
+    let doc = Doc::open(
+        "file:///a.php",
+        r#"<?php
+function foo() {}
+function foobar() {}
+foo();
+foobar();
+echo "foo"; // foo()
+"#,
+        &index,
+    );
+    let target = doc.target(1, 10, &index);
+    assert_eq!(doc.find(&target, &index, true), vec![(1, 9), (3, 0)]);
+    assert_eq!(doc.find(&target, &index, false), vec![(3, 0)]);
+}
+
+#[test]
+fn resolves_imports_and_aliases_in_unopened_files() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("b.php");
+    std::fs::write(
+        &path,
+        r#"<?php
+namespace Web;
+use App\User as Account;
+new Account();
+new \App\User();
+new User();
+"#,
+    )
+    .unwrap();
+    let index = indexer::new_index();
+    indexer::index_file(&path, &index).unwrap();
+    let doc = Doc::open(
+        "file:///a.php",
+        "<?php\nnamespace App;\nclass User {}\n",
+        &index,
+    );
+
+    let target = doc.target(2, 7, &index);
+    let uri = Url::from_file_path(&path).unwrap();
+    let found = references::occurrences_in_file(&target, &uri, &index);
+    assert_eq!(
+        starts(references::locations(&uri, found.clone(), false)),
+        vec![(2, 8), (3, 4), (4, 9)]
+    );
+
+    let edits = references::rename_edits(&target, &found, "Member");
+    let renamed: Vec<(u32, u32)> = edits
+        .iter()
+        .map(|e| (e.range.start.line, e.range.start.character))
+        .collect();
+    // `new Account()` keeps using the alias
+    assert_eq!(renamed, vec![(2, 8), (4, 9)]);
+    assert!(edits.iter().all(|e| e.new_text == "Member"));
+}
+
+#[test]
+fn static_members_resolve_through_their_class() {
+    let index = indexer::new_index();
+    let doc = Doc::open(
+        "file:///a.php",
+        r#"<?php
+class A { public static function make() {} }
+class B { public static function make() {} }
+A::make();
+B::make();
+"#,
+        &index,
+    );
+    let target = doc.target(3, 4, &index);
+    assert_eq!(doc.find(&target, &index, true), vec![(1, 33), (3, 3)]);
+}
+
+#[test]
+fn variables_stay_in_their_function() {
+    let index = indexer::new_index();
+    let doc = Doc::open(
+        "file:///a.php",
+        r#"<?php
+function a($x) {
+    $f = fn() => $x;
+    return $x;
+}
+function b() { $x = 1; return $x; }
+"#,
+        &index,
+    );
+    let target = doc.target(3, 12, &index);
+    assert_eq!(
+        doc.find(&target, &index, true),
+        vec![(1, 11), (2, 17), (3, 11)]
+    );
+    assert_eq!(doc.find(&target, &index, false), vec![(2, 17), (3, 11)]);
+}
+
+#[test]
+fn renames_variables_with_their_dollar() {
+    let index = indexer::new_index();
+    let doc = Doc::open(
+        "file:///a.php",
+        "<?php\nfunction a($x) { return $x; }\n",
+        &index,
+    );
+    let types = TypeCache::new();
+    let (target, range) =
+        references::prepare_rename(&doc.ctx(), Position::new(1, 25), &index, &types).unwrap();
+    assert_eq!(range.start, Position::new(1, 24));
+    let found = references::occurrences_in_document(&target, &doc.ctx(), &index, &types);
+    let edits = references::rename_edits(&target, &found, "y");
+    assert_eq!(edits.len(), 2);
+    assert!(edits.iter().all(|e| e.new_text == "$y"));
+}
+
+#[test]
+fn rename_rejects_builtins_vendor_code_and_unknown_names() {
+    let index = indexer::new_index();
+    let stub = Url::parse("phppp-stub:///standard.php").unwrap();
+    let src = "<?php function strlen($s) {}";
+    let ast = parser::parse_php(src);
+    index.insert_in(
+        stub.clone(),
+        indexer::extract_symbols(src, &ast, &stub),
+        Layer::Stub,
+    );
+    let vendor = Url::parse("file:///vendor/lib/Client.php").unwrap();
+    let src = "<?php namespace Lib; class Client {}";
+    let ast = parser::parse_php(src);
+    index.insert_in(
+        vendor.clone(),
+        indexer::extract_symbols(src, &ast, &vendor),
+        Layer::Vendor,
+    );
+
+    let doc = Doc::open(
+        "file:///a.php",
+        "<?php\nstrlen('x');\nnew \\Lib\\Client();\nmissing();\nclass A { function __construct() {} }\n",
+        &index,
+    );
+    let ctx = doc.ctx();
+    let types = TypeCache::new();
+    for (line, character) in [(1, 1), (2, 11), (3, 1), (4, 20)] {
+        let position = Position::new(line, character);
+        assert!(
+            references::prepare_rename(&ctx, position, &index, &types).is_err(),
+            "renamed at {}:{}",
+            line,
+            character
+        );
+    }
+}
+
+#[test]
+fn rename_rejects_promoted_properties() {
+    let index = indexer::new_index();
+    let doc = Doc::open(
+        "file:///a.php",
+        "<?php\nclass User {\n    public function __construct(public string $name, string $age) { echo $name; }\n    function greet() { return $this->name; }\n}\n",
+        &index,
+    );
+    let ctx = doc.ctx();
+    let types = TypeCache::new();
+    // the parameter, its use in the constructor body and the property
+    for (line, character) in [(2, 47), (2, 74), (3, 38)] {
+        let position = Position::new(line, character);
+        assert_eq!(
+            references::prepare_rename(&ctx, position, &index, &types).unwrap_err(),
+            "Promoted constructor properties can't be renamed",
+            "renamed at {}:{}",
+            line,
+            character
+        );
+    }
+    // plain parameters of the constructor still can be
+    assert!(references::prepare_rename(&ctx, Position::new(2, 61), &index, &types).is_ok());
+}
+
+#[test]
+fn validates_new_names() {
+    assert!(references::is_valid_name("renamed_2"));
+    assert!(references::is_valid_name("$count"));
+    assert!(references::is_valid_name("Ärger"));
+    assert!(!references::is_valid_name(""));
+    assert!(!references::is_valid_name("2fast"));
+    assert!(!references::is_valid_name("App\\User"));
+    assert!(!references::is_valid_name("$this"));
+    assert!(!references::is_valid_name("foo bar"));
+}
+
+#[test]
+fn this_and_self_members_are_references() {
+    let index = indexer::new_index();
+    let doc = Doc::open(
+        "file:///a.php",
+        r#"<?php
+class A {
+    private $count;
+    public function add() {
+        $this->count++;
+        self::add();
+        $other->add();
+    }
+}
+"#,
+        &index,
+    );
+    let add = doc.target(3, 21, &index);
+    assert_eq!(doc.find(&add, &index, true), vec![(3, 20), (5, 14)]);
+    let count = doc.target(4, 16, &index);
+    assert_eq!(doc.find(&count, &index, true), vec![(2, 12), (4, 15)]);
+}
+
+#[test]
+fn members_of_typed_objects_are_references() {
+    let index = indexer::new_index();
+    let user = Doc::open(
+        "file:///user.php",
+        r#"<?php
+class User { public $name; function save() {} }
+class Other { public $name; function save() {} }
+"#,
+        &index,
+    );
+    let doc = Doc::open(
+        "file:///a.php",
+        r#"<?php
+$u = new User();
+$u->save();
+echo $u->name;
+function f(User $x) { $x->save(); }
+(new Other())->save();
+$unknown->save();
+"#,
+        &index,
+    );
+    let save = doc.target(2, 5, &index);
+    assert_eq!(user.find(&save, &index, true), vec![(1, 36)]);
+    assert_eq!(doc.find(&save, &index, true), vec![(2, 4), (4, 26)]);
+    let name = doc.target(3, 10, &index);
+    assert_eq!(user.find(&name, &index, true), vec![(1, 20)]);
+    assert_eq!(doc.find(&name, &index, true), vec![(3, 9)]);
+}
@@ -1,4 +1,3 @@
-use bumpalo::Bump;
 use tower_lsp::lsp_types::{Position, Url};
 
 use phppp::{indexer, parser, resolver};
@@ -15,12 +14,11 @@ use Bar\someFunc as aliasFunc;
 
 aliasFunc();
 "#;
-    let bump = Bump::new();
-    let ast_a = parser::parse_php(src_a, &bump);
+    let ast_a = parser::parse_php(src_a);
     let uri_a = Url::parse("file:///a.php").unwrap();
     let symbols_a = indexer::extract_symbols(src_a, &ast_a, &uri_a);
 
-    let ast_b = parser::parse_php(src_b, &bump);
+    let ast_b = parser::parse_php(src_b);
     let uri_b = Url::parse("file:///b.php").unwrap();
     let symbols_b = indexer::extract_symbols(src_b, &ast_b, &uri_b);
 
@@ -46,8 +44,7 @@ function foo($bar) {
     echo $bar;
 }
 "#;
-    let bump = Bump::new();
-    let ast = parser::parse_php(src, &bump);
+    let ast = parser::parse_php(src);
     let uri = Url::parse("file:///c.php").unwrap();
     let symbols = indexer::extract_symbols(src, &ast, &uri);
     let index = indexer::new_index();
@@ -63,3 +60,318 @@ function foo($bar) {
     assert_eq!(resolved.kind, indexer::SymbolKind::Variable);
     assert_eq!(resolved.location.uri, uri);
 }
+
+#[test]
+fn resolve_in_second_namespace() {
+    let src = r#"<?php
+namespace First;
+function helper() {}
+
+namespace Second;
+function helper() {}
+helper();
+"#;
+    let ast = parser::parse_php(src);
+    let uri = Url::parse("file:///multi.php").unwrap();
+    let symbols = indexer::extract_symbols(src, &ast, &uri);
+    let index = indexer::new_index();
+    index.insert(uri.clone(), symbols.clone());
+
+    let pos = Position {
+        line: 6,
+        character: 0,
+    };
+    let resolved = resolver::resolve_symbol("helper", &uri, pos, src, &ast, &symbols, &index)
+        .expect("symbol not resolved");
+    assert_eq!(resolved.name, "Second\\helper");
+    assert_eq!(resolved.location.range.start.line, 5);
+}
+
+#[test]
+fn import_table_from_use_declarations() {
+    let src = r#"<?php
+namespace App;
+use Domain\{User, Models\Post as Article, function helper, const LIMIT};
+use function Util\format, Util\trim as strip;
+use const Config\DEBUG;
+use First, Second\Thing;
+
+class Repo {
+    use SomeTrait;
+}
+"#;
+    let ast = parser::parse_php(src);
+    let table = resolver::ImportTable::build(src, &ast);
+    let imports = table.imports_at(tree_sitter::Point { row: 8, column: 0 });
+
+    assert_eq!(imports.classes["User"], "Domain\\User");
+    assert_eq!(imports.classes["Article"], "Domain\\Models\\Post");
+    assert_eq!(imports.classes["First"], "First");
+    assert_eq!(imports.classes["Thing"], "Second\\Thing");
+    assert!(!imports.classes.contains_key("SomeTrait"));
+    assert_eq!(imports.functions["helper"], "Domain\\helper");
+    assert_eq!(imports.functions["format"], "Util\\format");
+    assert_eq!(imports.functions["strip"], "Util\\trim");
+    assert_eq!(imports.constants["LIMIT"], "Domain\\LIMIT");
+    assert_eq!(imports.constants["DEBUG"], "Config\\DEBUG");
+}
+
+#[test]
+fn imports_are_scoped_per_namespace() {
+    let src = r#"<?php
+namespace A {
+    use Lib\Widget;
+}
+namespace B {
+    use Other\Widget;
+    new Widget();
+}
+"#;
+    let ast = parser::parse_php(src);
+    let uri = Url::parse("file:///scoped.php").unwrap();
+    let symbols = indexer::extract_symbols(src, &ast, &uri);
+
+    let lib = r#"<?php
+namespace Other;
+class Widget {}
+"#;
+    let lib_ast = parser::parse_php(lib);
+    let lib_uri = Url::parse("file:///widget.php").unwrap();
+    let index = indexer::new_index();
+    index.insert(
+        lib_uri.clone(),
+        indexer::extract_symbols(lib, &lib_ast, &lib_uri),
+    );
+
+    let pos = Position {
+        line: 6,
+        character: 9,
+    };
+    let resolved = resolver::resolve_symbol("Widget", &uri, pos, src, &ast, &symbols, &index)
+        .expect("class not resolved");
+    assert_eq!(resolved.name, "Other\\Widget");
+    assert_eq!(resolved.location.uri, lib_uri);
+}
+
+#[test]
+fn unqualified_function_falls_back_to_global() {
+    let helpers = r#"<?php
+function global_helper() {}
+const GLOBAL_LIMIT = 10;
+class GlobalClass {}
+"#;
+    let src = r#"<?php
+namespace App;
+global_helper();
+echo GLOBAL_LIMIT;
+new GlobalClass();
+"#;
+    let helpers_ast = parser::parse_php(helpers);
+    let helpers_uri = Url::parse("file:///helpers.php").unwrap();
+    let ast = parser::parse_php(src);
+    let uri = Url::parse("file:///app.php").unwrap();
+    let symbols = indexer::extract_symbols(src, &ast, &uri);
+    let index = indexer::new_index();
+    index.insert(
+        helpers_uri.clone(),
+        indexer::extract_symbols(helpers, &helpers_ast, &helpers_uri),
+    );
+
+    let func = resolver::resolve_symbol(
+        "global_helper",
+        &uri,
+        Position {
+            line: 2,
+            character: 0,
+        },
+        src,
+        &ast,
+        &symbols,
+        &index,
+    )
+    .expect("function not resolved");
+    assert_eq!(func.name, "global_helper");
+
+    let constant = resolver::resolve_symbol(
+        "GLOBAL_LIMIT",
+        &uri,
+        Position {
+            line: 3,
+            character: 5,
+        },
+        src,
+        &ast,
+        &symbols,
+        &index,
+    )
+    .expect("constant not resolved");
+    assert_eq!(constant.name, "GLOBAL_LIMIT");
+
+    let class = resolver::resolve_symbol(
+        "GlobalClass",
+        &uri,
+        Position {
+            line: 4,
+            character: 4,
+        },
+        src,
+        &ast,
+        &symbols,
+        &index,
+    );
+    assert!(class.is_none(), "classes must not fall back to global");
+}
+
+#[test]
+fn resolve_class_through_psr4_path() {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path();
+    std::fs::create_dir_all(root.join("src/Models")).unwrap();
+    std::fs::write(
+        root.join("src/Models/User.php"),
+        "<?php\nnamespace App\\Models;\nclass User {\n    public function save() {}\n}\n",
+    )
+    .unwrap();
+    std::fs::write(
+        root.join("composer.json"),
+        r#"{"autoload": {"psr-4": {"App\\": "src/"}}}"#,
+    )
+    .unwrap();
+    let index = indexer::new_index();
+    index.set_autoload(phppp::composer::load_autoload(root).unwrap());
+
+    let src = r#"<?php
+namespace App\Http;
+use App\Models\User;
+new User();
+User::save();
+"#;
+    let ast = parser::parse_php(src);
+    let uri = Url::parse("file:///controller.php").unwrap();
+    let symbols = indexer::extract_symbols(src, &ast, &uri);
+
+    let pos = Position {
+        line: 3,
+        character: 5,
+    };
+    let resolved = resolver::resolve_symbol("User", &uri, pos, src, &ast, &symbols, &index)
+        .expect("class not located through PSR-4");
+    assert_eq!(resolved.name, "App\\Models\\User");
+    assert!(
+        resolved
+            .location
+            .uri
+            .path()
+            .ends_with("src/Models/User.php")
+    );
+    assert_eq!(index.len(), 1);
+
+    let pos = Position {
+        line: 4,
+        character: 7,
+    };
+    let method = resolver::resolve_symbol("User::save", &uri, pos, src, &ast, &symbols, &index)
+        .expect("method not resolved");
+    assert_eq!(method.name, "App\\Models\\User::save");
+}
+
+#[test]
+fn resolve_reports_every_definition_in_best_layer() {
+    let polyfill = r#"<?php
+if (!function_exists('array_is_list')) {
+    function array_is_list(array $a) {}
+}
+"#;
+    let other = "<?php\nfunction array_is_list(array $a) {}\n";
+    let src = "<?php\narray_is_list([]);\n";
+    let index = indexer::new_index();
+    let mut uris = Vec::new();
+    for (i, text) in [polyfill, other].into_iter().enumerate() {
+        let uri = Url::parse(&format!("file:///polyfill{}.php", i)).unwrap();
+        let ast = parser::parse_php(text);
+        index.insert(uri.clone(), indexer::extract_symbols(text, &ast, &uri));
+        uris.push(uri);
+    }
+    let stub = Url::parse("phppp-stub:///standard/standard.php").unwrap();
+    let ast = parser::parse_php(other);
+    index.insert_in(
+        stub.clone(),
+        indexer::extract_symbols(other, &ast, &stub),
+        indexer::Layer::Stub,
+    );
+
+    let ast = parser::parse_php(src);
+    let uri = Url::parse("file:///main.php").unwrap();
+    let symbols = indexer::extract_symbols(src, &ast, &uri);
+    let pos = Position {
+        line: 1,
+        character: 2,
+    };
+    let resolved =
+        resolver::resolve_symbol("array_is_list", &uri, pos, src, &ast, &symbols, &index)
+            .expect("symbol not resolved");
+    let mut found = vec![resolved.location.uri.clone()];
+    found.extend(resolved.alternatives.iter().map(|l| l.uri.clone()));
+    found.sort();
+    assert_eq!(found, uris);
+}
+
+#[test]
+fn resolve_members_through_this_self_static_and_parent() {
+    let base = r#"<?php
+namespace Lib;
+class Model {
+    public function save() {}
+}
+"#;
+    let src = r#"<?php
+namespace App;
+use Lib\Model as Base;
+trait Greets { public function hello() {} }
+interface HasName { const PREFIX = 'x'; }
+class User extends Base implements HasName {
+    use Greets;
+    public $name;
+    public static function create() {}
+    public function save() {
+        $this->hello();
+        self::PREFIX;
+        static::create();
+        parent::save();
+        $this->name;
+        $this?->save();
+    }
+}
+"#;
+    let index = indexer::new_index();
+    let base_uri = Url::parse("file:///lib/Model.php").unwrap();
+    let ast = parser::parse_php(base);
+    index.insert(
+        base_uri.clone(),
+        indexer::extract_symbols(base, &ast, &base_uri),
+    );
+    let uri = Url::parse("file:///app/User.php").unwrap();
+    let ast = parser::parse_php(src);
+    let symbols = indexer::extract_symbols(src, &ast, &uri);
+    index.insert(uri.clone(), symbols.clone());
+
+    let resolve_at = |line: u32, character: u32| {
+        let point = tree_sitter::Point {
+            row: line as usize,
+            column: character as usize,
+        };
+        let node = resolver::name_node_at(&ast, point).unwrap();
+        let name = resolver::reference_name(node, src).unwrap();
+        let pos = Position { line, character };
+        resolver::resolve_symbol(&name, &uri, pos, src, &ast, &symbols, &index)
+            .map(|r| (r.name, r.location.uri))
+    };
+    let expect = |name: &str, uri: &Url| Some((name.to_string(), uri.clone()));
+
+    assert_eq!(resolve_at(10, 16), expect("App\\Greets::hello", &uri));
+    assert_eq!(resolve_at(11, 15), expect("App\\HasName::PREFIX", &uri));
+    assert_eq!(resolve_at(12, 17), expect("App\\User::create", &uri));
+    assert_eq!(resolve_at(13, 17), expect("Lib\\Model::save", &base_uri));
+    assert_eq!(resolve_at(14, 16), expect("App\\User::$name", &uri));
+    assert_eq!(resolve_at(15, 17), expect("App\\User::save", &uri));
+}
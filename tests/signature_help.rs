@@ -0,0 +1,112 @@
+use tower_lsp::lsp_types::{Location, ParameterLabel, Position, Range, Url};
+
+use phppp::parser;
+use phppp::signature_help::{self, ActiveArgument};
+
+/// The active argument at the `|` in `src`, closing the call first when
+/// its parenthesis is missing.
+fn active_at(src: &str) -> ActiveArgument {
+    let offset = src.find('|').unwrap();
+    let src = src.replace('|', "");
+    let ast = parser::parse_php(&src);
+    if let Some(arguments) = signature_help::arguments_at(&ast, offset) {
+        return signature_help::active_argument(arguments, offset, &src);
+    }
+    let (src, ast) = signature_help::close_call(&src, offset);
+    let arguments = signature_help::arguments_at(&ast, offset).unwrap();
+    signature_help::active_argument(arguments, offset, &src)
+}
+
+const SRC: &str = r#"<?php
+/**
+ * Load users.
+ *
+ * @param int $limit how many
+ */
+function load(int $limit, string $order = 'id', bool ...$flags) {}
+"#;
+
+fn location() -> Location {
+    Location {
+        uri: Url::parse("file:///a.php").unwrap(),
+        range: Range::new(Position::new(6, 9), Position::new(6, 13)),
+    }
+}
+
+#[test]
+fn counts_commas_before_the_cursor() {
+    assert_eq!(active_at("<?php foo(|);").index, 0);
+    assert_eq!(active_at("<?php foo(1, |);").index, 1);
+    assert_eq!(active_at("<?php foo(1, bar(2, 3), |);").index, 2);
+    assert_eq!(active_at("<?php foo(1, bar(2, |), 4);").index, 1);
+    assert_eq!(active_at("<?php foo(1, |\n$x = 1;").index, 1);
+    assert_eq!(active_at("<?php new User(1, 2, |").index, 2);
+    assert_eq!(active_at("<?php User::find(|\necho 1;").index, 0);
+    assert!(signature_help::arguments_at(&parser::parse_php("<?php foo(1);"), 12).is_none());
+}
+
+#[test]
+fn closes_calls_after_multibyte_text() {
+    let src = "<?php foo(\"é";
+    let (closed, _) = signature_help::close_call(src, src.len() - 1);
+    assert_eq!(closed, "<?php foo(\")é");
+    assert_eq!(active_at("<?php foo('é', |").index, 1);
+}
+
+#[test]
+fn reads_named_arguments() {
+    let active = active_at("<?php foo(1, limit: 10, order: |);");
+    assert_eq!(active.name.as_deref(), Some("order"));
+    assert_eq!(active.positional, 1);
+    assert_eq!(active.named, ["limit"]);
+}
+
+#[test]
+fn picks_the_active_parameter() {
+    let ast = parser::parse_php(SRC);
+    let active = |text: &str| {
+        signature_help::signature(SRC, &ast, &location(), None, &active_at(text))
+            .unwrap()
+            .active_parameter
+    };
+    assert_eq!(active("<?php load(1, |);"), Some(1));
+    assert_eq!(active("<?php load(1, 'x', true, false, |);"), Some(2));
+    assert_eq!(active("<?php load(order: 'x', |);"), Some(0));
+    assert_eq!(active("<?php load(order: |);"), Some(1));
+    assert_eq!(active("<?php load(1, missing: |);"), None);
+}
+
+#[test]
+fn labels_parameters_within_the_signature() {
+    let phpdoc =
+        phppp::phpdoc::parse("/**\n * Load users.\n *\n * @param int $limit how many\n */");
+    let ast = parser::parse_php(SRC);
+    let sig = signature_help::signature(
+        SRC,
+        &ast,
+        &location(),
+        Some(&phpdoc),
+        &ActiveArgument::default(),
+    )
+    .unwrap();
+    assert_eq!(
+        sig.label,
+        "function load(int $limit, string $order = 'id', bool ...$flags)"
+    );
+    let labels: Vec<&str> = sig
+        .parameters
+        .as_ref()
+        .unwrap()
+        .iter()
+        .map(|p| match p.label {
+            ParameterLabel::LabelOffsets([start, end]) => &sig.label[start as usize..end as usize],
+            ParameterLabel::Simple(_) => panic!("expected offsets"),
+        })
+        .collect();
+    assert_eq!(
+        labels,
+        ["int $limit", "string $order = 'id'", "bool ...$flags"]
+    );
+    assert!(sig.parameters.unwrap()[0].documentation.is_some());
+    assert!(sig.documentation.is_some());
+}
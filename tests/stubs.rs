@@ -0,0 +1,29 @@
+use phppp::{indexer, parser, stubs};
+
+#[test]
+fn bundled_stubs_parse_and_index() {
+    let index = indexer::new_index();
+    stubs::index_stubs(&stubs::extensions(), &index);
+    for entry in index.iter() {
+        let uri = entry.key();
+        let src = stubs::source(uri).expect("stub source missing");
+        let ast = parser::parse_php(src);
+        assert!(!ast.0.root_node().has_error(), "{} failed to parse", uri);
+    }
+    let has = |fqn: &str| index.iter().any(|e| e.value().contains_key(fqn));
+    assert!(has("array_map"));
+    assert!(has("DateTimeImmutable"));
+    assert!(has("PDO::prepare"));
+    assert!(has("Throwable"));
+    assert!(has("mb_strlen"));
+}
+
+#[test]
+fn only_enabled_stubs_are_indexed() {
+    let index = indexer::new_index();
+    stubs::index_stubs(&["core".to_string(), "pdo".to_string()], &index);
+    let has = |fqn: &str| index.iter().any(|e| e.value().contains_key(fqn));
+    assert!(has("strlen"));
+    assert!(has("PDO"));
+    assert!(!has("mb_strlen"));
+}